@@ -0,0 +1,74 @@
+/**
+ * This shader blends the last frame of the outgoing scene into the incoming
+ * scene while a scene transition plays. Its output is passed on to the pixel
+ * art shader like any other frame.
+ */
+
+#version 330
+
+// Fragment texture UV coordinate
+in vec2 fragTexCoord;
+
+// The incoming scene
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+// The last frame of the outgoing scene
+uniform sampler2D previousScene;
+
+// Viewport size
+uniform vec2 viewport;
+
+// How far along the transition is, from 0 to 1
+uniform float progress;
+
+// Which effect to play. 1 is a fade, 2 is a glitch wipe, and 3 is a pixel dissolve
+uniform float effect;
+
+// Output fragment color
+out vec4 finalColor;
+
+// Size of the rows and blocks used by the glitch effects, in pixels
+const float blockSize = 12.0;
+
+// Cheap pseudo-random number from a position
+float random(vec2 position) {
+  return fract(sin(dot(position, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+  vec4 previous = texture(previousScene, fragTexCoord);
+  vec4 next = texture(texture0, fragTexCoord);
+
+  if (effect < 1.5) {
+    // Fade to black, then fade the new scene in
+    vec4 black = vec4(0.0, 0.0, 0.0, 1.0);
+    if (progress < 0.5) {
+      finalColor = mix(previous, black, progress * 2.0);
+    } else {
+      finalColor = mix(black, next, progress * 2.0 - 1.0);
+    }
+  } else if (effect < 2.5) {
+    // Wipe left to right, with every row a little ahead or behind
+    float row = floor(fragTexCoord.y * viewport.y / blockSize);
+    float jitter = random(vec2(row, floor(progress * 20.0))) * 0.15;
+    float edge = progress * 1.3 - jitter;
+
+    if (fragTexCoord.x < edge - 0.05) {
+      finalColor = next;
+    } else if (fragTexCoord.x > edge) {
+      finalColor = previous;
+    } else {
+      // Split the colors right at the edge of the wipe
+      float split = 6.0 / viewport.x;
+      finalColor = vec4(
+          texture(texture0, fragTexCoord + vec2(split, 0.0)).r,
+          texture(previousScene, fragTexCoord).g,
+          texture(texture0, fragTexCoord - vec2(split, 0.0)).b, 1.0);
+    }
+  } else {
+    // Swap each block over at its own random time
+    vec2 block = floor(fragTexCoord * viewport / blockSize);
+    finalColor = random(block) < progress ? next : previous;
+  }
+}
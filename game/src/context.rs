@@ -1,12 +1,30 @@
-use std::{cell::RefCell, collections::HashMap, sync::mpsc::Sender};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::mpsc::Sender};
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use discord_sdk::activity::ActivityBuilder;
-use raylib::audio::Sound;
+use raylib::{
+    audio::Sound,
+    math::{Rectangle, Vector2},
+};
 
 use crate::{
+    input::Input,
+    locale::Locale,
     progress::ProgressData,
-    utilities::{audio_player::AudioPlayer, non_ref_raylib::HackedRaylibHandle},
+    scenes::{
+        confirm_screen::Dialog,
+        ingame_scene::{
+            level::{campaign::Campaign, endless::{EndlessCatalogue, PreparedCatalogue}, Level, LevelZones},
+            timer::LevelTimer,
+        },
+        scene_stack::SceneStack,
+        Scenes,
+    },
+    settings::Settings,
+    utilities::{
+        asset_loader::LoadingProgress, audio_player::AudioPlayer,
+        non_ref_raylib::HackedRaylibHandle,
+    },
     GameConfig,
 };
 
@@ -14,7 +32,36 @@ use crate::{
 pub enum ControlFlag {
     Quit,
     BeginLevel(usize),
+    /// Start an endless run with the given seed
+    BeginEndless(u64),
+    /// Start the daily challenge for a date
+    BeginDailyChallenge(NaiveDate),
     EndLevel,
+    /// An endless run is over. Holds the distance reached, in meters
+    EndEndlessRun(u64),
+    UnloadLevels,
+    /// Swap out a level's colliders and zones, used by the level editor
+    ReplaceLevelData {
+        level: usize,
+        colliders: Vec<Rectangle>,
+        zones: LevelZones,
+    },
+    /// Unlock any secret levels using this code
+    UnlockSecret(String),
+    /// Make the next level start at a specific world position instead of the usual spawn
+    SetPlaytestSpawn(Option<Vector2>),
+    /// Switch to new settings and save them
+    ApplySettings(Settings),
+    /// A scene just switched to another one. Used to play a transition between them
+    SceneSwitched { from: Scenes, to: Scenes },
+    /// Pick the question for the confirm screen. Sent right before switching to it
+    OpenDialog(Dialog),
+    /// The level timer as the in-game scene was left. Sent before `EndLevel`, so the final time is saved
+    UpdateLevelTimer(LevelTimer),
+    /// How far an endless run had got when it was paused. Saved if the run is left from the pause menu
+    UpdateEndlessDistance(u64),
+    /// Reset the level timer, so the next attempt starts from zero
+    RestartLevel,
     // UpdateLevelStart(DateTime<Utc>),
     // SaveProgress,
     // MaybeUpdateHighScore(usize, Duration),
@@ -28,9 +75,35 @@ pub struct GameContext {
     pub sounds: HashMap<String, Sound>,
     pub config: GameConfig,
     pub player_progress: ProgressData,
+    pub settings: Settings,
+    /// Text for the language picked in the settings. Screens keep a copy to draw with
+    pub locale: Rc<Locale>,
+    /// What the player is pressing this frame
+    pub input: Input,
+    /// Every level from every campaign
+    pub levels: Vec<Level>,
+    pub campaigns: Vec<Campaign>,
     pub current_level: usize,
-    pub total_levels: usize,
     pub level_start_time: DateTime<Utc>,
+    /// The level timer as of the last time the in-game scene was left
+    pub level_timer: LevelTimer,
+    /// Set while playing an endless run instead of a normal level
+    pub endless_seed: Option<u64>,
+    /// The distance of a paused endless run that has not been saved yet
+    pub endless_distance: Option<u64>,
+    /// Set while playing a daily challenge. This is always an endless run too
+    pub daily_challenge: Option<NaiveDate>,
+    /// Every endless chunk, cut out by the asset loader. Only `None` until loading is done
+    pub endless_chunks: Option<PreparedCatalogue>,
+    /// Every endless chunk in VRAM. Only loaded while endless runs are being played
+    pub endless_catalogue: Option<Rc<EndlessCatalogue>>,
+    /// Set while the level editor is test-playing a level
+    pub playtest_spawn: Option<Vector2>,
+    pub loading_progress: LoadingProgress,
+    /// Overlays on screen, and the scenes that opened them
+    pub scene_stack: SceneStack,
+    /// The question the confirm screen is asking
+    pub dialog: Dialog,
     pub discord_rpc_send: Sender<Option<ActivityBuilder>>,
     pub flag_send: Sender<Option<ControlFlag>>,
 }
@@ -0,0 +1,455 @@
+//! Actions the player can take, and the keys, mouse buttons, and gamepad inputs bound to them.
+//!
+//! Gameplay and menu code should only ever ask [`Input`] about actions, never about specific keys,
+//! so every binding can be changed from the settings file or the options screen.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
+
+use raylib::{
+    consts::{GamepadAxis, GamepadButton, KeyboardKey, MouseButton},
+    RaylibHandle,
+};
+
+use crate::utilities::key_names::{
+    find_by_name, key_from_name, key_name, name_in, BINDABLE_GAMEPAD_AXES,
+    BINDABLE_GAMEPAD_BUTTONS, BINDABLE_MOUSE_BUTTONS,
+};
+
+/// Only the first gamepad is used
+const GAMEPAD: i32 = 0;
+
+/// How far a stick or trigger must move before it counts as pressed
+const AXIS_THRESHOLD: f32 = 0.5;
+
+/// Something the player can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputAction {
+    Jump,
+    Dash,
+    Pause,
+    Confirm,
+    Back,
+    Restart,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    ToggleFullscreen,
+    ToggleDebugView,
+}
+
+impl InputAction {
+    /// Key for the action's name in the string tables
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            Self::Jump => "action.jump",
+            Self::Dash => "action.dash",
+            Self::Pause => "action.pause",
+            Self::Confirm => "action.confirm",
+            Self::Back => "action.back",
+            Self::Restart => "action.restart",
+            Self::MenuUp => "action.menu_up",
+            Self::MenuDown => "action.menu_down",
+            Self::MenuLeft => "action.menu_left",
+            Self::MenuRight => "action.menu_right",
+            Self::ToggleFullscreen => "action.toggle_fullscreen",
+            Self::ToggleDebugView => "action.toggle_debug_view",
+        }
+    }
+}
+
+/// Actions that can be rebound from the options screen. Rebinding one to an input another already uses swaps them
+pub const REBINDABLE_ACTIONS: &[InputAction] = &[
+    InputAction::Jump,
+    InputAction::Dash,
+    InputAction::Restart,
+    InputAction::Pause,
+];
+
+#[derive(Debug, Error)]
+#[error("Unknown input: {0}")]
+pub struct UnknownInputError(String);
+
+/// A single key, mouse button, or gamepad input. Saved by name, like `"SPACE"`, `"PAD A"`, or `"LEFT STICK X-"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum InputBinding {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButton),
+    /// A stick or trigger pushed past [`AXIS_THRESHOLD`] in one direction
+    GamepadAxis {
+        axis: GamepadAxis,
+        positive: bool,
+    },
+}
+
+impl InputBinding {
+    /// The name shown to the player and saved to the settings file
+    pub fn name(&self) -> String {
+        let name = match *self {
+            Self::Key(key) => key_name(key),
+            Self::Mouse(button) => name_in(BINDABLE_MOUSE_BUTTONS, button),
+            Self::GamepadButton(button) => name_in(BINDABLE_GAMEPAD_BUTTONS, button),
+            Self::GamepadAxis { axis, positive } => {
+                return format!(
+                    "{}{}",
+                    name_in(BINDABLE_GAMEPAD_AXES, axis).unwrap_or("?"),
+                    if positive { "+" } else { "-" }
+                );
+            }
+        };
+        name.unwrap_or("?").to_string()
+    }
+
+    /// Find a binding by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(key) = key_from_name(name) {
+            Some(Self::Key(key))
+        } else if let Some(button) = find_by_name(BINDABLE_MOUSE_BUTTONS, name) {
+            Some(Self::Mouse(button))
+        } else if let Some(button) = find_by_name(BINDABLE_GAMEPAD_BUTTONS, name) {
+            Some(Self::GamepadButton(button))
+        } else {
+            let positive = name.ends_with('+');
+            let axis_name = name.strip_suffix(if positive { '+' } else { '-' })?;
+            find_by_name(BINDABLE_GAMEPAD_AXES, axis_name)
+                .map(|axis| Self::GamepadAxis { axis, positive })
+        }
+    }
+
+    /// Is this a gamepad input, as opposed to the keyboard or mouse
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, Self::GamepadButton(_) | Self::GamepadAxis { .. })
+    }
+}
+
+impl TryFrom<String> for InputBinding {
+    type Error = UnknownInputError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::from_name(&name).ok_or(UnknownInputError(name))
+    }
+}
+
+impl From<InputBinding> for String {
+    fn from(binding: InputBinding) -> Self {
+        binding.name()
+    }
+}
+
+/// Every binding for every action. Actions missing from the settings file keep their default bindings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    from = "BTreeMap<InputAction, Vec<InputBinding>>",
+    into = "BTreeMap<InputAction, Vec<InputBinding>>"
+)]
+pub struct InputBindings(BTreeMap<InputAction, Vec<InputBinding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use GamepadButton as Pad;
+        use KeyboardKey as Key;
+
+        let key = InputBinding::Key;
+        let pad = InputBinding::GamepadButton;
+        let axis = |axis, positive| InputBinding::GamepadAxis { axis, positive };
+        Self(
+            vec![
+                (
+                    InputAction::Jump,
+                    vec![
+                        key(Key::KEY_SPACE),
+                        pad(Pad::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+                    ],
+                ),
+                (
+                    InputAction::Dash,
+                    vec![
+                        key(Key::KEY_LEFT_SHIFT),
+                        pad(Pad::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+                        axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER, true),
+                    ],
+                ),
+                (
+                    InputAction::Pause,
+                    vec![key(Key::KEY_ESCAPE), pad(Pad::GAMEPAD_BUTTON_MIDDLE_RIGHT)],
+                ),
+                (
+                    InputAction::Confirm,
+                    vec![
+                        key(Key::KEY_ENTER),
+                        key(Key::KEY_SPACE),
+                        pad(Pad::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+                    ],
+                ),
+                (
+                    InputAction::Back,
+                    vec![
+                        key(Key::KEY_ESCAPE),
+                        key(Key::KEY_BACKSPACE),
+                        pad(Pad::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+                    ],
+                ),
+                (
+                    InputAction::Restart,
+                    vec![key(Key::KEY_R), pad(Pad::GAMEPAD_BUTTON_RIGHT_FACE_UP)],
+                ),
+                (
+                    InputAction::MenuUp,
+                    vec![
+                        key(Key::KEY_UP),
+                        key(Key::KEY_W),
+                        pad(Pad::GAMEPAD_BUTTON_LEFT_FACE_UP),
+                        axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y, false),
+                    ],
+                ),
+                (
+                    InputAction::MenuDown,
+                    vec![
+                        key(Key::KEY_DOWN),
+                        key(Key::KEY_S),
+                        pad(Pad::GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+                        axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y, true),
+                    ],
+                ),
+                (
+                    InputAction::MenuLeft,
+                    vec![
+                        key(Key::KEY_LEFT),
+                        key(Key::KEY_A),
+                        pad(Pad::GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+                        axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X, false),
+                    ],
+                ),
+                (
+                    InputAction::MenuRight,
+                    vec![
+                        key(Key::KEY_RIGHT),
+                        key(Key::KEY_D),
+                        pad(Pad::GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+                        axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X, true),
+                    ],
+                ),
+                (InputAction::ToggleFullscreen, vec![key(Key::KEY_F11)]),
+                (InputAction::ToggleDebugView, vec![key(Key::KEY_F3)]),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+}
+
+impl From<BTreeMap<InputAction, Vec<InputBinding>>> for InputBindings {
+    fn from(loaded: BTreeMap<InputAction, Vec<InputBinding>>) -> Self {
+        let mut bindings = Self::default();
+        bindings.0.extend(loaded);
+        bindings
+    }
+}
+
+impl From<InputBindings> for BTreeMap<InputAction, Vec<InputBinding>> {
+    fn from(bindings: InputBindings) -> Self {
+        bindings.0
+    }
+}
+
+impl InputBindings {
+    /// Every binding for an action
+    pub fn get(&self, action: InputAction) -> &[InputBinding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Name of the first binding for an action, for telling the player what to press
+    pub fn primary_name(&self, action: InputAction) -> String {
+        self.get(action)
+            .first()
+            .map_or_else(|| "?".to_string(), InputBinding::name)
+    }
+
+    /// Names of an action's first keyboard or mouse binding and its first gamepad binding
+    pub fn describe(&self, action: InputAction) -> String {
+        let bindings = self.get(action);
+        bindings
+            .iter()
+            .find(|binding| !binding.is_gamepad())
+            .into_iter()
+            .chain(bindings.iter().find(|binding| binding.is_gamepad()))
+            .map(InputBinding::name)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Bind an input to an action, replacing the action's bindings from the same kind of device.
+    /// If another rebindable action already uses the input, it gets the action's old binding instead
+    pub fn rebind(&mut self, action: InputAction, binding: InputBinding) {
+        let bindings = self.0.entry(action).or_default();
+        let old_binding = bindings
+            .iter()
+            .copied()
+            .find(|old| old.is_gamepad() == binding.is_gamepad());
+        bindings.retain(|old| old.is_gamepad() != binding.is_gamepad());
+        bindings.insert(0, binding);
+
+        for other in REBINDABLE_ACTIONS.iter().filter(|other| **other != action) {
+            let other_bindings = self.0.entry(*other).or_default();
+            if let Some(idx) = other_bindings.iter().position(|old| *old == binding) {
+                match old_binding {
+                    Some(old_binding) => other_bindings[idx] = old_binding,
+                    None => {
+                        other_bindings.remove(idx);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The state of every action this frame
+#[derive(Debug, Default)]
+pub struct Input {
+    bindings: InputBindings,
+    /// Actions that started being held this frame
+    pressed: BTreeSet<InputAction>,
+    /// Actions being held this frame
+    down: BTreeSet<InputAction>,
+    /// Gamepad axis movement from last frame, to tell when an axis is first pushed
+    last_axis_movement: BTreeMap<i32, f32>,
+    /// Set to ignore the player, like while a scene transition plays
+    blocked: bool,
+}
+
+impl Input {
+    pub fn set_bindings(&mut self, bindings: InputBindings) {
+        self.bindings = bindings;
+    }
+
+    /// Ignore every binding until unblocked. Takes effect on the next `update`
+    pub fn set_blocked(&mut self, blocked: bool) {
+        self.blocked = blocked;
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.blocked
+    }
+
+    /// Check every binding. Must be called once per frame, before anything asks about actions
+    pub fn update(&mut self, raylib: &RaylibHandle) {
+        let gamepad_available = raylib.is_gamepad_available(GAMEPAD);
+        let axis_movement: BTreeMap<i32, f32> = BINDABLE_GAMEPAD_AXES
+            .iter()
+            .map(|(axis, _)| {
+                let movement = if gamepad_available {
+                    raylib.get_gamepad_axis_movement(GAMEPAD, *axis)
+                } else {
+                    0.0
+                };
+                (*axis as i32, movement)
+            })
+            .collect();
+
+        self.pressed.clear();
+        self.down.clear();
+        let bindings = if self.blocked {
+            None
+        } else {
+            Some(&self.bindings.0)
+        };
+        for (action, bindings) in bindings.into_iter().flatten() {
+            for binding in bindings {
+                let (down, pressed) = match *binding {
+                    InputBinding::Key(key) => (raylib.is_key_down(key), raylib.is_key_pressed(key)),
+                    InputBinding::Mouse(button) => (
+                        raylib.is_mouse_button_down(button),
+                        raylib.is_mouse_button_pressed(button),
+                    ),
+                    InputBinding::GamepadButton(button) => (
+                        gamepad_available && raylib.is_gamepad_button_down(GAMEPAD, button),
+                        gamepad_available && raylib.is_gamepad_button_pressed(GAMEPAD, button),
+                    ),
+                    InputBinding::GamepadAxis { axis, positive } => {
+                        let is_pushed = |movement: Option<&f32>| {
+                            let movement = movement.copied().unwrap_or(0.0);
+                            if positive {
+                                movement > AXIS_THRESHOLD
+                            } else {
+                                movement < -AXIS_THRESHOLD
+                            }
+                        };
+                        let down = is_pushed(axis_movement.get(&(axis as i32)));
+                        let was_down = is_pushed(self.last_axis_movement.get(&(axis as i32)));
+                        (down, down && !was_down)
+                    }
+                };
+                if down {
+                    self.down.insert(*action);
+                }
+                if pressed {
+                    self.pressed.insert(*action);
+                }
+            }
+        }
+        self.last_axis_movement = axis_movement;
+    }
+
+    /// Was the action started this frame
+    pub fn is_pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Is the action being held
+    pub fn is_down(&self, action: InputAction) -> bool {
+        self.down.contains(&action)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_binding_names_round_trip() {
+        for binding in InputBindings::default().0.values().flatten() {
+            assert_eq!(InputBinding::from_name(&binding.name()), Some(*binding));
+        }
+        assert_eq!(InputBinding::from_name("NOT A KEY"), None);
+    }
+
+    #[test]
+    fn test_missing_actions_use_defaults() {
+        let bindings: InputBindings = serde_json::from_str("{\"jump\":[\"W\",\"PAD B\"]}").unwrap();
+        assert_eq!(
+            bindings.get(InputAction::Jump),
+            &[
+                InputBinding::Key(KeyboardKey::KEY_W),
+                InputBinding::GamepadButton(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)
+            ]
+        );
+        assert_eq!(
+            bindings.get(InputAction::Dash),
+            InputBindings::default().get(InputAction::Dash)
+        );
+    }
+
+    #[test]
+    fn test_rebind_swaps_with_other_actions() {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(
+            InputAction::Jump,
+            InputBinding::Key(KeyboardKey::KEY_LEFT_SHIFT),
+        );
+        assert_eq!(bindings.describe(InputAction::Jump), "LEFT SHIFT / PAD A");
+        assert_eq!(bindings.describe(InputAction::Dash), "SPACE / PAD X");
+
+        // Menu actions are never changed
+        bindings.rebind(
+            InputAction::Restart,
+            InputBinding::Key(KeyboardKey::KEY_ENTER),
+        );
+        assert_eq!(bindings.primary_name(InputAction::Confirm), "ENTER");
+    }
+}
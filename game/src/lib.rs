@@ -70,10 +70,11 @@
 )]
 #![clippy::msrv = "1.57.0"]
 
-use std::{borrow::BorrowMut, cell::RefCell, collections::HashMap, sync::mpsc::TryRecvError};
+use std::{
+    borrow::BorrowMut, cell::RefCell, collections::HashMap, rc::Rc, sync::mpsc::TryRecvError,
+};
 
-use chrono::Utc;
-use discord_sdk::activity::ActivityBuilder;
+use chrono::{Duration, Utc};
 use raylib::prelude::*;
 use tracing::{error, info, warn};
 use utilities::discord::DiscordConfig;
@@ -81,15 +82,39 @@ use utilities::discord::DiscordConfig;
 use crate::{
     context::GameContext,
     discord_rpc::{maybe_set_discord_presence, try_connect_to_local_discord},
+    input::{Input, InputAction},
+    locale::{Locale, DEFAULT_LANGUAGE, LOCALE_FONT_SIZE},
     progress::ProgressData,
-    scenes::{build_screen_state_machine, ingame_scene::level::loader::load_all_levels, Scenes},
+    scenes::{
+        build_screen_state_machine,
+        ingame_scene::{
+            level::{
+                endless::{daily_challenge_seed, load_endless_catalogue},
+                loader::stream_level_textures,
+            },
+            timer::LevelTimer,
+        },
+        confirm_screen::Dialog,
+        register_game_scenes,
+        scene_stack::SceneStack,
+        transition::{ActiveTransition, TransitionConfig},
+        Scenes,
+    },
+    settings::Settings,
     utilities::{
+        asset_loader::{AssetJob, AssetLoader, LoadingProgress, PreparedAsset},
         audio_player::AudioPlayer,
-        datastore::{load_music_from_internal_data, load_sound_from_internal_data},
-        game_config::FinalShaderConfig,
+        datastore::{
+            load_font_from_internal_data, load_music_from_staged_file,
+            load_sound_from_staged_file, upload_decoded_image, ResourceLoadError, StagedFile,
+        },
+        game_config::{FinalShaderConfig, FontConfig},
         shaders::{
             shader::ShaderWrapper,
-            util::{dynamic_screen_texture::DynScreenTexture, render_texture::render_to_texture},
+            util::{
+                dynamic_screen_texture::DynScreenTexture,
+                render_texture::{draw_render_texture, render_to_texture},
+            },
         },
     },
 };
@@ -107,11 +132,14 @@ extern crate async_trait;
 
 mod context;
 mod discord_rpc;
+mod input;
+mod locale;
 mod scenes;
 mod utilities;
 pub use utilities::{datastore::StaticGameData, game_config::GameConfig};
 mod character;
 mod progress;
+mod settings;
 
 /// The game entrypoint
 pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std::error::Error>> {
@@ -121,30 +149,18 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
         puffin_http::Server::new(&format!("0.0.0.0:{}", puffin_http::DEFAULT_PORT)).unwrap();
     puffin::set_scopes_on(true);
 
-    // Attempt to connect to a locally running Discord instance for rich presence access
+    // Attempt to connect to a locally running Discord instance for rich presence access.
+    // This happens in the background so the window does not hang while waiting for the connection to time out
     let discord_config = DiscordConfig::load(
         StaticGameData::get("configs/discord.json").expect("Failed to load discord.json"),
     )
     .unwrap();
-    let discord_rpc = match try_connect_to_local_discord(&discord_config).await {
-        Ok(client) => Some(client),
-        Err(err) => match err {
-            utilities::discord::rpc::DiscordError::ConnectionTimeout(time) => {
-                error!(
-                    "Could not find or connect to a local Discord instance after {} seconds",
-                    time
-                );
-                None
-            }
-            _ => panic!("Failed to connect to Discord: {}", err),
-        },
-    };
-    maybe_set_discord_presence(
-        &discord_rpc,
-        ActivityBuilder::default().details("Game starting"),
-    )
-    .await
-    .unwrap();
+    let (send_discord_client, recv_discord_client) = std::sync::mpsc::channel();
+    tokio::spawn(async move {
+        let _ = send_discord_client.send(try_connect_to_local_discord(&discord_config).await);
+    });
+    let mut discord_rpc = None;
+    let mut pending_discord_activity = None;
 
     // Build an MPSC for the game to send rich presence data to discord
     let (send_discord_rpc, recv_discord_rpc) = std::sync::mpsc::channel();
@@ -152,76 +168,94 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
     // Build an MPSC for signaling the control thread
     let (send_control_signal, recv_control_signal) = std::sync::mpsc::channel();
 
-    // Load the savefile
+    // Load the savefile and settings
     let mut save_file = ProgressData::load_from_file();
+    let settings = Settings::load_from_file();
 
     let mut context;
     let raylib_thread;
     {
         // Set up FFI access to raylib
         // hook_raylib_logging();
-        let (mut rl, thread) = raylib::init()
-            .size(
-                game_config.base_window_size.0,
-                game_config.base_window_size.1,
-            )
+        let window_size = settings
+            .resolution
+            .unwrap_or(game_config.base_window_size);
+        let mut builder = raylib::init();
+        builder
+            .size(window_size.0, window_size.1)
             .title(&format!("[{}]", game_config.name))
             .msaa_4x()
             .resizable()
-            .replace_logger()
-            .build();
+            .replace_logger();
+        if settings.vsync {
+            builder.vsync();
+        }
+        let (mut rl, thread) = builder.build();
         rl.set_exit_key(None);
-        rl.set_target_fps(60);
+        if settings.resolution.is_none() {
+            let window_size = default_window_size(&rl, game_config);
+            rl.set_window_size(window_size.0, window_size.1);
+        }
         raylib_thread = thread;
 
-        // Init the audio subsystem
-        let mut audio_system = AudioPlayer::new(RaylibAudio::init_audio_device());
-        audio_system.set_master_volume(0.4);
+        // Init the audio subsystem. Volumes are set along with the rest of the settings
+        let audio_system = AudioPlayer::new(RaylibAudio::init_audio_device());
 
-        // Load any other sounds
-        let mut sounds = HashMap::new();
-        sounds.insert(
-            "button-press".to_string(),
-            load_sound_from_internal_data("audio/button-press.mp3").unwrap(),
-        );
-
-        // Build the game context
+        // Build the game context. Sounds and levels are filled in by the asset loader
         context = Box::new(GameContext {
             renderer: RefCell::new(rl.into()),
             config: game_config.clone(),
             audio: audio_system,
-            sounds,
-            total_levels: 0,
+            sounds: HashMap::new(),
+            levels: Vec::new(),
+            campaigns: Vec::new(),
             current_level: 0,
             player_progress: save_file,
+            settings: settings.clone(),
+            locale: Rc::default(),
+            input: Input::default(),
             level_start_time: Utc::now(),
+            level_timer: LevelTimer::default(),
+            endless_seed: None,
+            endless_distance: None,
+            daily_challenge: None,
+            endless_chunks: None,
+            endless_catalogue: None,
+            playtest_spawn: None,
+            loading_progress: LoadingProgress::default(),
+            scene_stack: SceneStack::default(),
+            dialog: Dialog::default(),
             discord_rpc_send: send_discord_rpc,
             flag_send: send_control_signal,
         });
     }
 
-    // Load the game's main song
-    let mut main_song = load_music_from_internal_data(
-        &mut context.renderer.borrow_mut(),
-        &raylib_thread,
-        "audio/soundtrack.mp3",
-    )
-    .unwrap();
-
-    // Start the song
-    context.audio.play_music_stream(&mut main_song);
-
-    // Load all levels
-    let levels = load_all_levels(&mut context.renderer.borrow_mut(), &raylib_thread).unwrap();
-    context.total_levels = levels.len();
-
-    // Get the main state machine
+    // Apply everything from the settings file that was not needed to open the window
+    apply_settings(context.as_mut(), settings);
+    load_ui_font(context.as_mut(), &raylib_thread);
+    load_language(context.as_mut(), &raylib_thread);
+
+    // Start preparing all assets in the background. Only the GPU and audio device work happens on this thread
+    info!("Starting the background asset loader");
+    let mut asset_loader = Some(AssetLoader::spawn(vec![
+        AssetJob::Music("audio/soundtrack.mp3".to_string()),
+        AssetJob::Sound {
+            name: "button-press".to_string(),
+            path: "audio/button-press.mp3".to_string(),
+        },
+        AssetJob::Texture("character/player_run.png".to_string()),
+        AssetJob::Texture("default-texture.png".to_string()),
+        AssetJob::LevelMetadata,
+        AssetJob::EndlessChunks,
+    ]));
+    let mut loaded_textures = HashMap::new();
+    let mut main_song: Option<(Music, StagedFile)> = None;
+
+    // Get the main state machine. The rest of the scenes are registered once loading is done
     info!("Setting up the scene management state machine");
-    let mut game_state_machine =
-        build_screen_state_machine(&mut context.renderer.borrow_mut(), &raylib_thread, levels)
-            .unwrap();
+    let mut game_state_machine = build_screen_state_machine().unwrap();
     game_state_machine
-        .force_change_state(Scenes::MainMenuScreen)
+        .force_change_state(Scenes::LoadingScreen)
         .unwrap();
 
     // Create a dynamic texture to draw to for processing by shaders
@@ -229,6 +263,16 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
     let mut dynamic_texture =
         DynScreenTexture::new(&mut context.renderer.borrow_mut(), &raylib_thread)?;
 
+    // Scene transitions need two more: one holding the outgoing scene, and one for blending it with the incoming scene
+    let mut transition_snapshot =
+        DynScreenTexture::new(&mut context.renderer.borrow_mut(), &raylib_thread)?;
+    let mut transition_texture =
+        DynScreenTexture::new(&mut context.renderer.borrow_mut(), &raylib_thread)?;
+
+    // Overlays are drawn over a frozen frame of the scene that opened them
+    let mut overlay_background =
+        DynScreenTexture::new(&mut context.renderer.borrow_mut(), &raylib_thread)?;
+
     // Load the pixel art shader
     info!("Loading the pixel art shader");
     let pixel_shader_config = FinalShaderConfig::load(
@@ -250,42 +294,84 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
         &raylib_thread,
     )?;
 
+    // Load the scene transitions and their shader
+    info!("Loading scene transitions");
+    let transition_config = TransitionConfig::load(
+        StaticGameData::get("configs/transitions.json").expect("Failed to load transitions.json"),
+    )
+    .unwrap();
+    let mut transition_shader = ShaderWrapper::new(
+        None,
+        Some(StaticGameData::get("shaders/transition.fs")).expect("Failed to load transition.fs"),
+        vec!["viewport", "progress", "effect", "previousScene"],
+        &mut context.renderer.borrow_mut(),
+        &raylib_thread,
+    )?;
+    let mut active_transition: Option<ActiveTransition> = None;
+
+    // In debug builds, levels are reloaded whenever their files change on disk
+    #[cfg(all(debug_assertions, feature = "hot-reload"))]
+    let level_watcher = match utilities::level_watcher::LevelWatcher::new() {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            warn!("Could not watch level files for changes: {}", e);
+            None
+        }
+    };
+
     while !context.renderer.borrow().window_should_close() {
         // Profile the main game loop
         puffin::profile_scope!("main_loop");
         puffin::GlobalProfiler::lock().new_frame();
 
         // Update the audio
-        context.audio.update_music_stream(&mut main_song);
-        if !context.audio.is_music_playing(&main_song) {
-            context.audio.play_music_stream(&mut main_song);
+        if let Some((main_song, _)) = &mut main_song {
+            context.audio.update_music_channel(main_song);
+            if !context.audio.is_music_playing(main_song) {
+                context.audio.play_music_stream(main_song);
+            }
         }
 
         // Update the GPU texture that we draw to. This handles screen resizing and some other stuff
-        dynamic_texture
-            .update(&mut context.renderer.borrow_mut(), &raylib_thread)
-            .unwrap();
+        for texture in [
+            &mut dynamic_texture,
+            &mut transition_snapshot,
+            &mut transition_texture,
+            &mut overlay_background,
+        ] {
+            texture
+                .update(&mut context.renderer.borrow_mut(), &raylib_thread)
+                .unwrap();
+        }
+
+        // Move any scene transition along. The player can not do anything until it is over
+        if let Some(transition) = &mut active_transition {
+            transition.advance(context.renderer.borrow().get_frame_time());
+        }
+        active_transition = active_transition.filter(|transition| !transition.is_done());
+        context.as_mut().input.set_blocked(active_transition.is_some());
+
+        // Check what the player is pressing before anything asks about it
+        {
+            let context = context.as_mut();
+            context.input.update(&context.renderer.borrow());
+        }
 
         // If in dev mode, allow a debug key
         #[cfg(debug_assertions)]
         {
-            if context
-                .renderer
-                .borrow()
-                .is_key_pressed(KeyboardKey::KEY_F3)
-            {
+            if context.input.is_pressed(InputAction::ToggleDebugView) {
                 game_config.debug_view = !game_config.debug_view;
                 warn!("Debug view set: {}", game_config.debug_view);
             }
         }
 
         // Handle fullscreen shortcut
-        if context
-            .renderer
-            .borrow()
-            .is_key_pressed(KeyboardKey::KEY_F11)
-        {
-            context.renderer.borrow_mut().toggle_fullscreen();
+        if context.input.is_pressed(InputAction::ToggleFullscreen) {
+            let mut settings = context.settings.clone();
+            settings.fullscreen = !settings.fullscreen;
+            apply_settings(context.as_mut(), settings);
+            context.settings.save();
         }
 
         // Switch into draw mode the unsafe way (using unsafe code here to avoid borrow checker hell)
@@ -306,8 +392,14 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
                 pixel_shader_config.pixel_scale,
             ),
         )?;
-        pixel_shader.set_variable("warpFactor", pixel_shader_config.warp_factor)?;
-        pixel_shader.set_variable("scanlineDarkness", pixel_shader_config.scanline_darkness)?;
+        pixel_shader.set_variable(
+            "warpFactor",
+            pixel_shader_config.warp_factor * context.settings.shader_strength,
+        )?;
+        pixel_shader.set_variable(
+            "scanlineDarkness",
+            pixel_shader_config.scanline_darkness * context.settings.shader_strength,
+        )?;
         pixel_shader.set_variable("bloomSamples", pixel_shader_config.bloom_samples)?;
         pixel_shader.set_variable("bloomQuality", pixel_shader_config.bloom_quality)?;
 
@@ -316,6 +408,11 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
             // Profile the internal render code
             puffin::profile_scope!("internal_shaded_render");
 
+            // Put back the scene under any open overlays
+            if context.scene_stack.has_overlay() {
+                draw_render_texture(&mut *context.renderer.borrow_mut(), &overlay_background);
+            }
+
             // Run a state machine iteration
             let result = game_state_machine.run(&context);
 
@@ -327,11 +424,29 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
             }
         });
 
+        // Blend the outgoing scene into the new one while a transition plays
+        let final_texture = if let Some(transition) = &active_transition {
+            transition_shader.set_variable("viewport", screen_size)?;
+            transition_shader.set_variable("progress", transition.progress())?;
+            transition_shader.set_variable("effect", transition.effect().shader_id())?;
+            transition_shader.set_texture("previousScene", &transition_snapshot)?;
+            render_to_texture(&mut transition_texture, || {
+                transition_shader.process_texture_and_render(
+                    &mut context.renderer.borrow_mut(),
+                    &raylib_thread,
+                    &dynamic_texture,
+                );
+            });
+            &transition_texture
+        } else {
+            &dynamic_texture
+        };
+
         // Send the texture to the GPU to be drawn
         pixel_shader.process_texture_and_render(
             &mut context.renderer.borrow_mut(),
             &raylib_thread,
-            &dynamic_texture,
+            final_texture,
         );
 
         // We MUST end draw mode
@@ -340,11 +455,72 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
             raylib::ffi::EndDrawing();
         }
 
+        // Finish loading one asset per frame so the loading screen can show progress
+        if let Some(loader) = &mut asset_loader {
+            if let Some((item, asset)) = loader.try_next() {
+                if let Err(e) = finish_loading_asset(
+                    context.as_mut(),
+                    &raylib_thread,
+                    asset,
+                    &mut loaded_textures,
+                    &mut main_song,
+                ) {
+                    loader.fail(&item, &e);
+                }
+            }
+            let progress = loader.progress();
+
+            if progress.failed {
+                // The loading screen shows the error screen from here
+                asset_loader = None;
+            } else if progress.is_done() {
+                // Once everything is in memory, the rest of the game can be set up
+                info!("All assets loaded");
+                register_game_scenes(
+                    &mut game_state_machine,
+                    loaded_textures
+                        .remove("character/player_run.png")
+                        .expect("Player sprite sheet was not loaded"),
+                    loaded_textures
+                        .remove("default-texture.png")
+                        .expect("World background was not loaded"),
+                )?;
+                asset_loader = None;
+            }
+            context.as_mut().loading_progress = progress;
+        }
+
+        // Check if the background Discord connection has finished
+        if let Ok(result) = recv_discord_client.try_recv() {
+            match result {
+                Ok(client) => {
+                    discord_rpc = Some(client);
+
+                    // Catch Discord up on anything that happened while connecting
+                    if let Some(activity) = pending_discord_activity.take() {
+                        if let Err(e) = maybe_set_discord_presence(&discord_rpc, activity).await {
+                            error!("Failed to update discord presence: {:?}", e);
+                        }
+                    }
+                }
+                Err(utilities::discord::rpc::DiscordError::ConnectionTimeout(time)) => {
+                    error!(
+                        "Could not find or connect to a local Discord instance after {} seconds",
+                        time
+                    );
+                }
+                Err(err) => panic!("Failed to connect to Discord: {}", err),
+            }
+        }
+
         // Try to update discord
         match recv_discord_rpc.try_recv() {
             Ok(activity) => {
                 if let Some(activity) = activity {
-                    if let Err(e) = maybe_set_discord_presence(&discord_rpc, activity).await {
+                    if discord_rpc.is_none() {
+                        // Hold on to the latest activity until a connection is made
+                        pending_discord_activity = Some(activity);
+                    } else if let Err(e) = maybe_set_discord_presence(&discord_rpc, activity).await {
                         error!("Failed to update discord presence: {:?}", e);
                     }
                 }
@@ -356,40 +532,361 @@ pub async fn game_begin(game_config: &mut GameConfig) -> Result<(), Box<dyn std:
             }
         }
 
-        // Handle control flags
-        match recv_control_signal.try_recv() {
-            Ok(flag) => {
-                if let Some(flag) = flag {
-                    match flag {
-                        context::ControlFlag::Quit => break,
-                        context::ControlFlag::BeginLevel(level) => {
-                            context.as_mut().current_level = level;
-                            context.as_mut().level_start_time = Utc::now();
-                        }
-                        context::ControlFlag::EndLevel => {
-                            let now = Utc::now();
-                            let elapsed = now - context.as_mut().level_start_time;
-                            if elapsed.num_seconds().abs() > 1 {
-                                let current_level = context.as_mut().current_level;
+        // Hot reload any changed levels. The player is left alone, so they stay where they are
+        #[cfg(all(debug_assertions, feature = "hot-reload"))]
+        {
+            if let Some(watcher) = &level_watcher {
+                for level_name in watcher.changed_levels() {
+                    let context = context.as_mut();
+                    if let Err(e) = scenes::ingame_scene::level::loader::reload_level(
+                        &mut context.renderer.borrow_mut(),
+                        &raylib_thread,
+                        &mut context.levels,
+                        &level_name,
+                    ) {
+                        warn!("Failed to reload level {}: {}", level_name, e);
+                    }
+                }
+            }
+        }
+
+        // Handle control flags. More than one flag can be sent per frame, so drain the whole channel
+        let mut should_quit = false;
+        loop {
+            match recv_control_signal.try_recv() {
+                Ok(flag) => {
+                    if let Some(flag) = flag {
+                        match flag {
+                            context::ControlFlag::Quit => should_quit = true,
+                            context::ControlFlag::BeginLevel(level) => {
+                                context.as_mut().current_level = level;
+                                context.as_mut().endless_seed = None;
+                                context.as_mut().endless_distance = None;
+                                context.as_mut().daily_challenge = None;
+                                context.as_mut().level_start_time = Utc::now();
+                                context.as_mut().level_timer = LevelTimer::default();
+
+                                // Make sure the level's textures are in VRAM
+                                let preload_next = context.config.preload_next_level;
+                                let context = context.as_mut();
+                                stream_level_textures(
+                                    &mut context.renderer.borrow_mut(),
+                                    &raylib_thread,
+                                    &mut context.levels,
+                                    Some(level),
+                                    preload_next,
+                                    &context.player_progress,
+                                )?;
+                            }
+                            context::ControlFlag::BeginEndless(seed) => {
+                                context.as_mut().daily_challenge = None;
+                                begin_endless_run(context.as_mut(), &raylib_thread, seed)?;
+                            }
+                            context::ControlFlag::BeginDailyChallenge(date) => {
+                                context.as_mut().daily_challenge = Some(date);
+                                begin_endless_run(
+                                    context.as_mut(),
+                                    &raylib_thread,
+                                    daily_challenge_seed(date),
+                                )?;
+                            }
+                            context::ControlFlag::EndLevel => {
+                                let context = context.as_mut();
+                                let elapsed = context.level_timer.elapsed();
+                                if elapsed > Duration::zero() {
+                                    if let Some(date) = context.daily_challenge {
+                                        context
+                                            .player_progress
+                                            .maybe_write_daily_time(date, &elapsed);
+                                    } else if let Some(level) =
+                                        context.levels.get(context.current_level)
+                                    {
+                                        context.player_progress.maybe_write_new_time(
+                                            &level.name,
+                                            level.content_hash,
+                                            &elapsed,
+                                            context.level_timer.splits(),
+                                        );
+                                    }
+                                    context.player_progress.save();
+                                }
+                            }
+                            context::ControlFlag::EndEndlessRun(distance) => {
+                                context.as_mut().endless_distance = None;
+                                let progress = &mut context.as_mut().player_progress;
+                                progress.maybe_write_endless_distance(distance);
+                                progress.save();
+                            }
+                            context::ControlFlag::UnloadLevels => {
+                                let context = context.as_mut();
+                                context.endless_catalogue = None;
+                                stream_level_textures(
+                                    &mut context.renderer.borrow_mut(),
+                                    &raylib_thread,
+                                    &mut context.levels,
+                                    None,
+                                    false,
+                                    &context.player_progress,
+                                )?;
+                            }
+                            context::ControlFlag::ReplaceLevelData {
+                                level,
+                                colliders,
+                                zones,
+                            } => {
+                                if let Some(level) = context.as_mut().levels.get_mut(level) {
+                                    level.authored_colliders = colliders;
+                                    level.zones = zones;
+                                    level.rebuild_colliders();
+                                }
+                            }
+                            context::ControlFlag::UnlockSecret(code) => {
+                                info!("Unlocked secret: {}", code);
+                                let progress = &mut context.as_mut().player_progress;
+                                progress.unlocked_secrets.insert(code);
+                                progress.save();
+                            }
+                            context::ControlFlag::SetPlaytestSpawn(spawn) => {
+                                context.as_mut().playtest_spawn = spawn;
+                            }
+                            context::ControlFlag::ApplySettings(settings) => {
+                                let language_changed =
+                                    settings.language != context.settings.language;
+                                apply_settings(context.as_mut(), settings);
+                                if language_changed {
+                                    load_language(context.as_mut(), &raylib_thread);
+                                }
+                                context.settings.save();
+                            }
+                            context::ControlFlag::SceneSwitched { from, to } => {
+                                // The frame just drawn is the last one from the outgoing scene, so hold on to it
+                                if context.as_mut().scene_stack.on_switch(from, to) {
+                                    render_to_texture(&mut overlay_background, || {
+                                        draw_render_texture(
+                                            &mut *context.renderer.borrow_mut(),
+                                            &dynamic_texture,
+                                        );
+                                    });
+                                }
+                                active_transition =
+                                    ActiveTransition::start(transition_config.style_for(from, to));
+                                if active_transition.is_some() {
+                                    std::mem::swap(&mut dynamic_texture, &mut transition_snapshot);
+                                }
+                            }
+                            context::ControlFlag::OpenDialog(dialog) => {
+                                context.as_mut().dialog = dialog;
+                            }
+                            context::ControlFlag::UpdateLevelTimer(timer) => {
+                                context.as_mut().level_timer = timer;
+                            }
+                            context::ControlFlag::UpdateEndlessDistance(distance) => {
+                                context.as_mut().endless_distance = Some(distance);
+                            }
+                            context::ControlFlag::RestartLevel => {
+                                context.as_mut().level_timer = LevelTimer::default();
+                            }
+                            context::ControlFlag::SoundTrigger(name) => {
+                                let context = context.as_mut();
                                 context
-                                    .as_mut()
-                                    .player_progress
-                                    .maybe_write_new_time(current_level, &elapsed);
-                                context.as_mut().player_progress.save();
+                                    .audio
+                                    .play_sfx(context.sounds.get_mut(&name).unwrap());
                             }
                         }
-                        context::ControlFlag::SoundTrigger(name) => {
-                            context.audio.play_sound(context.sounds.get(&name).unwrap());
-                        }
                     }
                 }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    should_quit = true;
+                    break;
+                }
             }
-            Err(TryRecvError::Empty) => {}
-            Err(TryRecvError::Disconnected) => {
-                break;
-            }
+        }
+        if should_quit {
+            break;
         }
     }
     context.as_mut().player_progress.save();
     Ok(())
 }
+
+/// Finish off an asset from the loader on the main thread, where the GPU and audio device live
+fn finish_loading_asset(
+    context: &mut GameContext,
+    thread: &RaylibThread,
+    asset: PreparedAsset,
+    loaded_textures: &mut HashMap<String, Texture2D>,
+    main_song: &mut Option<(Music, StagedFile)>,
+) -> Result<(), ResourceLoadError> {
+    match asset {
+        PreparedAsset::Texture { path, image } => {
+            let texture = upload_decoded_image(&mut context.renderer.borrow_mut(), thread, &image)?;
+            loaded_textures.insert(path, texture);
+        }
+        PreparedAsset::Sound { name, file } => {
+            context
+                .sounds
+                .insert(name, load_sound_from_staged_file(&file)?);
+        }
+        PreparedAsset::Music { file, .. } => {
+            // Start the song
+            let mut song = load_music_from_staged_file(thread, &file)?;
+            context.audio.play_music_stream(&mut song);
+            *main_song = Some((song, file));
+        }
+        PreparedAsset::LevelMetadata { levels, campaigns } => {
+            // Old saves stored best times by position in the level list
+            let level_ids: Vec<&str> = levels.iter().map(|level| level.name.as_str()).collect();
+            if context.player_progress.migrate_legacy_times(&level_ids) {
+                context.player_progress.save();
+            }
+            context.levels = levels;
+            context.campaigns = campaigns;
+        }
+        PreparedAsset::EndlessChunks(chunks) => {
+            context.endless_chunks = Some(chunks);
+        }
+    }
+    Ok(())
+}
+
+/// Get everything ready for an endless run. Chunks are only sent to the GPU the first time
+fn begin_endless_run(
+    context: &mut GameContext,
+    thread: &RaylibThread,
+    seed: u64,
+) -> Result<(), ResourceLoadError> {
+    context.endless_seed = Some(seed);
+    context.endless_distance = None;
+    context.level_start_time = Utc::now();
+    context.level_timer = LevelTimer::default();
+
+    // Endless runs bring their own textures, so the normal levels can be freed
+    stream_level_textures(
+        &mut context.renderer.borrow_mut(),
+        thread,
+        &mut context.levels,
+        None,
+        false,
+        &context.player_progress,
+    )?;
+    if context.endless_catalogue.is_none() {
+        info!("Loading endless chunks");
+        let prepared = context.endless_chunks.as_ref().ok_or_else(|| {
+            ResourceLoadError::Generic("Endless chunks have not been loaded yet".to_string())
+        })?;
+        context.endless_catalogue = Some(Rc::new(load_endless_catalogue(
+            &mut context.renderer.borrow_mut(),
+            thread,
+            prepared,
+        )?));
+    }
+    Ok(())
+}
+
+/// Load the UI font from `application.json`. Raylib's built-in font is used if there is none, or it can not be loaded
+fn load_ui_font(context: &mut GameContext, thread: &RaylibThread) {
+    let config = context.config.ui_font.clone();
+
+    // Every language the built-in font can draw should be drawable with the UI font too
+    let latin_1: Vec<i32> = (32..=255).collect();
+    let font = config.path.as_ref().and_then(|path| {
+        match load_font_from_internal_data(
+            &mut context.renderer.borrow_mut(),
+            thread,
+            path,
+            config.base_size,
+            &latin_1,
+        ) {
+            Ok(font) => Some(font),
+            Err(e) => {
+                warn!("Could not load font {}: {}", path, e);
+                None
+            }
+        }
+    });
+    let config = if config.path.is_some() && font.is_none() {
+        FontConfig {
+            pixel_perfect: config.pixel_perfect,
+            ..FontConfig::default()
+        }
+    } else {
+        config
+    };
+    context.renderer.borrow_mut().set_ui_font(font, config);
+}
+
+/// Load the string table and font for the language picked in the settings.
+/// This needs the render thread for the font, so it is kept apart from [`apply_settings`]
+fn load_language(context: &mut GameContext, thread: &RaylibThread) {
+    let language = &context.settings.language;
+    let locale = Locale::load(language).unwrap_or_else(|e| {
+        warn!(
+            "Could not load language {}, falling back to {}: {}",
+            language, DEFAULT_LANGUAGE, e
+        );
+        Locale::load(DEFAULT_LANGUAGE).expect("Failed to load the default language")
+    });
+
+    // Languages that the built-in font can not draw bring their own
+    let font = locale.font().and_then(|path| {
+        match load_font_from_internal_data(
+            &mut context.renderer.borrow_mut(),
+            thread,
+            path,
+            LOCALE_FONT_SIZE,
+            &locale.codepoints(),
+        ) {
+            Ok(font) => Some(font),
+            Err(e) => {
+                warn!("Could not load font {}: {}", path, e);
+                None
+            }
+        }
+    });
+    context.renderer.borrow_mut().set_locale_font(font);
+    info!("Using language: {}", locale.language());
+    context.locale = Rc::new(locale);
+}
+
+/// The window size used when the settings do not pick one. This is scaled up on high DPI monitors so the window
+/// is not tiny, except on macOS where window sizes are already in points
+fn default_window_size(raylib: &RaylibHandle, config: &GameConfig) -> (i32, i32) {
+    let (width, height) = config.base_window_size;
+    let dpi_scale = if cfg!(target_os = "macos") {
+        Vector2::one()
+    } else {
+        raylib.get_window_scale_dpi()
+    };
+    (
+        (width as f32 * dpi_scale.x.max(1.0)).round() as i32,
+        (height as f32 * dpi_scale.y.max(1.0)).round() as i32,
+    )
+}
+
+/// Switch to new settings. Only what changed from the current settings is touched, so the window is not resized for no reason
+fn apply_settings(context: &mut GameContext, settings: Settings) {
+    {
+        let mut renderer = context.renderer.borrow_mut();
+        if settings.fullscreen != renderer.is_window_fullscreen() {
+            renderer.toggle_fullscreen();
+        }
+        if !settings.fullscreen && settings.resolution != context.settings.resolution {
+            let window_size = settings
+                .resolution
+                .unwrap_or_else(|| default_window_size(&renderer, &context.config));
+            renderer.set_window_size(window_size.0, window_size.1);
+        }
+        let (design_width, design_height) = context.config.base_window_size;
+        renderer.set_ui_layout(
+            Vector2::new(design_width as f32, design_height as f32),
+            settings.ui_scale,
+        );
+        renderer.set_target_fps(settings.target_fps);
+    }
+    context.audio.set_master_volume(settings.master_volume);
+    context.audio.set_music_channel_volume(settings.music_volume);
+    context.audio.set_sfx_channel_volume(settings.sfx_volume);
+    context.input.set_bindings(settings.input_bindings.clone());
+    context.settings = settings;
+}
@@ -0,0 +1,160 @@
+//! Translated text for everything the player can read.
+//!
+//! Each language has a string table in `assets/locale/<code>.json`. Screens look text up by key with
+//! [`Locale::text`], or [`Locale::text_with`] for text with `{placeholders}` in it. Any key missing from a
+//! table falls back to English, and any key missing from English is shown as the key itself.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::utilities::datastore::{ResourceLoadError, StaticGameData};
+
+/// The language used when a table is missing a string, and when the settings do not pick one
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Every language with a string table, as its code and its name in that language.
+/// Names are drawn with whatever font is in use, so they stay in Latin-1 even for languages that bring a font
+pub const LANGUAGES: &[(&str, &str)] = &[("en", "English"), ("es", "Español"), ("ru", "Russkiy")];
+
+/// The size a language's font is loaded at. Text is scaled down from this, so it should be at least the largest size drawn
+pub const LOCALE_FONT_SIZE: i32 = 70;
+
+/// The layout of a `assets/locale/<code>.json` file
+#[derive(Debug, Deserialize)]
+struct LocaleFile {
+    /// A font for scripts the built-in font can not draw, relative to `assets/`. The built-in font covers Latin-1
+    #[serde(default)]
+    font: Option<String>,
+    strings: HashMap<String, String>,
+}
+
+fn load_locale_file(language: &str) -> Result<LocaleFile, ResourceLoadError> {
+    let path = format!("locale/{}.json", language);
+    let data = StaticGameData::get(&path).ok_or(ResourceLoadError::AssetNotFound(path))?;
+    Ok(serde_json::from_slice(&data.data)?)
+}
+
+/// Text in one language. The loaded one is kept in `context.locale`, and screens keep a copy of that `Rc` from
+/// `execute`, so their rendering code can look text up without the context
+#[derive(Debug, Default)]
+pub struct Locale {
+    language: String,
+    font: Option<String>,
+    strings: HashMap<String, String>,
+    /// English strings, for anything not translated yet
+    fallback: HashMap<String, String>,
+}
+
+impl Locale {
+    /// Load a language's string table, along with the English one to fall back on
+    pub fn load(language: &str) -> Result<Self, ResourceLoadError> {
+        let fallback = load_locale_file(DEFAULT_LANGUAGE)?.strings;
+        let file = if language == DEFAULT_LANGUAGE {
+            None
+        } else {
+            Some(load_locale_file(language)?)
+        };
+        let (font, strings) = file.map_or((None, HashMap::new()), |file| (file.font, file.strings));
+        Ok(Self {
+            language: language.to_string(),
+            font,
+            strings,
+            fallback,
+        })
+    }
+
+    /// The code of the language that was loaded, like `"en"`
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// The font this language needs, if the built-in one can not draw it
+    pub fn font(&self) -> Option<&str> {
+        self.font.as_deref()
+    }
+
+    /// Look up a string by key
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// Look up a string by key, and fill in its `{placeholders}` by name
+    pub fn text_with(&self, key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        args.iter()
+            .fold(self.text(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), &value.to_string())
+            })
+    }
+
+    /// Every character this language's strings use, along with printable ASCII for anything typed or generated.
+    /// Fonts only have glyphs for the characters they are loaded with
+    pub fn codepoints(&self) -> Vec<i32> {
+        let mut codepoints: BTreeSet<i32> = (32..127).collect();
+        for text in self.strings.values().chain(self.fallback.values()) {
+            codepoints.extend(text.chars().map(|c| c as i32));
+        }
+        codepoints.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_language_has_every_string() {
+        let english = load_locale_file(DEFAULT_LANGUAGE).unwrap().strings;
+        for (language, _) in LANGUAGES {
+            let strings = load_locale_file(language).unwrap().strings;
+            for key in english.keys() {
+                assert!(strings.contains_key(key), "{} is missing {}", language, key);
+            }
+            for (key, text) in &strings {
+                assert!(english.contains_key(key), "{} has unknown key {}", language, key);
+
+                // Translations must use the same placeholders
+                let placeholders = |text: &str| {
+                    text.split('{')
+                        .skip(1)
+                        .filter_map(|part| part.split('}').next().map(str::to_string))
+                        .collect::<BTreeSet<_>>()
+                };
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(&english[key]),
+                    "{} has different placeholders in {}",
+                    language,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_falls_back_to_english_then_the_key() {
+        let mut locale = Locale::load("es").unwrap();
+        locale.strings.remove("main_menu.quit");
+        assert_eq!(locale.text("main_menu.quit"), "QUIT");
+        assert_eq!(locale.text("not.a.key"), "not.a.key");
+        assert_eq!(
+            locale.text_with("hud.attempt", &[("count", &3)]),
+            "INTENTO 3"
+        );
+    }
+
+    #[test]
+    fn test_language_fonts_are_shipped() {
+        for (language, _) in LANGUAGES {
+            if let Some(font) = load_locale_file(language).unwrap().font {
+                assert!(
+                    StaticGameData::get(&font).is_some(),
+                    "{} needs missing font {}",
+                    language,
+                    font
+                );
+            }
+        }
+    }
+}
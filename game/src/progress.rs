@@ -1,40 +1,196 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use chrono::Duration;
-use tracing::info;
+use chrono::{Duration, NaiveDate};
+use tracing::{info, warn};
+
+/// A level's best time, along with the version of the level it was set on
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LevelRecord {
+    /// Time in milliseconds
+    #[serde(default)]
+    pub time_ms: i64,
+    /// Time in whole seconds, from saves made before times were kept in milliseconds. Moved to `time_ms` on load
+    #[serde(default, rename = "time", skip_serializing)]
+    legacy_time: Option<i64>,
+    /// The level's content hash when this time was set. This is `None` for times carried over from old saves
+    pub content_hash: Option<u64>,
+    /// The time each of the level's split lines was crossed on the best run, in milliseconds
+    #[serde(default)]
+    pub splits_ms: Vec<i64>,
+}
+
+impl LevelRecord {
+    /// Check if this time was set on the current version of a level
+    pub fn is_current(&self, content_hash: u64) -> bool {
+        self.content_hash == Some(content_hash)
+    }
+
+    pub fn time(&self) -> Duration {
+        Duration::milliseconds(self.time_ms)
+    }
+
+    pub fn splits(&self) -> Vec<Duration> {
+        self.splits_ms
+            .iter()
+            .map(|split| Duration::milliseconds(*split))
+            .collect()
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct ProgressData {
-    pub level_best_times: HashMap<usize, i64>,
+    /// Best times, keyed by level name
+    #[serde(default)]
+    pub level_records: HashMap<String, LevelRecord>,
+    /// Best times in seconds from old saves, keyed by position in `levels.json`. These are moved to `level_records` on load
+    #[serde(default, rename = "level_best_times", skip_serializing)]
+    legacy_best_times: HashMap<usize, i64>,
+    /// The furthest distance reached in endless mode, in meters
+    #[serde(default)]
+    pub endless_best_distance: u64,
+    /// Best daily challenge times in milliseconds, keyed by the challenge's date
+    #[serde(default)]
+    pub daily_best_times_ms: HashMap<String, i64>,
+    /// Best daily challenge times in seconds from old saves. These are moved to `daily_best_times_ms` on load
+    #[serde(default, rename = "daily_best_times", skip_serializing)]
+    legacy_daily_best_times: HashMap<String, i64>,
+    /// Codes for every secret level that has been found
+    #[serde(default)]
+    pub unlocked_secrets: HashSet<String>,
+}
+
+/// The key used for a daily challenge's date in the save file
+fn daily_challenge_key(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
 }
 
 impl ProgressData {
-    pub fn get_level_best_time(&self, level: usize) -> Option<Duration> {
-        let level_best_time = self.level_best_times.get(&level);
-        match level_best_time {
-            Some(time) => Some(Duration::seconds(*time)),
-            None => None,
+    pub fn get_level_record(&self, level_id: &str) -> Option<&LevelRecord> {
+        self.level_records.get(level_id)
+    }
+
+    pub fn get_level_best_time(&self, level_id: &str) -> Option<Duration> {
+        self.get_level_record(level_id).map(LevelRecord::time)
+    }
+
+    /// Check if a level has ever been beaten, on any version of it
+    pub fn is_level_beaten(&self, level_id: &str) -> bool {
+        self.level_records.contains_key(level_id)
+    }
+
+    /// Save a level's time, and the splits from the same run, if it is the best one.
+    /// Times set on an older version of the level are always replaced
+    pub fn maybe_write_new_time(
+        &mut self,
+        level_id: &str,
+        content_hash: u64,
+        time: &Duration,
+        splits: &[Duration],
+    ) {
+        let time_ms = time.num_milliseconds();
+        let is_better = match self.get_level_record(level_id) {
+            Some(record) => !record.is_current(content_hash) || record.time_ms > time_ms,
+            None => true,
+        };
+        if is_better {
+            self.level_records.insert(
+                level_id.to_string(),
+                LevelRecord {
+                    time_ms,
+                    legacy_time: None,
+                    content_hash: Some(content_hash),
+                    splits_ms: splits.iter().map(Duration::num_milliseconds).collect(),
+                },
+            );
+        }
+    }
+
+    /// Move any best times from an old save over to level ids.
+    /// `level_ids` must be in the same order as `levels.json`, which has only ever had levels added to the end.
+    /// Returns `true` if anything was moved
+    pub fn migrate_legacy_times(&mut self, level_ids: &[&str]) -> bool {
+        if self.legacy_best_times.is_empty() {
+            return false;
+        }
+        info!(
+            "Migrating {} best times from an old save",
+            self.legacy_best_times.len()
+        );
+        for (idx, time) in self.legacy_best_times.drain() {
+            match level_ids.get(idx) {
+                // There is no telling which version of the level these were set on
+                Some(level_id) => {
+                    self.level_records
+                        .entry(level_id.to_string())
+                        .or_insert(LevelRecord {
+                            time_ms: time * 1000,
+                            legacy_time: None,
+                            content_hash: None,
+                            splits_ms: Vec::new(),
+                        });
+                }
+                None => warn!("Dropping best time for unknown level {}", idx),
+            }
         }
+        true
     }
 
-    pub fn maybe_write_new_time(&mut self, level: usize, time: &Duration) {
-        let time_in_seconds = time.num_seconds();
-        if let Some(best_time) = self.get_level_best_time(level) {
-            if best_time.num_seconds() > time_in_seconds {
-                self.level_best_times.insert(level, time_in_seconds);
+    /// Move times saved in whole seconds over to milliseconds
+    fn migrate_second_times(&mut self) {
+        for record in self.level_records.values_mut() {
+            if let Some(time) = record.legacy_time.take() {
+                record.time_ms = time * 1000;
             }
-        } else {
-            self.level_best_times.insert(level, time_in_seconds);
+        }
+        for (date, time) in self.legacy_daily_best_times.drain() {
+            self.daily_best_times_ms.entry(date).or_insert(time * 1000);
+        }
+    }
+
+    pub fn maybe_write_endless_distance(&mut self, distance: u64) {
+        if distance > self.endless_best_distance {
+            self.endless_best_distance = distance;
         }
     }
 
+    pub fn get_daily_best_time(&self, date: NaiveDate) -> Option<Duration> {
+        self.daily_best_times_ms
+            .get(&daily_challenge_key(date))
+            .map(|time| Duration::milliseconds(*time))
+    }
+
+    pub fn maybe_write_daily_time(&mut self, date: NaiveDate, time: &Duration) {
+        let time_ms = time.num_milliseconds();
+        let best_time = self
+            .daily_best_times_ms
+            .entry(daily_challenge_key(date))
+            .or_insert(time_ms);
+        *best_time = (*best_time).min(time_ms);
+    }
+
+    /// Count how many days in a row the daily challenge has been beaten.
+    /// The streak is not broken until the end of `today`, so it counts back from yesterday if today is not beaten yet
+    pub fn daily_streak(&self, today: NaiveDate) -> u32 {
+        let mut day = today;
+        if self.get_daily_best_time(day).is_none() {
+            day = day.pred();
+        }
+        let mut streak = 0;
+        while self.get_daily_best_time(day).is_some() {
+            streak += 1;
+            day = day.pred();
+        }
+        streak
+    }
+
     pub fn load_from_file() -> Self {
         info!("Loading progress data from file");
-        serde_json::from_str(
-            &std::fs::read_to_string("./savegame.json")
-                .unwrap_or("{\"level_best_times\":{}}".to_string()),
+        let mut progress: Self = serde_json::from_str(
+            &std::fs::read_to_string("./savegame.json").unwrap_or("{}".to_string()),
         )
-        .unwrap_or(Self::default())
+        .unwrap_or(Self::default());
+        progress.migrate_second_times();
+        progress
     }
 
     pub fn save(&self) {
@@ -42,3 +198,115 @@ impl ProgressData {
         std::fs::write("./savegame.json", serde_json::to_string(self).unwrap()).unwrap()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_times_from_old_level_versions_are_replaced() {
+        let mut progress = ProgressData::default();
+        progress.maybe_write_new_time("level_0", 1, &Duration::seconds(30), &[]);
+        progress.maybe_write_new_time("level_0", 1, &Duration::seconds(40), &[]);
+        assert_eq!(
+            progress.get_level_best_time("level_0"),
+            Some(Duration::seconds(30))
+        );
+
+        // The level changed, so the old time no longer means anything
+        progress.maybe_write_new_time("level_0", 2, &Duration::seconds(40), &[]);
+        let record = progress.get_level_record("level_0").unwrap();
+        assert_eq!(record.time_ms, 40_000);
+        assert!(record.is_current(2));
+    }
+
+    #[test]
+    fn test_migrate_legacy_times() {
+        let mut progress: ProgressData =
+            serde_json::from_str("{\"level_best_times\":{\"0\":30,\"1\":45,\"9\":10}}").unwrap();
+        assert!(progress.migrate_legacy_times(&["level_0", "level_1"]));
+        assert!(!progress.migrate_legacy_times(&["level_0", "level_1"]));
+
+        let record = progress.get_level_record("level_1").unwrap();
+        assert_eq!(record.time_ms, 45_000);
+        assert_eq!(record.content_hash, None);
+        assert_eq!(progress.level_records.len(), 2);
+
+        // Old times are not written back out
+        assert!(!serde_json::to_string(&progress)
+            .unwrap()
+            .contains("level_best_times"));
+    }
+
+    #[test]
+    fn test_times_keep_milliseconds_and_splits() {
+        let mut progress = ProgressData::default();
+        let splits = [Duration::milliseconds(4_250), Duration::milliseconds(9_001)];
+        progress.maybe_write_new_time("level_0", 1, &Duration::milliseconds(12_345), &splits);
+        progress.maybe_write_new_time("level_0", 1, &Duration::milliseconds(12_346), &[]);
+
+        let record = progress.get_level_record("level_0").unwrap();
+        assert_eq!(record.time(), Duration::milliseconds(12_345));
+        assert_eq!(record.splits(), splits.to_vec());
+    }
+
+    #[test]
+    fn test_migrate_second_times() {
+        let mut progress: ProgressData = serde_json::from_str(
+            "{\"level_records\":{\"level_0\":{\"time\":30,\"content_hash\":1}},\"daily_best_times\":{\"2021-10-03\":45}}",
+        )
+        .unwrap();
+        progress.migrate_second_times();
+        assert_eq!(
+            progress.get_level_best_time("level_0"),
+            Some(Duration::seconds(30))
+        );
+        assert_eq!(
+            progress.get_daily_best_time(NaiveDate::from_ymd(2021, 10, 3)),
+            Some(Duration::seconds(45))
+        );
+
+        // Only the new fields are written back out
+        let saved = serde_json::to_string(&progress).unwrap();
+        assert!(saved.contains("\"time_ms\":30000"));
+        assert!(!saved.contains("\"time\""));
+        assert!(!saved.contains("\"daily_best_times\""));
+    }
+
+    #[test]
+    fn test_daily_streak() {
+        let today = NaiveDate::from_ymd(2021, 10, 3);
+        let mut progress = ProgressData::default();
+        assert_eq!(progress.daily_streak(today), 0);
+
+        // Beating yesterday and the day before counts, even before today is beaten
+        progress.maybe_write_daily_time(NaiveDate::from_ymd(2021, 10, 2), &Duration::seconds(40));
+        progress.maybe_write_daily_time(NaiveDate::from_ymd(2021, 10, 1), &Duration::seconds(40));
+        assert_eq!(progress.daily_streak(today), 2);
+
+        progress.maybe_write_daily_time(today, &Duration::seconds(40));
+        assert_eq!(progress.daily_streak(today), 3);
+
+        // A missed day ends the streak
+        progress.maybe_write_daily_time(NaiveDate::from_ymd(2021, 9, 29), &Duration::seconds(40));
+        assert_eq!(progress.daily_streak(today), 3);
+        assert_eq!(progress.daily_streak(NaiveDate::from_ymd(2021, 10, 5)), 0);
+    }
+
+    #[test]
+    fn test_daily_best_time_only_improves() {
+        let date = NaiveDate::from_ymd(2021, 10, 3);
+        let mut progress = ProgressData::default();
+        progress.maybe_write_daily_time(date, &Duration::seconds(50));
+        progress.maybe_write_daily_time(date, &Duration::seconds(60));
+        assert_eq!(
+            progress.get_daily_best_time(date),
+            Some(Duration::seconds(50))
+        );
+        progress.maybe_write_daily_time(date, &Duration::seconds(45));
+        assert_eq!(
+            progress.get_daily_best_time(date),
+            Some(Duration::seconds(45))
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
 use chrono::{DateTime, Utc};
 use dirty_fsm::{Action, ActionFlag};
@@ -6,13 +9,20 @@ use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
 use raylib::prelude::*;
 
-use crate::{GameConfig, context::{ControlFlag, GameContext}, utilities::{
+use crate::{
+    context::GameContext,
+    locale::Locale,
+    utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
-    }};
+        widgets::UiState,
+    },
+    GameConfig,
+};
 
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, info, trace};
@@ -20,7 +30,9 @@ use tracing::{debug, error, info, trace};
 #[derive(Debug)]
 pub struct CheaterScreen {
     is_menu_pressed: bool, //Is menu button pressed
+    ui: UiState,
     counter: i32,
+    locale: Rc<Locale>,
 }
 
 impl CheaterScreen {
@@ -28,7 +40,9 @@ impl CheaterScreen {
     pub fn new() -> Self {
         Self {
             is_menu_pressed: false,
+            ui: UiState::default(),
             counter: 0,
+            locale: Rc::default(),
         }
     }
 }
@@ -44,7 +58,7 @@ impl Action<Scenes, ScreenError, GameContext> for CheaterScreen {
 
         if let Err(e) = context.discord_rpc_send.send(Some(
             ActivityBuilder::default()
-                .details("somehow won the game")
+                .details(context.locale.text("discord.cheater"))
                 .assets(
                     Assets::default().large("game-logo-small", Some(context.config.name.clone())),
                 ),
@@ -61,14 +75,13 @@ impl Action<Scenes, ScreenError, GameContext> for CheaterScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on CheaterScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
         self.counter += 1;
 
         if self.is_menu_pressed {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
             Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
         } else {
             Ok(ActionFlag::Continue)
@@ -78,6 +91,7 @@ impl Action<Scenes, ScreenError, GameContext> for CheaterScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished CheaterScreen");
         self.is_menu_pressed = false;
+        self.ui.reset();
         self.counter = 0;
 
         Ok(())
@@ -91,6 +105,7 @@ impl ScreenSpaceRender for CheaterScreen {
         config: &GameConfig,
     ) {
         let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
 
         // Render the background
         raylib.clear_background(Color::BLACK);
@@ -102,14 +117,9 @@ impl ScreenSpaceRender for CheaterScreen {
             config.colors.white,
         );
 
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
-
         raylib.draw_rgb_split_text(
-            Vector2::new(100.0, screen_size.y as f32 / 2.0 - 120.0),
-            "what are you doing.",
+            layout.at(Anchor::Left, Vector2::new(100.0, -120.0)),
+            self.locale.text("cheater.what"),
             30,
             false,
             Color::WHITE,
@@ -117,8 +127,8 @@ impl ScreenSpaceRender for CheaterScreen {
 
         if self.counter > 100 {
             raylib.draw_rgb_split_text(
-                Vector2::new(100.0, screen_size.y as f32 / 2.0 - 60.0),
-                "don't cheat.",
+                layout.at(Anchor::Left, Vector2::new(100.0, -60.0)),
+                self.locale.text("cheater.dont_cheat"),
                 30,
                 false,
                 Color::WHITE,
@@ -126,8 +136,8 @@ impl ScreenSpaceRender for CheaterScreen {
         }
         if self.counter > 300 {
             raylib.draw_rgb_split_text(
-                Vector2::new(100.0, screen_size.y as f32 / 2.0 + 50.0),
-                "get good.",
+                layout.at(Anchor::Left, Vector2::new(100.0, 50.0)),
+                self.locale.text("cheater.get_good"),
                 30,
                 false,
                 Color::WHITE,
@@ -136,27 +146,13 @@ impl ScreenSpaceRender for CheaterScreen {
 
         //Return to Main Menu
         if self.counter > 450 {
-            if Rectangle::new(100.0, screen_size.y as f32 / 2.0 + 90.0, 270.0, 20.0)
-                .check_collision_point_rec(mouse_position)
-            {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(100.0, screen_size.y as f32 / 2.0 + 100.0),
-                    ">> RETURN TO MAIN MENU",
-                    20,
-                    true,
-                    Color::WHITE,
-                );
-
-                self.is_menu_pressed = mouse_pressed
-            } else {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(100.0, screen_size.y as f32 / 2.0 + 100.0),
-                    ">> RETURN TO MAIN MENU",
-                    20,
-                    false,
-                    Color::WHITE,
-                );
-            }
+            let locale = self.locale.clone();
+            let mut ui = self.ui.begin(raylib);
+            self.is_menu_pressed = ui.button(
+                layout.at(Anchor::Left, Vector2::new(100.0, 100.0)),
+                locale.text("ui.return_to_main_menu"),
+                20,
+            ) || ui.back_pressed();
         }
     }
 }
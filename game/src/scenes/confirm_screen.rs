@@ -0,0 +1,175 @@
+use std::rc::Rc;
+
+use dirty_fsm::{Action, ActionFlag};
+use raylib::prelude::*;
+
+use crate::{
+    context::{ControlFlag, GameContext},
+    locale::Locale,
+    utilities::{layout::Anchor, render_layer::ScreenSpaceRender, widgets::UiState},
+    GameConfig,
+};
+
+use super::{Scenes, ScreenError};
+use tracing::{debug, trace};
+
+/// Questions the confirm screen can ask. Set with `ControlFlag::OpenDialog` before switching to the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialog {
+    LeaveLevel,
+    QuitGame,
+}
+
+impl Default for Dialog {
+    fn default() -> Self {
+        Self::QuitGame
+    }
+}
+
+impl Dialog {
+    /// Key for the question in the string tables
+    fn question_key(&self) -> &'static str {
+        match self {
+            Self::LeaveLevel => "dialog.leave_level",
+            Self::QuitGame => "dialog.quit_game",
+        }
+    }
+
+    /// Key for the line under the question in the string tables
+    fn detail_key(&self) -> &'static str {
+        match self {
+            Self::LeaveLevel => "dialog.leave_level_detail",
+            Self::QuitGame => "dialog.quit_game_detail",
+        }
+    }
+}
+
+/// A yes or no question shown over whatever scene opened it
+#[derive(Debug)]
+pub struct ConfirmScreen {
+    dialog: Dialog,
+    is_yes_pressed: bool,
+    is_no_pressed: bool,
+    ui: UiState,
+    locale: Rc<Locale>,
+}
+
+impl ConfirmScreen {
+    /// Construct a new `ConfirmScreen`
+    pub fn new() -> Self {
+        Self {
+            dialog: Dialog::default(),
+            is_yes_pressed: false,
+            is_no_pressed: false,
+            ui: UiState::default(),
+            locale: Rc::default(),
+        }
+    }
+}
+
+impl Action<Scenes, ScreenError, GameContext> for ConfirmScreen {
+    fn on_register(&mut self) -> Result<(), ScreenError> {
+        debug!("Registered");
+        Ok(())
+    }
+
+    fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
+        debug!("Running ConfirmScreen for the first time");
+        self.dialog = context.dialog;
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        _delta: &chrono::Duration,
+        context: &GameContext,
+    ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
+        trace!("execute() called on ConfirmScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
+        self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
+
+        let opener = context
+            .scene_stack
+            .opener()
+            .unwrap_or(Scenes::MainMenuScreen);
+        if self.is_yes_pressed {
+            match self.dialog {
+                Dialog::LeaveLevel => {
+                    // A paused endless run is over once it is left, so save how far it got
+                    if let Some(distance) = context.endless_distance {
+                        context
+                            .flag_send
+                            .send(Some(ControlFlag::EndEndlessRun(distance)))
+                            .unwrap();
+                    }
+                    Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
+                }
+                Dialog::QuitGame => {
+                    context.flag_send.send(Some(ControlFlag::Quit)).unwrap();
+                    Ok(ActionFlag::Continue)
+                }
+            }
+        } else if self.is_no_pressed {
+            Ok(ActionFlag::SwitchState(opener))
+        } else {
+            Ok(ActionFlag::Continue)
+        }
+    }
+
+    fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
+        debug!("Finished ConfirmScreen");
+        self.is_yes_pressed = false;
+        self.is_no_pressed = false;
+        self.ui.reset();
+        Ok(())
+    }
+}
+
+impl ScreenSpaceRender for ConfirmScreen {
+    fn render_screen_space(
+        &mut self,
+        raylib: &mut crate::utilities::non_ref_raylib::HackedRaylibHandle,
+        config: &GameConfig,
+    ) {
+        let screen_size = raylib.get_screen_size();
+
+        // Dim the frozen scene behind the dialog, and draw a box for the dialog itself
+        raylib.draw_rectangle_v(Vector2::zero(), screen_size, Color::BLACK.fade(0.6));
+        let layout = raylib.layout();
+        let dialog_box = layout.rect(Anchor::Center, Vector2::zero(), Vector2::new(500.0, 220.0));
+        raylib.draw_rectangle_rec(dialog_box, Color::BLACK);
+        raylib.draw_rectangle_lines_ex(
+            dialog_box,
+            layout.scaled(2.0).round() as i32,
+            config.colors.white,
+        );
+
+        let locale = self.locale.clone();
+        let mut ui = self.ui.begin(raylib);
+        // Offsets inside the box are in design pixels too
+        let inside = |x: f32, y: f32| {
+            Vector2::new(
+                dialog_box.x + layout.scaled(x).round(),
+                dialog_box.y + layout.scaled(y).round(),
+            )
+        };
+        ui.label(
+            inside(30.0, 30.0),
+            locale.text(self.dialog.question_key()),
+            40,
+        );
+        let detail = ui.raylib.wrap_ui_text(
+            locale.text(self.dialog.detail_key()),
+            20,
+            dialog_box.width - layout.scaled(60.0),
+        );
+        ui.label(inside(30.0, 85.0), &detail, 20);
+
+        // No comes first, so confirming by accident takes more than one key
+        self.is_no_pressed =
+            ui.button(inside(30.0, 160.0), locale.text("ui.no"), 30) || ui.back_pressed();
+        self.is_yes_pressed = ui.button(inside(250.0, 160.0), locale.text("ui.yes"), 30);
+    }
+}
@@ -1,26 +1,47 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use dirty_fsm::{Action, ActionFlag};
 use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
 use raylib::prelude::*;
 
-use crate::{GameConfig, context::{ControlFlag, GameContext}, utilities::{
+use crate::{
+    context::{ControlFlag, GameContext},
+    input::InputAction,
+    locale::Locale,
+    scenes::ingame_scene::timer::format_time,
+    utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
-    }};
+        widgets::UiState,
+    },
+    GameConfig,
+};
 
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, info, trace};
 
+/// How long the death screen stays up before retrying on its own, when auto respawn is turned on
+const AUTO_RESPAWN_DELAY_MS: i64 = 1200;
+
 #[derive(Debug)]
 pub struct DeathScreen {
     is_retry_pressed: bool,
     timer_value: String,
+    death_time: DateTime<Utc>,
+    /// How long until the level is retried automatically. `None` if auto respawn is off
+    respawn_countdown: Option<Duration>,
+    restart_key: String,
+    ui: UiState,
+    locale: Rc<Locale>,
 }
 
 impl DeathScreen {
@@ -28,7 +49,12 @@ impl DeathScreen {
     pub fn new() -> Self {
         Self {
             is_retry_pressed: false,
-            timer_value: "XX:XX".to_string(),
+            timer_value: "XX:XX.XXX".to_string(),
+            death_time: Utc::now(),
+            respawn_countdown: None,
+            restart_key: String::new(),
+            ui: UiState::default(),
+            locale: Rc::default(),
         }
     }
 }
@@ -41,11 +67,17 @@ impl Action<Scenes, ScreenError, GameContext> for DeathScreen {
 
     fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
         debug!("Running DeathScreen for the first time");
+        self.death_time = Utc::now();
+
+        // Retrying is the only thing to do here, so confirm should do it straight away
+        self.ui.focus_first();
 
         if let Err(e) = context.discord_rpc_send.send(Some(
-            ActivityBuilder::default().details("dead... again").assets(
-                Assets::default().large("game-logo-small", Some(context.config.name.clone())),
-            ),
+            ActivityBuilder::default()
+                .details(context.locale.text("discord.dead"))
+                .assets(
+                    Assets::default().large("game-logo-small", Some(context.config.name.clone())),
+                ),
         )) {
             error!("Failed to update discord: {}", e);
         }
@@ -59,20 +91,41 @@ impl Action<Scenes, ScreenError, GameContext> for DeathScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on DeathScreen");
+        self.ui.read_input(context);
+        self.timer_value = format_time(&context.level_timer.elapsed());
+        self.restart_key = context
+            .settings
+            .input_bindings
+            .primary_name(InputAction::Restart);
+        self.respawn_countdown = context.settings.auto_respawn.then(|| {
+            Duration::milliseconds(AUTO_RESPAWN_DELAY_MS) - (Utc::now() - self.death_time)
+        });
+
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
+
+        // Restarting also throws away the time, while retrying keeps it going
+        let is_restart = context.input.is_pressed(InputAction::Restart);
+        let is_respawn_due = self
+            .respawn_countdown
+            .map_or(false, |countdown| countdown <= Duration::zero());
+        if is_restart {
+            context
+                .flag_send
+                .send(Some(ControlFlag::RestartLevel))
+                .unwrap();
+        }
 
-        let elapsed = Utc::now() - context.level_start_time;
-        self.timer_value = format!("{:02}:{:02}", elapsed.num_minutes(), elapsed.num_seconds() % 60);
-
-        if self.is_retry_pressed ||
-        context
-            .renderer
-            .borrow_mut()
-            .is_key_pressed(KeyboardKey::KEY_SPACE){
+        // The retry button plays its own click sound
+        if is_restart || is_respawn_due {
             context
                 .flag_send
                 .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
                 .unwrap();
+        }
+
+        if is_restart || is_respawn_due || self.is_retry_pressed {
             Ok(ActionFlag::SwitchState(Scenes::InGameScene))
         } else {
             Ok(ActionFlag::Continue)
@@ -82,6 +135,7 @@ impl Action<Scenes, ScreenError, GameContext> for DeathScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished DeathScreen");
         self.is_retry_pressed = false;
+        self.ui.reset();
         Ok(())
     }
 }
@@ -95,62 +149,35 @@ impl ScreenSpaceRender for DeathScreen {
         // Render the background
         raylib.clear_background(Color::DARKBLUE);
 
-        let screen_size = raylib.get_screen_size();
-
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
-
-        raylib.draw_text(
-            &format!(
-                "ERR: Corrupted Player Data Detected
+        let layout = raylib.layout();
 
-The program has detected lowering player integrity,
-and has halted as a safety precaution.
-
-If this is the first time you've seen this error screen,
-restart the level. If problems continue, simply get good.
-
-The timer has not been reset. You are wasting time
-reading this message. GLHF ;)
-
---------   Technical information   --------
-*** CALL STACK:
-*** C  [libraylib.so+0x75c] END_DRAWING()
-*** RS [data_loss.so+0x48f] validate_player()
-*** ---------------------------------------
-*** PROGRAM_HALT (TIMER: {})
-*** ---------------------------------------",
-                self.timer_value
-            ),
-            25,
-            20,
+        let locale = self.locale.clone();
+        raylib.draw_ui_text(
+            &locale.text_with("death.error", &[("time", &self.timer_value)]),
+            layout.at(Anchor::TopLeft, Vector2::new(25.0, 20.0)),
             20,
             Color::WHITE,
         );
 
-        //Retry
-        if Rectangle::new(35.0, screen_size.y as f32 - 80.0, 200.0, 40.0)
-            .check_collision_point_rec(mouse_position)
-        {
-            raylib.draw_text(
-                ">>CLICK HERE TO RETRY",
-                20,
-                screen_size.y as i32 - 40,
-                19,
-                Color::WHITE,
-            );
-
-            self.is_retry_pressed = mouse_pressed
-        } else {
-            raylib.draw_text(
-                ">>CLICK HERE TO RETRY",
-                25,
-                screen_size.y as i32 - 40,
+        // Quick retry hints
+        raylib.draw_ui_text(
+            &locale.text_with("death.restart_hint", &[("key", &self.restart_key)]),
+            layout.at(Anchor::BottomLeft, Vector2::new(25.0, -130.0)),
+            18,
+            Color::WHITE,
+        );
+        if let Some(countdown) = &self.respawn_countdown {
+            let seconds = format!("{:.1}", countdown.num_milliseconds().max(0) as f32 / 1000.0);
+            raylib.draw_ui_text(
+                &locale.text_with("death.auto_retry", &[("seconds", &seconds)]),
+                layout.at(Anchor::BottomLeft, Vector2::new(25.0, -105.0)),
                 18,
                 Color::WHITE,
             );
         }
+
+        let mut ui = self.ui.begin(raylib);
+        let retry_position = ui.at(Anchor::BottomLeft, Vector2::new(25.0, -40.0));
+        self.is_retry_pressed = ui.button(retry_position, locale.text("death.retry"), 18);
     }
 }
@@ -1,4 +1,7 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
 use chrono::{DateTime, Utc};
 use dirty_fsm::{Action, ActionFlag};
@@ -6,13 +9,21 @@ use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
 use raylib::prelude::*;
 
-use crate::{GameConfig, context::{ControlFlag, GameContext}, utilities::{
+use crate::{
+    context::GameContext,
+    input::InputAction,
+    locale::Locale,
+    utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
-    }};
+        widgets::UiState,
+    },
+    GameConfig,
+};
 
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, info, trace};
@@ -21,6 +32,10 @@ use tracing::{debug, error, info, trace};
 pub struct HowToPlayScreen {
     is_btm_pressed: bool, //Is back to menu button pressed
     counter: i32,
+    /// Instructions using the player's current key bindings
+    instructions: String,
+    ui: UiState,
+    locale: Rc<Locale>,
 }
 
 impl HowToPlayScreen {
@@ -29,6 +44,9 @@ impl HowToPlayScreen {
         Self {
             is_btm_pressed: false,
             counter: 0,
+            instructions: String::new(),
+            ui: UiState::default(),
+            locale: Rc::default(),
         }
     }
 }
@@ -44,7 +62,7 @@ impl Action<Scenes, ScreenError, GameContext> for HowToPlayScreen {
 
         if let Err(e) = context.discord_rpc_send.send(Some(
             ActivityBuilder::default()
-                .details("learning how to play")
+                .details(context.locale.text("discord.how_to_play"))
                 .assets(
                     Assets::default().large("game-logo-small", Some(context.config.name.clone())),
                 ),
@@ -52,6 +70,16 @@ impl Action<Scenes, ScreenError, GameContext> for HowToPlayScreen {
             error!("Failed to update discord: {}", e);
         }
 
+        let bindings = &context.settings.input_bindings;
+        self.instructions = context.locale.text_with(
+            "how_to_play.instructions",
+            &[
+                ("jump", &bindings.primary_name(InputAction::Jump)),
+                ("dash", &bindings.primary_name(InputAction::Dash)),
+                ("restart", &bindings.primary_name(InputAction::Restart)),
+            ],
+        );
+
         Ok(())
     }
 
@@ -61,15 +89,14 @@ impl Action<Scenes, ScreenError, GameContext> for HowToPlayScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on HowToPlayScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
 
         self.counter += 1;
 
         if self.is_btm_pressed {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
             Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
         } else {
             Ok(ActionFlag::Continue)
@@ -79,6 +106,7 @@ impl Action<Scenes, ScreenError, GameContext> for HowToPlayScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished HowToPlayScreen");
         self.is_btm_pressed = false;
+        self.ui.reset();
         self.counter = 0;
         Ok(())
     }
@@ -91,6 +119,7 @@ impl ScreenSpaceRender for HowToPlayScreen {
         config: &GameConfig,
     ) {
         let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
 
         // Render the background
         raylib.clear_background(Color::BLACK);
@@ -102,56 +131,30 @@ impl ScreenSpaceRender for HowToPlayScreen {
             config.colors.white,
         );
 
-        let screen_size = raylib.get_screen_size();
-
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        let locale = self.locale.clone();
+        let mut ui = self.ui.begin(raylib);
 
         //Render the title
-        let timer: i32 = get_random_value(50, 400);
-            if self.counter > timer {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(40.0, 80.0),
-                    "[How to Play]",
-                    70,
-                    true,
-                    Color::WHITE,
-                );
-                if self.counter > timer + 20 {
-                    self.counter = 0;
-                }
-            }
-            else{
-                raylib.draw_rgb_split_text(
-                    Vector2::new(40.0, 80.0),
-                    "[How to Play]",
-                    70,
-                    false,
-                    Color::WHITE,
-                );
-            }
+        ui.title(
+            layout.at(Anchor::TopLeft, Vector2::new(40.0, 80.0)),
+            &format!("[{}]", locale.text("how_to_play.title")),
+            &mut self.counter,
+        );
 
         // Render the instructions
-        raylib.draw_rgb_split_text(
-            Vector2::new(100.0, 300.0),
-            ">> SPACE to jump\n>> SHIFT to dash\n>> Marcelo made these maps\n>> Marcelo hates you",
+        ui.raylib.draw_rgb_split_text(
+            layout.at(Anchor::TopLeft, Vector2::new(100.0, 300.0)),
+            &self.instructions,
             45,
             true,
             Color::WHITE,
         );
 
         //Back to Menu
-        let hovering_back_button = Rectangle::new(35.0, screen_size.y as f32 - 80.0, 200.0, 40.0)
-            .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(25.0, screen_size.y - 50.0),
-            "BACK TO MENU",
+        self.is_btm_pressed = ui.button(
+            layout.at(Anchor::BottomLeft, Vector2::new(50.0, -50.0)),
+            locale.text("ui.back_to_menu"),
             25,
-            hovering_back_button,
-            Color::WHITE,
-        );
-        self.is_btm_pressed = hovering_back_button && mouse_pressed;
+        ) || ui.back_pressed();
     }
 }
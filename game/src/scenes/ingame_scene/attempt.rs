@@ -0,0 +1,111 @@
+//! Attempts at a level, and picking one back up after an overlay like the pause menu is closed
+
+use chrono::{DateTime, Utc};
+use raylib::math::Vector2;
+
+use super::timer::LevelTimer;
+
+/// How many times the current level has been tried. Only real starts count: starting it from a menu,
+/// retrying after dying, or restarting. Coming back from an overlay does not
+#[derive(Debug, Default)]
+pub struct Attempts {
+    count: u32,
+    /// The level start being counted for. A new level start resets the count
+    level_start: Option<DateTime<Utc>>,
+}
+
+impl Attempts {
+    /// Count a new attempt at the level started at `level_start`
+    pub fn start(&mut self, level_start: DateTime<Utc>) {
+        if self.level_start != Some(level_start) {
+            self.level_start = Some(level_start);
+            self.count = 0;
+        }
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Where the player was when an overlay was opened over the level
+#[derive(Debug, Clone)]
+pub struct ResumePoint {
+    /// The level start this belongs to. Starting any level changes it, so a run that was left is never resumed
+    level_start: DateTime<Utc>,
+    pub position: Vector2,
+    pub timer: LevelTimer,
+}
+
+impl ResumePoint {
+    pub fn new(level_start: DateTime<Utc>, position: Vector2, timer: LevelTimer) -> Self {
+        Self {
+            level_start,
+            position,
+            timer,
+        }
+    }
+
+    /// Take the resume point if the scene is coming back to the same level start. `None` means a new attempt
+    pub fn take_for(point: &mut Option<Self>, level_start: DateTime<Utc>) -> Option<Self> {
+        point
+            .take()
+            .filter(|point| point.level_start == level_start)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_attempts_only_count_real_starts() {
+        let level_start = Utc::now();
+        let mut attempts = Attempts::default();
+        attempts.start(level_start);
+
+        // Pausing and resuming is not a new attempt
+        let mut point = Some(ResumePoint::new(
+            level_start,
+            Vector2::zero(),
+            LevelTimer::default(),
+        ));
+        assert!(ResumePoint::take_for(&mut point, level_start).is_some());
+        assert_eq!(attempts.count(), 1);
+
+        // Dying and retrying, or restarting, is
+        attempts.start(level_start);
+        attempts.start(level_start);
+        assert_eq!(attempts.count(), 3);
+
+        // Starting the level again from a menu starts counting over
+        attempts.start(level_start + chrono::Duration::seconds(1));
+        assert_eq!(attempts.count(), 1);
+    }
+
+    #[test]
+    fn test_resuming_keeps_time_and_position() {
+        let level_start = Utc::now();
+        let mut timer = LevelTimer::default();
+        timer.tick(&chrono::Duration::milliseconds(16));
+        timer.tick(&chrono::Duration::milliseconds(16));
+        timer.update_splits(&[100.0], 150.0);
+        let position = Vector2::new(1234.0, -56.0);
+
+        // Pausing and then resuming picks up exactly where the player was
+        let mut point = Some(ResumePoint::new(level_start, position, timer.clone()));
+        let resumed = ResumePoint::take_for(&mut point, level_start).unwrap();
+        assert_eq!(resumed.position, position);
+        assert_eq!(resumed.timer.elapsed(), timer.elapsed());
+        assert_eq!(resumed.timer.splits(), timer.splits());
+        assert!(point.is_none());
+
+        // Leaving from the pause menu and starting again does not
+        let mut point = Some(ResumePoint::new(level_start, position, timer));
+        assert!(
+            ResumePoint::take_for(&mut point, level_start + chrono::Duration::seconds(1)).is_none()
+        );
+        assert!(point.is_none());
+    }
+}
@@ -1,18 +1,287 @@
-use crate::{GameConfig, utilities::render_layer::ScreenSpaceRender};
+use std::rc::Rc;
+
+use chrono::Duration;
 use raylib::prelude::*;
-use super::InGameScreen;
+
+use super::{
+    active_level,
+    level::endless::EndlessRun,
+    timer::{format_delta, format_time},
+    InGameScreen,
+};
+use crate::{
+    context::GameContext,
+    locale::Locale,
+    progress::LevelRecord,
+    settings::HudSettings,
+    utilities::{layout::Anchor, render_layer::ScreenSpaceRender, text::TextAlign},
+    GameConfig,
+};
+
+/// Everything the HUD shows. Gathered from the context before drawing, since rendering can not see it
+#[derive(Debug)]
+pub struct HudInfo {
+    settings: HudSettings,
+    level_name: String,
+    elapsed: Duration,
+    best_time: Option<Duration>,
+    /// The last split crossed, and how far ahead or behind the best run it was
+    split_delta: Option<(usize, Duration)>,
+    attempts: u32,
+    /// How far the player is towards the win line, from `0.0` to `1.0`. Not known for endless runs
+    progress: Option<f32>,
+    locale: Rc<Locale>,
+}
+
+impl Default for HudInfo {
+    fn default() -> Self {
+        Self {
+            settings: HudSettings::default(),
+            level_name: String::new(),
+            elapsed: Duration::zero(),
+            best_time: None,
+            split_delta: None,
+            attempts: 0,
+            progress: None,
+            locale: Rc::default(),
+        }
+    }
+}
+
+/// How far along a line from `start` to `end` a position is, from `0.0` to `1.0`
+fn progress_between(start: f32, end: f32, position: f32) -> f32 {
+    if end <= start {
+        return 0.0;
+    }
+    ((position - start) / (end - start)).clamp(0.0, 1.0)
+}
+
+impl InGameScreen {
+    /// Gather everything the HUD needs for this frame
+    pub(super) fn update_hud(&mut self, context: &GameContext) {
+        let level = active_level(&self.endless, context, self.current_level_idx);
+        let mut best_splits = Vec::new();
+        let (level_name, best_time) = if let Some(date) = context.daily_challenge {
+            (
+                context
+                    .locale
+                    .text_with("hud.daily", &[("date", &date.format("%Y-%m-%d"))]),
+                context.player_progress.get_daily_best_time(date),
+            )
+        } else if self.endless.is_some() {
+            (context.locale.text("hud.endless").to_string(), None)
+        } else {
+            // Records from an older version of the level were set on a different layout, so they are not compared
+            // against. The next finished run replaces them
+            let record = context
+                .player_progress
+                .get_level_record(&level.name)
+                .filter(|record| record.is_current(level.content_hash));
+            if let Some(record) = record {
+                best_splits = record.splits();
+            }
+
+            // Levels are named the same way as on the level select screen
+            let name = context
+                .campaigns
+                .iter()
+                .find_map(|campaign| {
+                    let position = campaign
+                        .levels
+                        .iter()
+                        .position(|idx| *idx == self.current_level_idx)?;
+                    Some(context.locale.text_with(
+                        "hud.level",
+                        &[("campaign", &campaign.name), ("level", &position)],
+                    ))
+                })
+                .unwrap_or_else(|| level.name.to_uppercase());
+            (name, record.map(LevelRecord::time))
+        };
+
+        let splits = self.timer.splits();
+        let split_delta = splits.len().checked_sub(1).and_then(|idx| {
+            best_splits
+                .get(idx)
+                .map(|best_split| (idx, splits[idx] - *best_split))
+        });
+
+        self.hud = HudInfo {
+            settings: context.settings.hud.clone(),
+            level_name,
+            elapsed: self.timer.elapsed(),
+            best_time,
+            split_delta,
+            attempts: self.attempts.count(),
+            progress: self.endless.is_none().then(|| {
+                progress_between(
+                    self.player.start_position.x,
+                    level.zones.win.x,
+                    self.player.position.x,
+                )
+            }),
+            locale: context.locale.clone(),
+        };
+    }
+}
 
 impl ScreenSpaceRender for InGameScreen {
     fn render_screen_space(
         &mut self,
         raylib: &mut crate::utilities::non_ref_raylib::HackedRaylibHandle,
-        config: &GameConfig
+        config: &GameConfig,
     ) {
         puffin::profile_function!();
-        // Calculate the logo position
         let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
+        let hud = &self.hud;
 
         // Draw a thin glow box around the screen
-        raylib.draw_rectangle_lines(0, 0, screen_size.x as i32, screen_size.y as i32, config.colors.red);
+        raylib.draw_rectangle_lines(
+            0,
+            0,
+            screen_size.x as i32,
+            screen_size.y as i32,
+            config.colors.red,
+        );
+
+        // Every element is stacked down from the top left, skipping any that are turned off. `y` is in design pixels
+        let mut y = 20.0;
+        if hud.settings.level_name {
+            raylib.draw_rgb_split_text(
+                layout.at(Anchor::TopLeft, Vector2::new(20.0, y)),
+                &hud.level_name,
+                20,
+                false,
+                Color::WHITE,
+            );
+            y += 25.0;
+        }
+
+        // Endless runs are scored by distance
+        if self.endless.as_ref().map_or(false, EndlessRun::is_endless) {
+            let distance = self.endless_distance();
+            raylib.draw_rgb_split_text(
+                layout.at(Anchor::TopLeft, Vector2::new(20.0, y)),
+                &format!("{}m", distance),
+                40,
+                distance > self.endless_best_distance,
+                Color::WHITE,
+            );
+            raylib.draw_ui_text(
+                &hud.locale.text_with(
+                    "hud.best_distance",
+                    &[("distance", &self.endless_best_distance.max(distance))],
+                ),
+                layout.at(Anchor::TopLeft, Vector2::new(20.0, y + 45.0)),
+                20,
+                Color::DARKGRAY,
+            );
+            y += 70.0;
+        }
+
+        if hud.settings.timer {
+            raylib.draw_rgb_split_text(
+                layout.at(Anchor::TopLeft, Vector2::new(20.0, y)),
+                &format_time(&hud.elapsed),
+                40,
+                false,
+                Color::WHITE,
+            );
+            y += 45.0;
+        }
+
+        if hud.settings.best_time {
+            if let Some(best_time) = &hud.best_time {
+                let best_text = hud
+                    .locale
+                    .text_with("hud.best_time", &[("time", &format_time(best_time))]);
+                let row = layout.at(Anchor::TopLeft, Vector2::new(20.0, y));
+                raylib.draw_ui_text(&best_text, row, 20, Color::DARKGRAY);
+
+                // Green while the run is still faster than the best one
+                let delta = hud.elapsed - *best_time;
+                let delta_x =
+                    row.x + raylib.measure_ui_text_ex(&best_text, 20).x + layout.scaled(10.0);
+                raylib.draw_ui_text(
+                    &format_delta(&delta),
+                    Vector2::new(delta_x, row.y),
+                    20,
+                    if delta < Duration::zero() {
+                        config.colors.green
+                    } else {
+                        config.colors.red
+                    },
+                );
+                y += 25.0;
+            }
+
+            // Compare each section against the best run as soon as it is finished
+            if let Some((idx, delta)) = &hud.split_delta {
+                let split_text = hud.locale.text_with("hud.split", &[("split", &(idx + 1))]);
+                let row = layout.at(Anchor::TopLeft, Vector2::new(20.0, y));
+                raylib.draw_ui_text(&split_text, row, 20, Color::DARKGRAY);
+                let delta_x =
+                    row.x + raylib.measure_ui_text_ex(&split_text, 20).x + layout.scaled(10.0);
+                raylib.draw_ui_text(
+                    &format_delta(delta),
+                    Vector2::new(delta_x, row.y),
+                    20,
+                    if *delta < Duration::zero() {
+                        config.colors.green
+                    } else {
+                        config.colors.red
+                    },
+                );
+            }
+        }
+
+        if hud.settings.attempts {
+            let attempts_text = hud
+                .locale
+                .text_with("hud.attempt", &[("count", &hud.attempts)]);
+            let position = raylib.aligned_ui_text_position(
+                &attempts_text,
+                layout.at(Anchor::TopRight, Vector2::new(-20.0, 20.0)),
+                20,
+                TextAlign::Right,
+            );
+            raylib.draw_rgb_split_text(position, &attempts_text, 20, false, Color::WHITE);
+        }
+
+        if hud.settings.progress_bar {
+            if let Some(progress) = hud.progress {
+                let bar = layout.rect(
+                    Anchor::BottomLeft,
+                    Vector2::new(20.0, -16.0),
+                    Vector2::new(0.0, 8.0),
+                );
+                let bar = Rectangle {
+                    width: screen_size.x - bar.x * 2.0,
+                    ..bar
+                };
+                raylib.draw_rectangle_lines_ex(bar, 1, config.colors.white);
+                raylib.draw_rectangle_rec(
+                    Rectangle {
+                        width: bar.width * progress,
+                        ..bar
+                    },
+                    config.colors.white,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_progress_between() {
+        assert_relative_eq!(progress_between(0.0, 200.0, 50.0), 0.25);
+        assert_relative_eq!(progress_between(0.0, 200.0, -10.0), 0.0);
+        assert_relative_eq!(progress_between(0.0, 200.0, 500.0), 1.0);
+        assert_relative_eq!(progress_between(100.0, 100.0, 500.0), 0.0);
     }
 }
@@ -0,0 +1,33 @@
+//! Campaigns group levels into separately played lists, like the main story and bonus packs
+
+use crate::progress::ProgressData;
+
+use super::Level;
+
+/// The win screen message used by campaigns that do not set one
+pub const DEFAULT_WIN_MESSAGE: &str = "you win.";
+
+#[derive(Debug, Clone)]
+pub struct Campaign {
+    /// Name shown on the level select screen
+    pub name: String,
+    /// Shown on the win screen once the last level is beaten
+    pub win_message: String,
+    /// Indices into the full level list, in play order
+    pub levels: Vec<usize>,
+}
+
+impl Campaign {
+    /// Count how many of this campaign's levels have been beaten
+    pub fn beaten_levels(&self, levels: &[Level], progress: &ProgressData) -> usize {
+        self.levels
+            .iter()
+            .filter(|idx| progress.is_level_beaten(&levels[**idx].name))
+            .count()
+    }
+
+    /// Check if every level in this campaign has been beaten
+    pub fn is_complete(&self, levels: &[Level], progress: &ProgressData) -> bool {
+        self.beaten_levels(levels, progress) == self.levels.len()
+    }
+}
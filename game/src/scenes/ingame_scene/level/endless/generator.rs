@@ -0,0 +1,140 @@
+//! Picks which chunk comes next in an endless run. This is kept free of any textures so it can be tested
+
+use crate::utilities::seeded_rng::SeededRng;
+
+use super::ChunkDef;
+
+/// How many chunks the player has to get through before harder chunks can show up
+pub const CHUNKS_PER_DIFFICULTY_STEP: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct ChunkGenerator {
+    rng: SeededRng,
+    ground_y: i32,
+    placed_chunks: usize,
+    last_chunk: Option<usize>,
+}
+
+impl ChunkGenerator {
+    /// Construct a new generator. The first chunk will start at `ground_y`
+    pub fn new(seed: u64, ground_y: i32) -> Self {
+        Self {
+            rng: SeededRng::new(seed),
+            ground_y,
+            placed_chunks: 0,
+            last_chunk: None,
+        }
+    }
+
+    /// The hardest chunk difficulty allowed right now
+    pub fn max_difficulty(&self) -> u32 {
+        (self.placed_chunks / CHUNKS_PER_DIFFICULTY_STEP) as u32
+    }
+
+    /// How many chunks have been picked so far
+    pub fn placed_chunks(&self) -> usize {
+        self.placed_chunks
+    }
+
+    /// Pick the index of the next chunk. Returns `None` if no chunk starts where the last one ended
+    pub fn next_chunk(&mut self, chunks: &[ChunkDef]) -> Option<usize> {
+        // Only chunks that line up with the end of the last one can be used
+        let fitting: Vec<usize> = (0..chunks.len())
+            .filter(|idx| chunks[*idx].entry_height == self.ground_y)
+            .collect();
+        if fitting.is_empty() {
+            return None;
+        }
+
+        // Lean towards the hardest chunks allowed, so the difficulty actually rises.
+        // If every fitting chunk is too hard, fall back to the easiest of them
+        let max_difficulty = self.max_difficulty();
+        let allowed: Vec<usize> = fitting
+            .iter()
+            .copied()
+            .filter(|idx| chunks[*idx].difficulty <= max_difficulty)
+            .collect();
+        let mut pool: Vec<usize> = if allowed.is_empty() {
+            let easiest = fitting
+                .iter()
+                .map(|idx| chunks[*idx].difficulty)
+                .min()
+                .unwrap();
+            fitting
+                .into_iter()
+                .filter(|idx| chunks[*idx].difficulty == easiest)
+                .collect()
+        } else {
+            let hardest = allowed
+                .iter()
+                .map(|idx| chunks[*idx].difficulty)
+                .max()
+                .unwrap();
+            allowed
+                .into_iter()
+                .filter(|idx| chunks[*idx].difficulty + 1 >= hardest)
+                .collect()
+        };
+
+        // Try not to repeat the same chunk twice in a row
+        if pool.len() > 1 {
+            pool.retain(|idx| Some(*idx) != self.last_chunk);
+        }
+
+        let chosen = pool[self.rng.next_index(pool.len())];
+        self.ground_y = chunks[chosen].exit_height;
+        self.placed_chunks += 1;
+        self.last_chunk = Some(chosen);
+        Some(chosen)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn load_chunk_defs() -> Vec<ChunkDef> {
+        serde_json::from_str(include_str!("../../../../../assets/endless/chunks.json")).unwrap()
+    }
+
+    #[test]
+    fn test_same_seed_same_chunks() {
+        let chunks = load_chunk_defs();
+        let mut a = ChunkGenerator::new(49, 800);
+        let mut b = ChunkGenerator::new(49, 800);
+        for _ in 0..50 {
+            assert_eq!(a.next_chunk(&chunks), b.next_chunk(&chunks));
+        }
+    }
+
+    #[test]
+    fn test_chunks_line_up() {
+        let chunks = load_chunk_defs();
+        let mut generator = ChunkGenerator::new(1234, 800);
+        let mut ground_y = 800;
+        for _ in 0..200 {
+            let chunk = &chunks[generator.next_chunk(&chunks).expect("Ran out of chunks")];
+            assert_eq!(chunk.entry_height, ground_y);
+            ground_y = chunk.exit_height;
+        }
+    }
+
+    #[test]
+    fn test_difficulty_rises() {
+        let chunks = load_chunk_defs();
+        let mut generator = ChunkGenerator::new(7, 800);
+        let first: u32 = (0..CHUNKS_PER_DIFFICULTY_STEP)
+            .map(|_| chunks[generator.next_chunk(&chunks).unwrap()].difficulty)
+            .max()
+            .unwrap();
+        for _ in 0..(CHUNKS_PER_DIFFICULTY_STEP * 4) {
+            generator.next_chunk(&chunks);
+        }
+        let later: u32 = (0..CHUNKS_PER_DIFFICULTY_STEP)
+            .map(|_| chunks[generator.next_chunk(&chunks).unwrap()].difficulty)
+            .max()
+            .unwrap();
+        assert_eq!(first, 0);
+        assert!(later > first);
+    }
+}
@@ -0,0 +1,400 @@
+//! Endless runs, built on the fly from pieces of the normal levels.
+//!
+//! Every piece (a "chunk") is listed in `endless/chunks.json`, along with the height of the ground where it starts and ends.
+//! Chunks are only ever placed after one that ends at the same height, so the player can always run between them.
+
+use std::{collections::HashMap, rc::Rc};
+
+use chrono::{Datelike, NaiveDate};
+
+use raylib::{
+    math::{Rectangle, Vector2},
+    RaylibHandle, RaylibThread,
+};
+use tracing::{error, info};
+
+use crate::utilities::{
+    chunked_texture::ChunkedTexture,
+    datastore::{decode_image_from_internal_data, DecodedImage, ResourceLoadError},
+};
+
+use self::generator::ChunkGenerator;
+
+use super::{
+    loader::{load_level, read_internal_json, LEVEL_CHUNK_WIDTH},
+    unlock::UnlockRule,
+    Level, LevelTextures, LevelZones,
+};
+
+pub mod generator;
+
+/// Endless levels are laid out as if their platform layer was this tall. This puts the floor at the same place as the normal levels
+pub const ENDLESS_LEVEL_HEIGHT: f32 = 800.0;
+
+/// The level space y of the floor
+const FLOOR_Y: f32 = 800.0;
+
+/// How far ahead of the player (in level pixels) the level must always be built
+const GENERATE_AHEAD_DISTANCE: f32 = 4000.0;
+
+/// How far behind the player (in level pixels) to keep old chunks around
+const KEEP_BEHIND_DISTANCE: f32 = 2500.0;
+
+/// How much floor comes after the last chunk of a run with a set length. The win line is in the middle of it
+const FINISH_LENGTH: f32 = 2000.0;
+
+/// How many chunks make up a daily challenge
+pub const DAILY_CHALLENGE_CHUNKS: usize = 12;
+
+/// Get the seed for a date's daily challenge. This only depends on the date, so everyone gets the same level
+pub fn daily_challenge_seed(date: NaiveDate) -> u64 {
+    date.num_days_from_ce() as u64
+}
+
+/// One entry in `endless/chunks.json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChunkDef {
+    /// The name of the level this chunk is cut from
+    pub level: String,
+    pub start_x: i32,
+    pub end_x: i32,
+    /// Level space y of the ground at the start of the chunk
+    pub entry_height: i32,
+    /// Level space y of the ground at the end of the chunk
+    pub exit_height: i32,
+    pub difficulty: u32,
+}
+
+/// A chunk cut out of its level and ready to be placed. Everything is relative to the chunk's left edge
+#[derive(Debug)]
+pub struct EndlessChunk {
+    colliders: Vec<Rectangle>,
+    appear: Vec<Rectangle>,
+    disappear: Vec<Rectangle>,
+    kill: Vec<Rectangle>,
+    platform_tex: ChunkedTexture,
+    appearing_platform_tex: ChunkedTexture,
+    disappearing_platform_tex: ChunkedTexture,
+    width: f32,
+}
+
+/// Every chunk available to endless runs, with its textures in VRAM
+#[derive(Debug)]
+pub struct EndlessCatalogue {
+    pub defs: Vec<ChunkDef>,
+    chunks: Vec<EndlessChunk>,
+}
+
+/// Clip a level space rectangle to a chunk, and move it to be relative to the chunk
+fn clip_to_chunk(rect: &Rectangle, def: &ChunkDef) -> Option<Rectangle> {
+    let left = rect.x.max(def.start_x as f32);
+    let right = (rect.x + rect.width).min(def.end_x as f32);
+    if right <= left {
+        None
+    } else {
+        Some(Rectangle::new(
+            left - def.start_x as f32,
+            rect.y,
+            right - left,
+            rect.height,
+        ))
+    }
+}
+
+/// A chunk cut out of its level on the loader thread, with its layers decoded but not yet in VRAM
+#[derive(Debug)]
+pub struct PreparedChunk {
+    colliders: Vec<Rectangle>,
+    appear: Vec<Rectangle>,
+    disappear: Vec<Rectangle>,
+    kill: Vec<Rectangle>,
+    /// Each layer, and how far down it is drawn to line up with the platform layer
+    layers: [(DecodedImage, f32); 3],
+    width: f32,
+}
+
+/// Every chunk available to endless runs, cut out and ready to be sent to the GPU when a run starts
+#[derive(Debug)]
+pub struct PreparedCatalogue {
+    defs: Vec<ChunkDef>,
+    chunks: Vec<PreparedChunk>,
+}
+
+/// Cut out every chunk listed in `endless/chunks.json`. This decodes the layers of every level used, so it is slow,
+/// but it does not touch the GPU and can run on the asset loader thread
+pub fn prepare_endless_catalogue() -> Result<PreparedCatalogue, ResourceLoadError> {
+    let defs: Vec<ChunkDef> = read_internal_json("endless/chunks.json")?;
+
+    // Work through one source level at a time, so only one set of full-size layers is in memory at once
+    let mut level_names: Vec<&str> = Vec::new();
+    for def in &defs {
+        if !level_names.contains(&def.level.as_str()) {
+            level_names.push(&def.level);
+        }
+    }
+    let mut chunks = HashMap::new();
+    for level_name in level_names {
+        info!("Cutting endless chunks from level: {}", level_name);
+        let level = load_level(level_name)?;
+        let platforms =
+            decode_image_from_internal_data(&format!("levels/{}/platforms.png", level_name))?;
+        let appearing = decode_image_from_internal_data(&format!(
+            "levels/{}/appearing_platforms.png",
+            level_name
+        ))?;
+        let disappearing = decode_image_from_internal_data(&format!(
+            "levels/{}/disappearing_platforms.png",
+            level_name
+        ))?;
+
+        // Floors reach past the edges of the art. Endless runs get their own floor instead
+        let art_width = platforms.width as f32;
+        let colliders: Vec<&Rectangle> = level
+            .authored_colliders
+            .iter()
+            .filter(|rect| rect.x >= 0.0 && rect.x + rect.width <= art_width)
+            .collect();
+
+        for (idx, def) in defs.iter().enumerate() {
+            if def.level != level_name {
+                continue;
+            }
+
+            let clip_all = |rects: &[Rectangle]| -> Vec<Rectangle> {
+                rects
+                    .iter()
+                    .filter_map(|rect| clip_to_chunk(rect, def))
+                    .collect()
+            };
+            // Layers are drawn with their bottom edge on the floor, so shorter ones are moved down
+            let cut = |image: &DecodedImage| {
+                (
+                    image.crop_columns(def.start_x as u32, (def.end_x - def.start_x) as u32),
+                    platforms.height as f32 - image.height as f32,
+                )
+            };
+            chunks.insert(
+                idx,
+                PreparedChunk {
+                    colliders: colliders
+                        .iter()
+                        .filter_map(|rect| clip_to_chunk(rect, def))
+                        .collect(),
+                    appear: clip_all(&level.zones.appear),
+                    disappear: clip_all(&level.zones.disappear),
+                    kill: clip_all(&level.zones.kill),
+                    layers: [cut(&platforms), cut(&appearing), cut(&disappearing)],
+                    width: (def.end_x - def.start_x) as f32,
+                },
+            );
+        }
+    }
+
+    Ok(PreparedCatalogue {
+        chunks: (0..defs.len())
+            .map(|idx| chunks.remove(&idx).unwrap())
+            .collect(),
+        defs,
+    })
+}
+
+/// Send one of a chunk's layers to the GPU
+fn upload_layer(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
+    (image, y_offset): &(DecodedImage, f32),
+) -> Result<ChunkedTexture, ResourceLoadError> {
+    let cut = ChunkedTexture::new(raylib_handle, thread, image, LEVEL_CHUNK_WIDTH)?;
+    let mut texture = ChunkedTexture::empty(ENDLESS_LEVEL_HEIGHT as i32);
+    texture.extend_from(&cut, Vector2::new(0.0, *y_offset));
+    Ok(texture)
+}
+
+/// Send every prepared chunk to the GPU. The prepared catalogue is kept, so this can be done again after unloading
+pub fn load_endless_catalogue(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
+    prepared: &PreparedCatalogue,
+) -> Result<EndlessCatalogue, ResourceLoadError> {
+    let chunks = prepared
+        .chunks
+        .iter()
+        .map(|chunk| {
+            let [platforms, appearing, disappearing] = &chunk.layers;
+            Ok(EndlessChunk {
+                colliders: chunk.colliders.clone(),
+                appear: chunk.appear.clone(),
+                disappear: chunk.disappear.clone(),
+                kill: chunk.kill.clone(),
+                platform_tex: upload_layer(raylib_handle, thread, platforms)?,
+                appearing_platform_tex: upload_layer(raylib_handle, thread, appearing)?,
+                disappearing_platform_tex: upload_layer(raylib_handle, thread, disappearing)?,
+                width: chunk.width,
+            })
+        })
+        .collect::<Result<_, ResourceLoadError>>()?;
+    Ok(EndlessCatalogue {
+        defs: prepared.defs.clone(),
+        chunks,
+    })
+}
+
+/// A single endless run. This owns a `Level` that keeps growing as the player moves
+#[derive(Debug)]
+pub struct EndlessRun {
+    catalogue: Rc<EndlessCatalogue>,
+    generator: ChunkGenerator,
+    pub level: Level,
+    /// Level space x of the end of the level so far
+    end_x: f32,
+    /// If set, the run ends with a win line after this many chunks
+    chunk_limit: Option<usize>,
+}
+
+impl EndlessRun {
+    /// Start a new run. Two runs with the same seed will always have the same chunks
+    pub fn new(catalogue: Rc<EndlessCatalogue>, seed: u64) -> Self {
+        Self::build(catalogue, seed, None)
+    }
+
+    /// Start a run that can be won by getting through `chunk_count` chunks
+    pub fn with_length(catalogue: Rc<EndlessCatalogue>, seed: u64, chunk_count: usize) -> Self {
+        Self::build(catalogue, seed, Some(chunk_count))
+    }
+
+    fn build(catalogue: Rc<EndlessCatalogue>, seed: u64, chunk_limit: Option<usize>) -> Self {
+        let mut run = Self {
+            catalogue,
+            generator: ChunkGenerator::new(seed, FLOOR_Y as i32),
+            level: Level {
+                name: "endless".to_string(),
+                unlock: UnlockRule::Always,
+                campaign: 0,
+                platform_height: ENDLESS_LEVEL_HEIGHT,
+                // A bit of floor to land on before the first chunk
+                authored_colliders: vec![Rectangle::new(-1000.0, FLOOR_Y, 1000.0, 10.0)],
+                colliders: Vec::new(),
+                zones: LevelZones {
+                    appear: Vec::new(),
+                    disappear: Vec::new(),
+                    kill: Vec::new(),
+                    // Endless runs can not be won. Runs with a set length move this once their last chunk is placed
+                    win: Rectangle::new(f32::MAX, 0.0, 0.0, 0.0),
+                    splits: Vec::new(),
+                    extra: Default::default(),
+                },
+                background: Vec::new(),
+                textures: Some(LevelTextures {
+                    background_layers: Vec::new(),
+                    platform_tex: ChunkedTexture::empty(ENDLESS_LEVEL_HEIGHT as i32),
+                    appearing_platform_tex: ChunkedTexture::empty(ENDLESS_LEVEL_HEIGHT as i32),
+                    disappearing_platform_tex: ChunkedTexture::empty(ENDLESS_LEVEL_HEIGHT as i32),
+                }),
+                content_hash: 0,
+            },
+            end_x: 0.0,
+            chunk_limit,
+        };
+        run.update(0.0);
+        run
+    }
+
+    /// Check if this run goes on forever, instead of ending with a win line
+    pub fn is_endless(&self) -> bool {
+        self.chunk_limit.is_none()
+    }
+
+    /// Check if every chunk of a run with a set length has been placed
+    fn is_complete(&self) -> bool {
+        self.chunk_limit
+            .map_or(false, |limit| self.generator.placed_chunks() >= limit)
+    }
+
+    /// Close off a run with a set length with some floor and the win line
+    fn place_finish(&mut self) {
+        self.level.authored_colliders.push(Rectangle::new(
+            self.end_x,
+            FLOOR_Y,
+            FINISH_LENGTH,
+            10.0,
+        ));
+        self.level.zones.win = Rectangle::new(self.end_x + FINISH_LENGTH / 2.0, 0.0, 10.0, FLOOR_Y);
+        self.end_x += FINISH_LENGTH;
+    }
+
+    /// Add a single chunk to the end of the level
+    fn place_next_chunk(&mut self) {
+        let offset = self.end_x;
+        let chunk = match self.generator.next_chunk(&self.catalogue.defs) {
+            Some(idx) => &self.catalogue.chunks[idx],
+            None => {
+                // This only happens if the chunk list is broken. Keep the player on solid ground instead of crashing
+                error!("No endless chunk starts where the last one ended");
+                self.level
+                    .authored_colliders
+                    .push(Rectangle::new(offset, FLOOR_Y, 2000.0, 10.0));
+                self.end_x += 2000.0;
+                return;
+            }
+        };
+
+        let shift = |rects: &[Rectangle]| {
+            rects
+                .iter()
+                .map(|rect| Rectangle::new(rect.x + offset, rect.y, rect.width, rect.height))
+                .collect::<Vec<_>>()
+        };
+        self.level
+            .authored_colliders
+            .push(Rectangle::new(offset, FLOOR_Y, chunk.width, 10.0));
+        self.level
+            .authored_colliders
+            .extend(shift(&chunk.colliders));
+        self.level.zones.appear.extend(shift(&chunk.appear));
+        self.level.zones.disappear.extend(shift(&chunk.disappear));
+        self.level.zones.kill.extend(shift(&chunk.kill));
+        if let Some(textures) = &mut self.level.textures {
+            let offset = Vector2::new(offset, 0.0);
+            textures
+                .platform_tex
+                .extend_from(&chunk.platform_tex, offset);
+            textures
+                .appearing_platform_tex
+                .extend_from(&chunk.appearing_platform_tex, offset);
+            textures
+                .disappearing_platform_tex
+                .extend_from(&chunk.disappearing_platform_tex, offset);
+        }
+        self.end_x += chunk.width;
+    }
+
+    /// Build more of the level ahead of the player, and throw away anything far behind them
+    pub fn update(&mut self, player_level_x: f32) {
+        if self.is_complete() || self.end_x > player_level_x + GENERATE_AHEAD_DISTANCE {
+            return;
+        }
+        while !self.is_complete() && self.end_x <= player_level_x + GENERATE_AHEAD_DISTANCE {
+            self.place_next_chunk();
+            if self.is_complete() {
+                self.place_finish();
+            }
+        }
+
+        let keep_from = player_level_x - KEEP_BEHIND_DISTANCE;
+        let is_needed = |rect: &Rectangle| rect.x + rect.width >= keep_from;
+        self.level.authored_colliders.retain(is_needed);
+        self.level.zones.appear.retain(is_needed);
+        self.level.zones.disappear.retain(is_needed);
+        self.level.zones.kill.retain(is_needed);
+        if let Some(textures) = &mut self.level.textures {
+            textures.platform_tex.drop_chunks_before(keep_from);
+            textures
+                .appearing_platform_tex
+                .drop_chunks_before(keep_from);
+            textures
+                .disappearing_platform_tex
+                .drop_chunks_before(keep_from);
+        }
+        self.level.rebuild_colliders();
+    }
+}
@@ -1,82 +1,343 @@
-use raylib::{RaylibHandle, RaylibThread};
+use std::{io::Cursor, path::PathBuf};
+
+use level_tools::format::{write_json, LevelZones as SavedZones, Rect};
+use raylib::{math::Rectangle, RaylibHandle, RaylibThread};
+use tracing::info;
 
 use crate::{
+    progress::ProgressData,
     utilities::{
-        datastore::{load_texture_from_internal_data, ResourceLoadError},
+        chunked_texture::ChunkedTexture,
+        datastore::{
+            decode_image_from_internal_data, load_texture_from_internal_data, ResourceLoadError,
+        },
         world_paint_texture::WorldPaintTexture,
     },
     StaticGameData,
 };
 
-use super::Level;
+use super::{
+    campaign::{Campaign, DEFAULT_WIN_MESSAGE},
+    unlock::{next_level, UnlockRule},
+    BackgroundLayer, Level, LevelTextures, LevelZones,
+};
 
-pub fn load_all_levels(
-    raylib_handle: &mut RaylibHandle,
-    thread: &RaylibThread,
-) -> Result<Vec<Level>, ResourceLoadError> {
+/// The width of each piece a platform layer is split into. This is well under the max texture size of any GPU we support
+pub const LEVEL_CHUNK_WIDTH: u32 = 1024;
+
+/// One level in `levels.json`. Levels listed by name alone use the default unlock rule
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ManifestEntry {
+    Name(String),
+    Detailed {
+        name: String,
+        #[serde(default)]
+        unlock: UnlockRule,
+    },
+}
+
+/// One campaign in `levels.json`
+#[derive(Debug, Deserialize)]
+struct CampaignManifest {
+    name: String,
+    #[serde(default)]
+    win_message: Option<String>,
+    levels: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Manifest {
+    /// A single list of levels, which becomes the main campaign
+    Levels(Vec<ManifestEntry>),
+    Campaigns { campaigns: Vec<CampaignManifest> },
+}
+
+/// Loads the metadata (colliders, zones, and sizing) for every level, along with the campaigns they are grouped into.
+/// Levels from every campaign are put in one list, in order. No textures are loaded here
+pub fn load_all_levels() -> Result<(Vec<Level>, Vec<Campaign>), ResourceLoadError> {
     // Get a listing of all levels we have
-    let level_names: Vec<String> = serde_json::from_str(
-        &String::from_utf8(
-            StaticGameData::get("levels/levels.json")
-                .expect("Could not load levels.json")
-                .data
-                .into(),
-        )
-        .unwrap(),
-    )?;
+    let manifest: Manifest = read_internal_json("levels/levels.json")?;
+    let campaign_manifests = match manifest {
+        Manifest::Levels(levels) => vec![CampaignManifest {
+            name: "MAIN STORY".to_string(),
+            win_message: None,
+            levels,
+        }],
+        Manifest::Campaigns { campaigns } => campaigns,
+    };
 
-    // Build a level list
+    // Load every level
     let mut levels = Vec::new();
+    let mut campaigns = Vec::new();
+    for (campaign_idx, campaign) in campaign_manifests.into_iter().enumerate() {
+        let mut campaign_levels = Vec::new();
+        for entry in campaign.levels {
+            let (level_name, unlock) = match entry {
+                ManifestEntry::Name(name) => (name, UnlockRule::default()),
+                ManifestEntry::Detailed { name, unlock } => (name, unlock),
+            };
+            let mut level = load_level(&level_name)?;
+            level.unlock = unlock;
+            level.campaign = campaign_idx;
+            campaign_levels.push(levels.len());
+            levels.push(level);
+        }
+        campaigns.push(Campaign {
+            name: campaign.name,
+            win_message: campaign
+                .win_message
+                .unwrap_or_else(|| DEFAULT_WIN_MESSAGE.to_string()),
+            levels: campaign_levels,
+        });
+    }
+    Ok((levels, campaigns))
+}
 
-    for level_name in &level_names {
-        let zones = serde_json::from_str(
-            &String::from_utf8(
-                StaticGameData::get(&format!("levels/{}/zones.json", level_name))
-                    .unwrap()
-                    .data
-                    .into(),
-            )
-            .unwrap(),
-        )?;
-        levels.push({
-            let mut l = Level {
-                name: level_name.to_string(),
-                background_tex: WorldPaintTexture::new(load_texture_from_internal_data(
-                    raylib_handle,
-                    thread,
-                    &format!("levels/{}/background.png", level_name),
-                )?),
-                platform_tex: load_texture_from_internal_data(
-                    raylib_handle,
-                    thread,
-                    &format!("levels/{}/platforms.png", level_name),
-                )?,
-                appearing_platform_tex: load_texture_from_internal_data(
-                    raylib_handle,
-                    thread,
-                    &format!("levels/{}/appearing_platforms.png", level_name),
-                )?,
-                disappearing_platform_tex: load_texture_from_internal_data(
+/// Loads the metadata for a single level
+pub fn load_level(level_name: &str) -> Result<Level, ResourceLoadError> {
+    // Levels can be reloaded while they are being edited, so a missing file must not panic
+    let zones = read_internal_json(&format!("levels/{}/zones.json", level_name))?;
+
+    // The background is optional, and falls back to a single layer using the level's `background.png`
+    let background = match StaticGameData::get(&format!("levels/{}/background.json", level_name)) {
+        Some(file) => serde_json::from_slice(&file.data)?,
+        None => vec![BackgroundLayer::default()],
+    };
+
+    let mut level = Level {
+        name: level_name.to_string(),
+        unlock: UnlockRule::default(),
+        campaign: 0,
+        platform_height: get_internal_image_size(&format!("levels/{}/platforms.png", level_name))?.1
+            as f32,
+        authored_colliders: read_internal_json(&format!("levels/{}/colliders.json", level_name))?,
+        colliders: Vec::new(),
+        zones,
+        background,
+        textures: None,
+        content_hash: 0,
+    };
+    level.rebuild_colliders();
+    Ok(level)
+}
+
+/// Loads all textures used by a single level into VRAM
+pub fn load_level_textures(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
+    level_name: &str,
+    background: &[BackgroundLayer],
+) -> Result<LevelTextures, ResourceLoadError> {
+    Ok(LevelTextures {
+        background_layers: background
+            .iter()
+            .map(|layer| {
+                Ok(WorldPaintTexture::new(load_texture_from_internal_data(
                     raylib_handle,
                     thread,
-                    &format!("levels/{}/disappearing_platforms.png", level_name),
-                )?,
-                colliders: serde_json::from_str(
-                    &String::from_utf8(
-                        StaticGameData::get(&format!("levels/{}/colliders.json", level_name))
-                            .unwrap()
-                            .data
-                            .into(),
-                    )
-                    .unwrap(),
-                )?,
-                zones,
-            };
-            for a in &l.zones.appear {
-                l.colliders.push(a.clone());
-            }
-            l
-        });
+                    &format!("levels/{}/{}", level_name, layer.texture),
+                )?))
+            })
+            .collect::<Result<_, ResourceLoadError>>()?,
+        platform_tex: load_chunked_layer(
+            raylib_handle,
+            thread,
+            &format!("levels/{}/platforms.png", level_name),
+        )?,
+        appearing_platform_tex: load_chunked_layer(
+            raylib_handle,
+            thread,
+            &format!("levels/{}/appearing_platforms.png", level_name),
+        )?,
+        disappearing_platform_tex: load_chunked_layer(
+            raylib_handle,
+            thread,
+            &format!("levels/{}/disappearing_platforms.png", level_name),
+        )?,
+    })
+}
+
+/// Loads a single platform layer, split into chunks
+fn load_chunked_layer(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
+    path: &str,
+) -> Result<ChunkedTexture, ResourceLoadError> {
+    ChunkedTexture::new(
+        raylib_handle,
+        thread,
+        &decode_image_from_internal_data(path)?,
+        LEVEL_CHUNK_WIDTH,
+    )
+}
+
+/// Reloads a level's data (and textures, if they are in VRAM) from disk.
+///
+/// The level is only replaced once everything has loaded, so a half-saved file will not break the running game.
+#[cfg(all(debug_assertions, feature = "hot-reload"))]
+pub fn reload_level(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
+    levels: &mut [Level],
+    level_name: &str,
+) -> Result<(), ResourceLoadError> {
+    let level = match levels.iter_mut().find(|level| level.name == level_name) {
+        Some(level) => level,
+        None => return Ok(()),
+    };
+
+    let mut new_level = load_level(level_name)?;
+    new_level.unlock = level.unlock.clone();
+    new_level.campaign = level.campaign;
+    if level.textures.is_some() {
+        new_level.textures = Some(load_level_textures(
+            raylib_handle,
+            thread,
+            &new_level.name,
+            &new_level.background,
+        )?);
+    }
+    *level = new_level;
+    info!("Reloaded level: {}", level_name);
+    Ok(())
+}
+
+/// Makes sure only the active level (and optionally the one played after it) have their textures in VRAM.
+/// The next level is picked the same way as when the active one is won, so it follows campaigns and unlocks.
+///
+/// Passing `None` as the active level will unload everything.
+pub fn stream_level_textures(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
+    levels: &mut [Level],
+    active_level: Option<usize>,
+    preload_next: bool,
+    progress: &ProgressData,
+) -> Result<(), ResourceLoadError> {
+    let next = active_level
+        .filter(|_| preload_next)
+        .and_then(|active| next_level(levels, active, progress));
+    for (idx, level) in levels.iter_mut().enumerate() {
+        let should_be_loaded = active_level == Some(idx) || next == Some(idx);
+
+        if should_be_loaded && level.textures.is_none() {
+            info!("Loading textures for level: {}", level.name);
+            level.textures = Some(load_level_textures(
+                raylib_handle,
+                thread,
+                &level.name,
+                &level.background,
+            )?);
+        } else if !should_be_loaded && level.textures.is_some() {
+            // Dropping the textures frees them from VRAM
+            info!("Unloading textures for level: {}", level.name);
+            level.textures = None;
+        }
+    }
+    Ok(())
+}
+
+/// A level space rectangle, in the integer layout used by the level files
+fn saved_rect(rect: &Rectangle) -> Rect {
+    Rect {
+        x: rect.x.round() as i32,
+        y: rect.y.round() as i32,
+        width: rect.width.round() as i32,
+        height: rect.height.round() as i32,
+    }
+}
+
+/// The layout of a level's `zones.json`, shared with `level_tools` so both write the same files
+fn saved_zones(zones: &LevelZones) -> SavedZones {
+    SavedZones {
+        appear: zones.appear.iter().map(saved_rect).collect(),
+        disappear: zones.disappear.iter().map(saved_rect).collect(),
+        kill: zones.kill.iter().map(saved_rect).collect(),
+        win: saved_rect(&zones.win),
+        splits: zones.splits.iter().map(|x| x.round() as i32).collect(),
+        extra: zones.extra.clone(),
+    }
+}
+
+/// Writes a level's colliders and zones back to its source directory.
+///
+/// This only makes sense in development, where `StaticGameData` reads assets straight from disk.
+pub fn save_level_data(
+    level_name: &str,
+    colliders: &[Rectangle],
+    zones: &LevelZones,
+) -> Result<(), ResourceLoadError> {
+    let level_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("assets/levels")
+        .join(level_name);
+
+    write_json(
+        &level_dir.join("colliders.json"),
+        &colliders.iter().map(saved_rect).collect::<Vec<_>>(),
+    )?;
+    write_json(&level_dir.join("zones.json"), &saved_zones(zones))?;
+    info!("Saved level data to: {}", level_dir.display());
+    Ok(())
+}
+
+/// Reads and parses an embedded JSON file
+pub(super) fn read_internal_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, ResourceLoadError> {
+    let data = StaticGameData::get(path)
+        .ok_or(ResourceLoadError::AssetNotFound(path.to_string()))?
+        .data;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// Reads the size of an embedded image without decoding the whole thing
+fn get_internal_image_size(path: &str) -> Result<(u32, u32), ResourceLoadError> {
+    let data = StaticGameData::get(path)
+        .ok_or(ResourceLoadError::AssetNotFound(path.to_string()))?
+        .data;
+    Ok(image::io::Reader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_dimensions()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_saved_zones_load_back() {
+        let zones = LevelZones {
+            appear: vec![Rectangle::new(0.0, 32.0, 64.0, 32.0)],
+            disappear: Vec::new(),
+            kill: vec![Rectangle::new(-100.0, 900.4, 5000.0, 100.0)],
+            win: Rectangle::new(4000.0, 0.0, 32.0, 1000.0),
+            splits: vec![1200.0, 2400.0],
+            extra: Default::default(),
+        };
+        let json = serde_json::to_string(&saved_zones(&zones)).unwrap();
+        let loaded: LevelZones = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.appear, zones.appear);
+        assert_eq!(
+            loaded.kill,
+            vec![Rectangle::new(-100.0, 900.0, 5000.0, 100.0)]
+        );
+        assert_eq!(loaded.win, zones.win);
+        assert_eq!(loaded.splits, zones.splits);
+
+        // Fields the game does not know about survive being saved again
+        let mut file: serde_json::Value = serde_json::from_str(&json).unwrap();
+        file["checkpoints"] = serde_json::json!([{ "x": 900, "y": 700 }]);
+        let loaded: LevelZones = serde_json::from_value(file.clone()).unwrap();
+        let saved = serde_json::to_value(&saved_zones(&loaded)).unwrap();
+        assert_eq!(saved["checkpoints"], file["checkpoints"]);
+        assert_eq!(saved, file);
+
+        // Levels without splits leave the field out
+        let json = serde_json::to_string(&saved_zones(&LevelZones {
+            splits: Vec::new(),
+            ..zones
+        }))
+        .unwrap();
+        assert!(!json.contains("splits"));
     }
-    Ok(levels)
 }
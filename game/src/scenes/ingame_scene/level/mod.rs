@@ -1,24 +1,143 @@
-use raylib::{math::Rectangle, texture::Texture2D};
+use raylib::{color::Color, math::Rectangle};
 
-use crate::utilities::world_paint_texture::WorldPaintTexture;
+use crate::utilities::{
+    chunked_texture::ChunkedTexture,
+    world_paint_texture::{ParallaxSettings, WorldPaintTexture},
+};
 
+use self::unlock::UnlockRule;
+
+pub mod campaign;
+pub mod endless;
 pub mod loader;
+pub mod unlock;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LevelZones {
     pub appear: Vec<Rectangle>,
     pub disappear: Vec<Rectangle>,
     pub kill: Vec<Rectangle>,
     pub win: Rectangle,
+    /// X positions of the lines that split the level into timed sections, from left to right
+    #[serde(default)]
+    pub splits: Vec<f32>,
+    /// Fields the game does not use. These are kept so the level editor writes them back untouched
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One layer of a level's parallax background, as declared in the level's `background.json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackgroundLayer {
+    /// Path to the layer's texture, relative to the level's directory
+    pub texture: String,
+    #[serde(default = "BackgroundLayer::default_scroll_factor")]
+    pub scroll_factor: f32,
+    #[serde(default)]
+    pub tile_vertically: bool,
+    #[serde(default = "BackgroundLayer::default_tint")]
+    pub tint: Color,
+    /// Vertical offset from where the layer normally sits, with its bottom edge on the floor
+    #[serde(default)]
+    pub y_offset: f32,
+}
+
+impl BackgroundLayer {
+    fn default_scroll_factor() -> f32 {
+        1.0
+    }
+
+    fn default_tint() -> Color {
+        Color::WHITE
+    }
+
+    /// Get the settings needed to render this layer
+    pub fn parallax_settings(&self) -> ParallaxSettings {
+        ParallaxSettings {
+            scroll_factor: self.scroll_factor,
+            tile_vertically: self.tile_vertically,
+            tint: self.tint,
+        }
+    }
+}
+
+impl Default for BackgroundLayer {
+    /// Levels without a `background.json` use their `background.png`, locked to the world
+    fn default() -> Self {
+        Self {
+            texture: "background.png".to_string(),
+            scroll_factor: Self::default_scroll_factor(),
+            tile_vertically: false,
+            tint: Self::default_tint(),
+            y_offset: 0.0,
+        }
+    }
+}
+
+/// All the textures needed to draw a level. These only live in VRAM while the level is being played
+#[derive(Debug)]
+pub struct LevelTextures {
+    /// One texture for each of the level's background layers, in the same order
+    pub background_layers: Vec<WorldPaintTexture>,
+    pub platform_tex: ChunkedTexture,
+    pub appearing_platform_tex: ChunkedTexture,
+    pub disappearing_platform_tex: ChunkedTexture,
 }
 
 #[derive(Debug)]
 pub struct Level {
     pub name: String,
-    pub background_tex: WorldPaintTexture,
-    pub platform_tex: Texture2D,
-    pub appearing_platform_tex: Texture2D,
-    pub disappearing_platform_tex: Texture2D,
+    /// When this level can be played, from `levels.json`
+    pub unlock: UnlockRule,
+    /// Index of the campaign this level is part of
+    pub campaign: usize,
+    pub platform_height: f32,
+    /// The colliders from the level's `colliders.json`
+    pub authored_colliders: Vec<Rectangle>,
+    /// Everything the player can stand on. This is the authored colliders plus all appear zones
     pub colliders: Vec<Rectangle>,
     pub zones: LevelZones,
+    /// Background layers, drawn back to front
+    pub background: Vec<BackgroundLayer>,
+    pub textures: Option<LevelTextures>,
+    /// A hash of the colliders and zones, used to tell when a best time was set on an older version of the level
+    pub content_hash: u64,
+}
+
+/// Hash every rectangle in a list with FNV-1a. This must stay the same between builds, so the standard library's hasher can not be used
+fn hash_rectangles<'a>(hash: u64, rects: impl IntoIterator<Item = &'a Rectangle>) -> u64 {
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+    let mut hash = hash;
+    for rect in rects {
+        for value in &[rect.x, rect.y, rect.width, rect.height] {
+            for byte in &value.to_bits().to_le_bytes() {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+    }
+
+    // Mark the end of the list, so moving a rectangle from one list to the next changes the hash
+    hash ^= 0xFF;
+    hash.wrapping_mul(FNV_PRIME)
+}
+
+impl Level {
+    /// The vertical offset between level space and world space. Levels sit on top of the floor
+    pub fn height_offset(&self) -> f32 {
+        -self.platform_height
+    }
+
+    /// Rebuild the runtime collider list and content hash. This must be called after changing the authored colliders or zones
+    pub fn rebuild_colliders(&mut self) {
+        self.colliders = self.authored_colliders.clone();
+        self.colliders.extend(self.zones.appear.iter().cloned());
+
+        let mut hash = 0xCBF2_9CE4_8422_2325;
+        hash = hash_rectangles(hash, &self.authored_colliders);
+        hash = hash_rectangles(hash, &self.zones.appear);
+        hash = hash_rectangles(hash, &self.zones.disappear);
+        hash = hash_rectangles(hash, &self.zones.kill);
+        self.content_hash = hash_rectangles(hash, std::iter::once(&self.zones.win));
+    }
 }
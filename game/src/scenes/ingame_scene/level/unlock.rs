@@ -0,0 +1,249 @@
+//! Rules for when levels show up on the level select screen
+
+use std::collections::HashSet;
+
+use crate::progress::ProgressData;
+
+use super::Level;
+
+/// When a level can be played, as declared in `levels.json`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockRule {
+    /// Always playable
+    Always,
+    /// Locked until the level before it in its campaign has been beaten. The first level of a campaign is always playable
+    PreviousBeaten,
+    /// Locked until every level using `PreviousBeaten` or `Always` has been beaten. Used for bonus levels,
+    /// in place of collecting every pickup, since the game has no pickups
+    AllMainLevelsBeaten,
+    /// Hidden until the code is typed on the level select screen
+    Secret(String),
+}
+
+impl Default for UnlockRule {
+    /// Levels have always been unlocked one at a time, so that is the default
+    fn default() -> Self {
+        Self::PreviousBeaten
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelAvailability {
+    Unlocked,
+    /// Shown on the level select screen, but can not be played yet
+    Locked,
+    /// Not shown at all
+    Hidden,
+}
+
+/// Find the level played before this one in the same campaign
+fn previous_in_campaign(levels: &[Level], level: usize) -> Option<usize> {
+    levels[..level]
+        .iter()
+        .rposition(|other| other.campaign == levels[level].campaign)
+}
+
+/// Work out if a level can be played, given a way to check if a level has been beaten
+fn availability_with(
+    levels: &[Level],
+    level: usize,
+    is_beaten: &dyn Fn(usize) -> bool,
+    unlocked_secrets: &HashSet<String>,
+) -> LevelAvailability {
+    let unlocked_if = |condition: bool| {
+        if condition {
+            LevelAvailability::Unlocked
+        } else {
+            LevelAvailability::Locked
+        }
+    };
+
+    match &levels[level].unlock {
+        UnlockRule::Always => LevelAvailability::Unlocked,
+        UnlockRule::PreviousBeaten => unlocked_if(
+            previous_in_campaign(levels, level).map_or(true, |previous| is_beaten(previous)),
+        ),
+        UnlockRule::AllMainLevelsBeaten => unlocked_if(
+            levels
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| {
+                    matches!(
+                        other.unlock,
+                        UnlockRule::Always | UnlockRule::PreviousBeaten
+                    )
+                })
+                .all(|(idx, _)| is_beaten(idx)),
+        ),
+        UnlockRule::Secret(code) => {
+            if unlocked_secrets.contains(code) {
+                LevelAvailability::Unlocked
+            } else {
+                LevelAvailability::Hidden
+            }
+        }
+    }
+}
+
+/// Check if a level can be played with the player's current progress
+pub fn level_availability(
+    levels: &[Level],
+    level: usize,
+    progress: &ProgressData,
+) -> LevelAvailability {
+    availability_with(
+        levels,
+        level,
+        &|idx| progress.is_level_beaten(&levels[idx].name),
+        &progress.unlocked_secrets,
+    )
+}
+
+/// Find the next level to play in the same campaign after beating `current`.
+/// Returns `None` if the campaign has no more playable levels
+pub fn next_level(levels: &[Level], current: usize, progress: &ProgressData) -> Option<usize> {
+    // This is called right as a level is won, before the time is saved, so the current level must count as beaten
+    let is_beaten = |idx: usize| idx == current || progress.is_level_beaten(&levels[idx].name);
+    (current + 1..levels.len())
+        .filter(|idx| levels[*idx].campaign == levels[current].campaign)
+        .find(|idx| {
+            availability_with(levels, *idx, &is_beaten, &progress.unlocked_secrets)
+                == LevelAvailability::Unlocked
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+    use raylib::math::Rectangle;
+
+    use super::*;
+    use crate::scenes::ingame_scene::level::LevelZones;
+
+    fn build_levels(rules: Vec<UnlockRule>) -> Vec<Level> {
+        build_campaign_levels(rules.into_iter().map(|rule| (rule, 0)).collect())
+    }
+
+    fn build_campaign_levels(rules: Vec<(UnlockRule, usize)>) -> Vec<Level> {
+        rules
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (unlock, campaign))| Level {
+                name: format!("level_{}", idx),
+                unlock,
+                campaign,
+                platform_height: 800.0,
+                authored_colliders: Vec::new(),
+                colliders: Vec::new(),
+                zones: LevelZones {
+                    appear: Vec::new(),
+                    disappear: Vec::new(),
+                    kill: Vec::new(),
+                    win: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+                    splits: Vec::new(),
+                    extra: Default::default(),
+                },
+                background: Vec::new(),
+                textures: None,
+                content_hash: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_levels_unlock_in_order() {
+        let levels = build_levels(vec![UnlockRule::PreviousBeaten; 3]);
+        let mut progress = ProgressData::default();
+        assert_eq!(
+            level_availability(&levels, 0, &progress),
+            LevelAvailability::Unlocked
+        );
+        assert_eq!(
+            level_availability(&levels, 1, &progress),
+            LevelAvailability::Locked
+        );
+
+        progress.maybe_write_new_time("level_0", 0, &Duration::seconds(30), &[]);
+        assert_eq!(
+            level_availability(&levels, 1, &progress),
+            LevelAvailability::Unlocked
+        );
+        assert_eq!(
+            level_availability(&levels, 2, &progress),
+            LevelAvailability::Locked
+        );
+    }
+
+    #[test]
+    fn test_bonus_and_secret_levels() {
+        let levels = build_levels(vec![
+            UnlockRule::PreviousBeaten,
+            UnlockRule::Always,
+            UnlockRule::AllMainLevelsBeaten,
+            UnlockRule::Secret("ABYSS".to_string()),
+        ]);
+        let mut progress = ProgressData::default();
+        progress.maybe_write_new_time("level_0", 0, &Duration::seconds(30), &[]);
+        assert_eq!(
+            level_availability(&levels, 2, &progress),
+            LevelAvailability::Locked
+        );
+        assert_eq!(
+            level_availability(&levels, 3, &progress),
+            LevelAvailability::Hidden
+        );
+
+        progress.maybe_write_new_time("level_1", 0, &Duration::seconds(30), &[]);
+        progress.unlocked_secrets.insert("ABYSS".to_string());
+        assert_eq!(
+            level_availability(&levels, 2, &progress),
+            LevelAvailability::Unlocked
+        );
+        assert_eq!(
+            level_availability(&levels, 3, &progress),
+            LevelAvailability::Unlocked
+        );
+    }
+
+    #[test]
+    fn test_next_level_skips_hidden_levels() {
+        let levels = build_levels(vec![
+            UnlockRule::PreviousBeaten,
+            UnlockRule::Secret("ABYSS".to_string()),
+            UnlockRule::Always,
+        ]);
+        let progress = ProgressData::default();
+        assert_eq!(next_level(&levels, 0, &progress), Some(2));
+        assert_eq!(next_level(&levels, 2, &progress), None);
+
+        // Beating a level unlocks the one after it right away
+        let levels = build_levels(vec![UnlockRule::PreviousBeaten; 2]);
+        assert_eq!(next_level(&levels, 0, &progress), Some(1));
+    }
+
+    #[test]
+    fn test_campaigns_unlock_separately() {
+        let levels = build_campaign_levels(vec![
+            (UnlockRule::PreviousBeaten, 0),
+            (UnlockRule::PreviousBeaten, 0),
+            (UnlockRule::PreviousBeaten, 1),
+            (UnlockRule::PreviousBeaten, 1),
+        ]);
+        let progress = ProgressData::default();
+
+        // The first level of every campaign is playable
+        assert_eq!(
+            level_availability(&levels, 2, &progress),
+            LevelAvailability::Unlocked
+        );
+        assert_eq!(
+            level_availability(&levels, 3, &progress),
+            LevelAvailability::Locked
+        );
+
+        // Beating the last level of a campaign does not lead into the next one
+        assert_eq!(next_level(&levels, 1, &progress), None);
+        assert_eq!(next_level(&levels, 2, &progress), Some(3));
+    }
+}
@@ -6,19 +6,34 @@ use raylib::prelude::*;
 use crate::{
     character::{CharacterState, MainCharacter},
     context::{ControlFlag, GameContext},
+    input::InputAction,
     utilities::{
         render_layer::{FrameUpdate, ScreenSpaceRender, WorldSpaceRender},
         world_paint_texture::WorldPaintTexture,
     },
 };
 
-use self::level::Level;
-
+use self::{
+    attempt::{Attempts, ResumePoint},
+    hud::HudInfo,
+    level::{
+        endless::{EndlessRun, DAILY_CHALLENGE_CHUNKS},
+        unlock::next_level,
+        Level,
+    },
+    timer::LevelTimer,
+    world::WORLD_LEVEL_X_OFFSET,
+};
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, trace};
 
+/// How many world pixels make up one meter of distance in endless runs
+pub const PIXELS_PER_METER: f32 = 50.0;
+
+mod attempt;
 mod hud;
 pub mod level;
+pub mod timer;
 mod update;
 pub mod world;
 
@@ -27,10 +42,29 @@ pub struct InGameScreen {
     camera: Camera2D,
     player: MainCharacter,
     world_background: WorldPaintTexture,
-    levels: Vec<Level>,
     current_level_idx: usize,
     player_dead: bool,
     level_switch_timestamp: DateTime<Utc>,
+    /// Set while playing an endless run or daily challenge
+    endless: Option<EndlessRun>,
+    endless_best_distance: u64,
+    attempts: Attempts,
+    timer: LevelTimer,
+    hud: HudInfo,
+    /// Set while an overlay is open over the level, so closing it carries on instead of starting over
+    resume_point: Option<ResumePoint>,
+}
+
+/// Get the level being played. Endless runs build their own level instead of using one from the context
+fn active_level<'a>(
+    endless: &'a Option<EndlessRun>,
+    context: &'a GameContext,
+    level_idx: usize,
+) -> &'a Level {
+    match endless {
+        Some(run) => &run.level,
+        None => context.levels.get(level_idx).unwrap(),
+    }
 }
 
 impl InGameScreen {
@@ -38,7 +72,6 @@ impl InGameScreen {
     pub fn new(
         player_sprite_sheet: Texture2D,
         background_texture: Texture2D,
-        levels: Vec<Level>,
     ) -> Self {
         Self {
             camera: Camera2D {
@@ -49,12 +82,54 @@ impl InGameScreen {
             },
             player: MainCharacter::new(Vector2::new(0.0, -85.0), player_sprite_sheet),
             world_background: WorldPaintTexture::new(background_texture),
-            levels,
             current_level_idx: 0,
             player_dead: false,
             level_switch_timestamp: Utc::now(),
+            endless: None,
+            endless_best_distance: 0,
+            attempts: Attempts::default(),
+            timer: LevelTimer::default(),
+            hud: HudInfo::default(),
+            resume_point: None,
         }
     }
+
+    /// Put the player, the level, and the splits back to the start of the level
+    fn start_attempt(&mut self, context: &GameContext) {
+        self.player_dead = false;
+        self.player.reset();
+        self.attempts.start(context.level_start_time);
+        self.timer.start_attempt();
+
+        // When test-playing from the level editor, start wherever the designer asked
+        if let Some(spawn) = context.playtest_spawn {
+            self.player.position = spawn;
+        }
+
+        // Endless runs start from scratch every time, just like normal levels
+        self.endless = match (context.endless_seed, &context.endless_catalogue) {
+            (Some(seed), Some(catalogue)) if context.daily_challenge.is_some() => Some(
+                EndlessRun::with_length(catalogue.clone(), seed, DAILY_CHALLENGE_CHUNKS),
+            ),
+            (Some(seed), Some(catalogue)) => Some(EndlessRun::new(catalogue.clone(), seed)),
+            _ => None,
+        };
+        self.endless_best_distance = context.player_progress.endless_best_distance;
+
+        // Set the player to running
+        let cur_level = active_level(&self.endless, context, context.current_level);
+        let _ = self.player.update_player(
+            Some(CharacterState::Running),
+            &cur_level.colliders,
+            &cur_level.zones.kill,
+            cur_level.height_offset(),
+        );
+    }
+
+    /// How far the player has made it into the current endless run, in meters
+    fn endless_distance(&self) -> u64 {
+        ((self.player.position.x - WORLD_LEVEL_X_OFFSET).max(0.0) / PIXELS_PER_METER) as u64
+    }
 }
 
 impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
@@ -66,23 +141,33 @@ impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
     fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
         debug!("Running InGameScreen for the first time");
 
-        // Handle cleanup after death
-        self.player_dead = false;
-        self.player.reset();
-
-        // Set the player to running
-        let cur_level = self.levels.get(context.current_level).unwrap();
-        let _ = self.player.update_player(
-            Some(CharacterState::Running),
-            &cur_level.colliders,
-            &cur_level.zones.kill,
-            -cur_level.platform_tex.height as f32,
-        );
+        // Closing an overlay goes back to exactly where the player was, with the same run and time
+        if let Some(point) = ResumePoint::take_for(&mut self.resume_point, context.level_start_time)
+        {
+            self.player.position = point.position;
+            self.timer = point.timer;
+        } else {
+            // Pick the timer up from wherever it was left. Other screens can reset it with `ControlFlag::RestartLevel`
+            self.timer = context.level_timer.clone();
+            self.start_attempt(context);
+        }
 
         // Update discord
+        let details = if let Some(date) = context.daily_challenge {
+            context.locale.text_with(
+                "discord.daily",
+                &[("date", &date.format("%Y-%m-%d"))],
+            )
+        } else if self.endless.is_some() {
+            context.locale.text("discord.endless").to_string()
+        } else {
+            context
+                .locale
+                .text_with("discord.level", &[("level", &context.current_level)])
+        };
         if let Err(e) = context.discord_rpc_send.send(Some(
             ActivityBuilder::default()
-                .details(format!("LVL {}", context.current_level))
+                .details(details)
                 .assets(
                     Assets::default().large("game-logo-small", Some(context.config.name.clone())),
                 )
@@ -117,11 +202,23 @@ impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
             //     .unwrap();
         }
 
+        // Restarting skips the death screen, and starts the run over from nothing within this frame
+        if context.input.is_pressed(InputAction::Restart) {
+            if self.endless.as_ref().map_or(false, EndlessRun::is_endless) {
+                context
+                    .flag_send
+                    .send(Some(ControlFlag::EndEndlessRun(self.endless_distance())))
+                    .unwrap();
+            }
+            self.timer = LevelTimer::default();
+            self.start_attempt(context);
+        }
+
         // Grab exclusive access to the renderer
         let mut renderer = context.renderer.borrow_mut();
 
         // Update the inputs and checking logic
-        self.update(&mut renderer, delta, &context.config);
+        self.update(&mut renderer, delta, context);
 
         // Wipe the background
         renderer.clear_background(context.config.colors.background);
@@ -132,15 +229,48 @@ impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
             let mut raylib_camera_space = renderer.begin_mode2D(self.camera);
 
             // Render in world space
-            self.render_world_space(&mut raylib_camera_space, &context.config);
+            self.render_world_space(&mut raylib_camera_space, context);
         }
 
         // Render the HUD
+        self.update_hud(context);
         self.render_screen_space(&mut renderer, &context.config);
 
+        // Endless runs end when the player dies. Pausing only hands the distance over, so it can
+        // still be saved if the run is left from the pause menu
+        let is_pausing = context.input.is_pressed(InputAction::Pause);
+        let is_leaving = self.player_dead || is_pausing;
+        if self.endless.as_ref().map_or(false, EndlessRun::is_endless) {
+            if self.player_dead {
+                context
+                    .flag_send
+                    .send(Some(ControlFlag::EndEndlessRun(self.endless_distance())))
+                    .unwrap();
+            } else if is_pausing {
+                context
+                    .flag_send
+                    .send(Some(ControlFlag::UpdateEndlessDistance(self.endless_distance())))
+                    .unwrap();
+            }
+        }
+
+        // Hand the timer over whenever the level is left, so other screens can show it and it can be saved
+        let cur_level = active_level(&self.endless, context, self.current_level_idx);
+        let is_won = self.player.position.x > cur_level.zones.win.x;
+        if is_leaving || is_won {
+            context
+                .flag_send
+                .send(Some(ControlFlag::UpdateLevelTimer(self.timer.clone())))
+                .unwrap();
+        }
+
         // Check if the player won
-        let cur_level = self.levels.get(context.current_level).unwrap();
-        if self.player.position.x > cur_level.zones.win.x {
+        if is_won {
+            // Test-plays never count towards progress
+            if context.playtest_spawn.is_some() {
+                return Ok(ActionFlag::SwitchState(Scenes::LevelEditor));
+            }
+
             // Save the current time
             // let elapsed = Utc::now() - self.level_switch_timestamp;
             // context
@@ -156,8 +286,13 @@ impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
             // End the level
             context.flag_send.send(Some(ControlFlag::EndLevel)).unwrap();
 
-            // If this is the last level, win the game
-            if self.current_level_idx >= self.levels.len() - 1 {
+            // If there are no more levels to play, or this is a daily challenge, win the game
+            let next_level = next_level(
+                &context.levels,
+                self.current_level_idx,
+                &context.player_progress,
+            );
+            if self.endless.is_some() || next_level.is_none() {
                 return Ok(ActionFlag::SwitchState(Scenes::WinScreen));
             } else {
                 // Otherwise, increment the level counter and switch to the next level
@@ -170,7 +305,17 @@ impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
             }
         }
 
-        if renderer.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        if context.playtest_spawn.is_some()
+            && is_leaving
+        {
+            // Test-plays go straight back to the editor
+            Ok(ActionFlag::SwitchState(Scenes::LevelEditor))
+        } else if is_pausing {
+            self.resume_point = Some(ResumePoint::new(
+                context.level_start_time,
+                self.player.position,
+                self.timer.clone(),
+            ));
             Ok(ActionFlag::SwitchState(Scenes::PauseScreen))
         } else if self.player_dead {
             Ok(ActionFlag::SwitchState(Scenes::DeathScreen))
@@ -188,6 +333,11 @@ impl Action<Scenes, ScreenError, GameContext> for InGameScreen {
             self.player.reset();
         }
 
+        // A new run is built when the scene starts again, unless it is only paused
+        if self.resume_point.is_none() {
+            self.endless = None;
+        }
+
         Ok(())
     }
 }
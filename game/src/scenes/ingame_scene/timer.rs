@@ -0,0 +1,124 @@
+//! The level timer.
+//!
+//! Time only passes while the level is being simulated, so pausing, dying, and loading do not count
+//! towards a run. Deaths do not reset the timer, only starting the level again from a menu does.
+
+use chrono::Duration;
+
+/// The longest a single tick may add to the timer. Anything longer is a hitch, not gameplay
+const MAX_TICK_MS: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct LevelTimer {
+    elapsed: Duration,
+    /// The time each split line was crossed on the current attempt, in order
+    splits: Vec<Duration>,
+}
+
+impl Default for LevelTimer {
+    fn default() -> Self {
+        Self {
+            elapsed: Duration::zero(),
+            splits: Vec::new(),
+        }
+    }
+}
+
+impl LevelTimer {
+    /// Add one simulation tick to the timer
+    pub fn tick(&mut self, delta: &Duration) {
+        self.elapsed = self.elapsed + (*delta).min(Duration::milliseconds(MAX_TICK_MS));
+    }
+
+    /// Forget the splits from the last attempt. The time keeps counting
+    pub fn start_attempt(&mut self) {
+        self.splits.clear();
+    }
+
+    /// Record a split for every split line the player has passed since the last call
+    pub fn update_splits(&mut self, split_lines: &[f32], position_x: f32) {
+        let passed = split_lines
+            .iter()
+            .take_while(|line| position_x > **line)
+            .count();
+        while self.splits.len() < passed {
+            self.splits.push(self.elapsed);
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+}
+
+/// Format a time as `MM:SS.mmm`
+pub fn format_time(time: &Duration) -> String {
+    format!(
+        "{:02}:{:02}.{:03}",
+        time.num_minutes(),
+        time.num_seconds() % 60,
+        time.num_milliseconds() % 1000
+    )
+}
+
+/// Format how far ahead (`-`) or behind (`+`) a run is, as `+MM:SS.mmm`
+pub fn format_delta(delta: &Duration) -> String {
+    let sign = if *delta < Duration::zero() { '-' } else { '+' };
+    let magnitude = if *delta < Duration::zero() {
+        -*delta
+    } else {
+        *delta
+    };
+    format!("{}{}", sign, format_time(&magnitude))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hitches_are_capped() {
+        let mut timer = LevelTimer::default();
+        timer.tick(&Duration::milliseconds(16));
+        timer.tick(&Duration::seconds(5));
+        assert_eq!(timer.elapsed(), Duration::milliseconds(116));
+    }
+
+    #[test]
+    fn test_splits_are_recorded_once() {
+        let lines = [100.0, 200.0, 300.0];
+        let mut timer = LevelTimer::default();
+        timer.tick(&Duration::milliseconds(50));
+        timer.update_splits(&lines, 50.0);
+        assert!(timer.splits().is_empty());
+        timer.update_splits(&lines, 150.0);
+        timer.tick(&Duration::milliseconds(50));
+        timer.update_splits(&lines, 160.0);
+
+        // Skipping past a line still records it
+        timer.update_splits(&lines, 350.0);
+        assert_eq!(
+            timer.splits(),
+            &[
+                Duration::milliseconds(50),
+                Duration::milliseconds(100),
+                Duration::milliseconds(100)
+            ]
+        );
+
+        timer.start_attempt();
+        assert!(timer.splits().is_empty());
+        assert_eq!(timer.elapsed(), Duration::milliseconds(100));
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(&Duration::milliseconds(-75_250)), "-01:15.250");
+        assert_eq!(format_delta(&Duration::milliseconds(4_005)), "+00:04.005");
+        assert_eq!(format_delta(&Duration::zero()), "+00:00.000");
+    }
+}
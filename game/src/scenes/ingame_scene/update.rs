@@ -1,7 +1,7 @@
 use std::ops::Div;
 
-use super::InGameScreen;
-use crate::{GameConfig, character::CharacterState, utilities::{math::{interpolate_exp_unchecked, linear_interpolate}, non_ref_raylib::HackedRaylibHandle, render_layer::FrameUpdate}};
+use super::{active_level, world::WORLD_LEVEL_X_OFFSET, InGameScreen};
+use crate::{character::CharacterState, context::GameContext, input::InputAction, utilities::{math::{interpolate_exp_unchecked, linear_interpolate}, non_ref_raylib::HackedRaylibHandle, render_layer::FrameUpdate}};
 use chrono::Duration;
 use raylib::prelude::*;
 use tracing::trace;
@@ -11,12 +11,24 @@ impl FrameUpdate for InGameScreen {
         &mut self,
         raylib: &HackedRaylibHandle,
         delta_seconds: &Duration,
-        config: &GameConfig,
+        context: &GameContext,
     ) {
         puffin::profile_function!();
 
+        // Endless runs keep building the level ahead of the player
+        if let Some(run) = &mut self.endless {
+            run.update(self.player.position.x - WORLD_LEVEL_X_OFFSET);
+        }
+
         // Get the current level
-        let cur_level = self.levels.get(self.current_level_idx).unwrap();
+        let cur_level = active_level(&self.endless, context, self.current_level_idx);
+
+        // Every update is one tick of the level timer
+        self.timer.tick(delta_seconds);
+        self.timer.update_splits(
+            &cur_level.zones.splits,
+            self.player.position.x - WORLD_LEVEL_X_OFFSET,
+        );
 
         // Set the camera's offset based on screen size
         self.camera.offset = raylib.get_screen_size().div(Vector2::new(2.0, 1.05));
@@ -24,10 +36,10 @@ impl FrameUpdate for InGameScreen {
         self.camera.zoom = linear_interpolate(raylib.get_screen_size().y.max(200.0), 720.0..1016.0, 0.85..1.2);
         trace!("Zoom level set to: {} {}", raylib.get_screen_size().y, self.camera.zoom);
 
-        // Check the only possible keyboard inputs
-        let is_jump = raylib.is_key_pressed(KeyboardKey::KEY_SPACE)
+        // Check the only possible inputs
+        let is_jump = context.input.is_pressed(InputAction::Jump)
             && !(self.player.current_state == CharacterState::Jumping);
-        let is_dash = raylib.is_key_pressed(KeyboardKey::KEY_LEFT_SHIFT)
+        let is_dash = context.input.is_pressed(InputAction::Dash)
             && !(self.player.current_state == CharacterState::Dashing);
 
         let collision_result = if is_jump {
@@ -35,14 +47,14 @@ impl FrameUpdate for InGameScreen {
                 Some(CharacterState::Jumping),
                 &cur_level.colliders,
                 &cur_level.zones.kill,
-                -cur_level.platform_tex.height as f32,
+                cur_level.height_offset(),
             )
         } else if is_dash {
             self.player.update_player(
                 Some(CharacterState::Dashing),
                 &cur_level.colliders,
                 &cur_level.zones.kill,
-                -cur_level.platform_tex.height as f32,
+                cur_level.height_offset(),
             )
         } else {
             if self.player.current_state != CharacterState::Jumping
@@ -52,14 +64,14 @@ impl FrameUpdate for InGameScreen {
                     Some(CharacterState::Running),
                     &cur_level.colliders,
                     &cur_level.zones.kill,
-                    -cur_level.platform_tex.height as f32,
+                    cur_level.height_offset(),
                 )
             } else {
                 self.player.update_player(
                     None,
                     &cur_level.colliders,
                     &cur_level.zones.kill,
-                    -cur_level.platform_tex.height as f32,
+                    cur_level.height_offset(),
                 )
             }
         };
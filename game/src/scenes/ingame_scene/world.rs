@@ -1,12 +1,16 @@
 use std::ops::{Div, Mul, Sub};
 
-use super::InGameScreen;
+use super::{
+    active_level,
+    level::{Level, LevelTextures},
+    InGameScreen,
+};
 use crate::{
     character::render::render_character_in_camera_space,
+    context::GameContext,
     utilities::{
         math::interpolate_exp, non_ref_raylib::HackedRaylibHandle, render_layer::WorldSpaceRender,
     },
-    GameConfig,
 };
 use raylib::prelude::*;
 use tracing::trace;
@@ -19,22 +23,73 @@ impl WorldSpaceRender for InGameScreen {
     fn render_world_space(
         &mut self,
         raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>,
-        config: &GameConfig,
+        context: &GameContext,
     ) {
         puffin::profile_function!();
 
         // Get the current level
-        let cur_level = self.levels.get(self.current_level_idx).unwrap();
+        let cur_level = active_level(&self.endless, context, self.current_level_idx);
+
+        // Level textures are streamed in by the main loop. If they are not ready yet, skip drawing the level this frame
+        if let Some(textures) = &cur_level.textures {
+            self.render_level_textures(raylib, cur_level, textures);
+        }
+
+        #[cfg(all(debug_assertions, feature = "collider_debug"))]
+        {
+            for collider in &cur_level.colliders {
+                let mut translated_collider = collider.clone();
+                translated_collider.y += cur_level.height_offset();
+                translated_collider.x += WORLD_LEVEL_X_OFFSET;
+                raylib.draw_rectangle_lines_ex(translated_collider, 5, Color::RED);
+            }
+        }
+
+        // Render the floor as a line
+        let screen_world_zero = raylib.get_screen_to_world2D(Vector2::zero(), self.camera);
+        let screen_world_size =
+            raylib.get_screen_to_world2D(raylib.get_screen_size().mul(2.0), self.camera);
+
+        raylib.draw_rectangle(
+            screen_world_zero.x as i32,
+            0,
+            screen_world_size.x as i32,
+            5,
+            context.config.colors.white,
+        );
+
+        // Render the player
+        render_character_in_camera_space(raylib, &self.player, &context.config);
+    }
+}
 
-        // Render the world background
-        cur_level
-            .background_tex
-            .render(raylib, Vector2::new(0.0, -1080.0), &self.camera);
+impl InGameScreen {
+    /// Renders the background and all platform layers of a level
+    fn render_level_textures(
+        &self,
+        raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>,
+        cur_level: &Level,
+        textures: &LevelTextures,
+    ) {
+        // Render the world background layers, back to front
+        for (layer, texture) in cur_level
+            .background
+            .iter()
+            .zip(textures.background_layers.iter())
+        {
+            texture.render_parallax(
+                raylib,
+                Vector2::new(0.0, layer.y_offset - texture.height() as f32),
+                &self.camera,
+                &layer.parallax_settings(),
+            );
+        }
 
         // Render the platform layer
-        raylib.draw_texture_v(
-            &cur_level.platform_tex,
-            Vector2::new(WORLD_LEVEL_X_OFFSET, -cur_level.platform_tex.height as f32),
+        textures.platform_tex.render(
+            raylib,
+            Vector2::new(WORLD_LEVEL_X_OFFSET, -textures.platform_tex.height as f32),
+            &self.camera,
             Color::WHITE,
         );
 
@@ -72,12 +127,13 @@ impl WorldSpaceRender for InGameScreen {
             );
 
             // Render the appearing layer
-            raylib.draw_texture_v(
-                &cur_level.appearing_platform_tex,
+            textures.appearing_platform_tex.render(
+                raylib,
                 Vector2::new(
                     WORLD_LEVEL_X_OFFSET,
-                    -cur_level.appearing_platform_tex.height as f32,
+                    -textures.appearing_platform_tex.height as f32,
                 ),
+                &self.camera,
                 Color::WHITE.fade(appear_opacity),
             );
         }
@@ -116,40 +172,15 @@ impl WorldSpaceRender for InGameScreen {
             );
 
             // Render the appearing layer
-            raylib.draw_texture_v(
-                &cur_level.disappearing_platform_tex,
+            textures.disappearing_platform_tex.render(
+                raylib,
                 Vector2::new(
                     WORLD_LEVEL_X_OFFSET,
-                    -cur_level.disappearing_platform_tex.height as f32,
+                    -textures.disappearing_platform_tex.height as f32,
                 ),
+                &self.camera,
                 Color::WHITE.fade(1.0 - disappear_opacity),
             );
         }
-
-        #[cfg(all(debug_assertions, feature = "collider_debug"))]
-        {
-            for collider in &cur_level.colliders {
-                let mut translated_collider = collider.clone();
-                translated_collider.y += -cur_level.platform_tex.height as f32;
-                translated_collider.x += WORLD_LEVEL_X_OFFSET;
-                raylib.draw_rectangle_lines_ex(translated_collider, 5, Color::RED);
-            }
-        }
-
-        // Render the floor as a line
-        let screen_world_zero = raylib.get_screen_to_world2D(Vector2::zero(), self.camera);
-        let screen_world_size =
-            raylib.get_screen_to_world2D(raylib.get_screen_size().mul(2.0), self.camera);
-
-        raylib.draw_rectangle(
-            screen_world_zero.x as i32,
-            0,
-            screen_world_size.x as i32,
-            5,
-            config.colors.white,
-        );
-
-        // Render the player
-        render_character_in_camera_space(raylib, &self.player, &config);
     }
 }
@@ -0,0 +1,812 @@
+//! A development tool for drawing a level's colliders and zones with the mouse
+
+use std::rc::Rc;
+
+use chrono::Duration;
+use dirty_fsm::{Action, ActionFlag};
+use discord_sdk::activity::{ActivityBuilder, Assets};
+use raylib::prelude::*;
+
+use crate::{
+    context::{ControlFlag, GameContext},
+    locale::Locale,
+    utilities::{
+        layout::Anchor,
+        non_ref_raylib::HackedRaylibHandle,
+        render_layer::{FrameUpdate, ScreenSpaceRender, WorldSpaceRender},
+    },
+    GameConfig,
+};
+
+use super::{
+    ingame_scene::{
+        level::{loader::save_level_data, Level, LevelZones},
+        world::WORLD_LEVEL_X_OFFSET,
+    },
+    Scenes, ScreenError,
+};
+use tracing::{debug, error, trace};
+
+/// Anything drawn above this line on screen is part of the toolbar, and should not be clicked through
+const TOOLBAR_HEIGHT: f32 = 70.0;
+
+/// Size of the grid that edits snap to, in level pixels
+const GRID_SIZE: f32 = 8.0;
+
+/// How close (in screen pixels) the mouse has to be to an edge to grab it
+const EDGE_GRAB_DISTANCE: f32 = 8.0;
+
+/// How fast the keyboard pans the camera, in screen pixels per second
+const PAN_SPEED: f32 = 1200.0;
+
+/// How long status messages stay on screen, in frames
+const STATUS_MESSAGE_FRAMES: i32 = 120;
+
+/// The kind of data currently being edited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditorTool {
+    Collider,
+    Kill,
+    Appear,
+    Disappear,
+    Win,
+}
+
+impl EditorTool {
+    const ALL: [EditorTool; 5] = [
+        EditorTool::Collider,
+        EditorTool::Kill,
+        EditorTool::Appear,
+        EditorTool::Disappear,
+        EditorTool::Win,
+    ];
+
+    fn label_key(&self) -> &'static str {
+        match self {
+            EditorTool::Collider => "editor.tool_collider",
+            EditorTool::Kill => "editor.tool_kill",
+            EditorTool::Appear => "editor.tool_appear",
+            EditorTool::Disappear => "editor.tool_disappear",
+            EditorTool::Win => "editor.tool_win",
+        }
+    }
+
+    fn color(&self, config: &GameConfig) -> Color {
+        match self {
+            EditorTool::Collider => config.colors.green,
+            EditorTool::Kill => config.colors.red,
+            EditorTool::Appear => config.colors.blue,
+            EditorTool::Disappear => config.colors.yellow,
+            EditorTool::Win => config.colors.pink,
+        }
+    }
+}
+
+/// What the mouse is currently doing
+#[derive(Debug, Clone, Copy)]
+enum DragAction {
+    /// Drawing a new rectangle, starting at a point in level space
+    Create { start: Vector2 },
+    /// Moving the selected rectangle. The offset is from the rectangle's corner to the mouse
+    Move { grab_offset: Vector2 },
+    /// Dragging one or more edges of the selected rectangle
+    Resize {
+        left: bool,
+        right: bool,
+        top: bool,
+        bottom: bool,
+    },
+    /// Dragging the win line
+    MoveWin,
+    /// Panning the camera
+    Pan { last_mouse: Vector2 },
+}
+
+#[derive(Debug)]
+pub struct LevelEditorScreen {
+    camera: Camera2D,
+    level_idx: Option<usize>,
+    level_name: String,
+    height_offset: f32,
+    colliders: Vec<Rectangle>,
+    zones: LevelZones,
+    /// A copy of the data as it is on disk, used to throw away changes
+    saved_data: (Vec<Rectangle>, LevelZones),
+    tool: EditorTool,
+    selected: Option<usize>,
+    drag: Option<DragAction>,
+    mouse_level_position: Vector2,
+    snap_to_grid: bool,
+    has_unsaved_changes: bool,
+    /// The locale key of the status message, and how many frames it has left
+    status_message: Option<(&'static str, i32)>,
+    wants_save: bool,
+    wants_exit: bool,
+    playtest_spawn: Option<Vector2>,
+    locale: Rc<Locale>,
+}
+
+impl LevelEditorScreen {
+    /// Construct a new `LevelEditorScreen`
+    pub fn new() -> Self {
+        let empty_zones = LevelZones {
+            appear: Vec::new(),
+            disappear: Vec::new(),
+            kill: Vec::new(),
+            win: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            splits: Vec::new(),
+            extra: Default::default(),
+        };
+        Self {
+            camera: Camera2D {
+                offset: Vector2::zero(),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 0.5,
+            },
+            level_idx: None,
+            level_name: String::new(),
+            height_offset: 0.0,
+            colliders: Vec::new(),
+            zones: empty_zones.clone(),
+            saved_data: (Vec::new(), empty_zones),
+            tool: EditorTool::Collider,
+            selected: None,
+            drag: None,
+            mouse_level_position: Vector2::zero(),
+            snap_to_grid: true,
+            has_unsaved_changes: false,
+            status_message: None,
+            wants_save: false,
+            wants_exit: false,
+            playtest_spawn: None,
+            locale: Rc::default(),
+        }
+    }
+
+    /// Take a working copy of a level's data
+    fn open_level(&mut self, level_idx: usize, level: &Level) {
+        self.level_idx = Some(level_idx);
+        self.level_name = level.name.clone();
+        self.height_offset = level.height_offset();
+        self.colliders = level.authored_colliders.clone();
+        self.zones = level.zones.clone();
+        self.saved_data = (self.colliders.clone(), self.zones.clone());
+        self.selected = None;
+        self.drag = None;
+        self.has_unsaved_changes = false;
+        self.camera.target = Vector2::new(WORLD_LEVEL_X_OFFSET + 960.0, self.height_offset / 2.0);
+        self.camera.zoom = 0.5;
+    }
+
+    /// Get the list of rectangles the current tool edits. The win line is not a list, so it has none
+    fn tool_rects(&mut self) -> Option<&mut Vec<Rectangle>> {
+        match self.tool {
+            EditorTool::Collider => Some(&mut self.colliders),
+            EditorTool::Kill => Some(&mut self.zones.kill),
+            EditorTool::Appear => Some(&mut self.zones.appear),
+            EditorTool::Disappear => Some(&mut self.zones.disappear),
+            EditorTool::Win => None,
+        }
+    }
+
+    fn level_to_world(&self, rect: &Rectangle) -> Rectangle {
+        Rectangle::new(
+            rect.x + WORLD_LEVEL_X_OFFSET,
+            rect.y + self.height_offset,
+            rect.width,
+            rect.height,
+        )
+    }
+
+    fn snap(&self, value: f32) -> f32 {
+        if self.snap_to_grid {
+            (value / GRID_SIZE).round() * GRID_SIZE
+        } else {
+            value.round()
+        }
+    }
+
+    fn show_status(&mut self, message_key: &'static str) {
+        self.status_message = Some((message_key, STATUS_MESSAGE_FRAMES));
+    }
+
+    /// Handle a left click in level space
+    fn begin_drag(&mut self, mouse: Vector2) {
+        if self.tool == EditorTool::Win {
+            self.zones.win.x = self.snap(mouse.x);
+            self.has_unsaved_changes = true;
+            self.drag = Some(DragAction::MoveWin);
+            return;
+        }
+
+        let grab_distance = EDGE_GRAB_DISTANCE / self.camera.zoom;
+        let snapped = Vector2::new(self.snap(mouse.x), self.snap(mouse.y));
+        let rects = self.tool_rects().unwrap();
+
+        // Prefer the most recently drawn rectangle when several overlap
+        let hit = rects
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, rect)| {
+                Rectangle::new(
+                    rect.x - grab_distance,
+                    rect.y - grab_distance,
+                    rect.width + grab_distance * 2.0,
+                    rect.height + grab_distance * 2.0,
+                )
+                .check_collision_point_rec(mouse)
+            })
+            .map(|(idx, rect)| (idx, rect.clone()));
+
+        match hit {
+            Some((idx, rect)) => {
+                let left = (mouse.x - rect.x).abs() < grab_distance;
+                let right = (mouse.x - (rect.x + rect.width)).abs() < grab_distance;
+                let top = (mouse.y - rect.y).abs() < grab_distance;
+                let bottom = (mouse.y - (rect.y + rect.height)).abs() < grab_distance;
+
+                self.drag = Some(if left || right || top || bottom {
+                    DragAction::Resize {
+                        left,
+                        right,
+                        top,
+                        bottom,
+                    }
+                } else {
+                    DragAction::Move {
+                        grab_offset: Vector2::new(mouse.x - rect.x, mouse.y - rect.y),
+                    }
+                });
+                self.selected = Some(idx);
+            }
+            None => {
+                self.selected = None;
+                self.drag = Some(DragAction::Create { start: snapped });
+            }
+        }
+    }
+
+    /// Apply the current drag to the data
+    fn continue_drag(&mut self, mouse: Vector2) {
+        let snapped = Vector2::new(self.snap(mouse.x), self.snap(mouse.y));
+        let selected = self.selected;
+        match self.drag {
+            Some(DragAction::MoveWin) => {
+                self.zones.win.x = snapped.x;
+            }
+            Some(DragAction::Move { grab_offset }) => {
+                let x = self.snap(mouse.x - grab_offset.x);
+                let y = self.snap(mouse.y - grab_offset.y);
+                if let (Some(idx), Some(rects)) = (selected, self.tool_rects()) {
+                    rects[idx].x = x;
+                    rects[idx].y = y;
+                }
+                self.has_unsaved_changes = true;
+            }
+            Some(DragAction::Resize {
+                left,
+                right,
+                top,
+                bottom,
+            }) => {
+                if let (Some(idx), Some(rects)) = (selected, self.tool_rects()) {
+                    let rect = &mut rects[idx];
+                    let mut x1 = rect.x;
+                    let mut y1 = rect.y;
+                    let mut x2 = rect.x + rect.width;
+                    let mut y2 = rect.y + rect.height;
+                    if left {
+                        x1 = snapped.x.min(x2 - GRID_SIZE);
+                    }
+                    if right {
+                        x2 = snapped.x.max(x1 + GRID_SIZE);
+                    }
+                    if top {
+                        y1 = snapped.y.min(y2 - GRID_SIZE);
+                    }
+                    if bottom {
+                        y2 = snapped.y.max(y1 + GRID_SIZE);
+                    }
+                    *rect = Rectangle::new(x1, y1, x2 - x1, y2 - y1);
+                }
+                self.has_unsaved_changes = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Finish the current drag, creating a rectangle if one was being drawn
+    fn end_drag(&mut self, mouse: Vector2) {
+        if let Some(DragAction::Create { start }) = self.drag {
+            let end = Vector2::new(self.snap(mouse.x), self.snap(mouse.y));
+            let rect = rect_from_points(start, end);
+
+            // Ignore clicks that did not really draw anything
+            if rect.width >= GRID_SIZE && rect.height >= GRID_SIZE {
+                if let Some(rects) = self.tool_rects() {
+                    rects.push(rect);
+                    let new_idx = rects.len() - 1;
+                    self.selected = Some(new_idx);
+                }
+                self.has_unsaved_changes = true;
+            }
+        }
+        self.drag = None;
+    }
+
+    /// Send the working copy to the game, so test-plays use it
+    fn push_level_data(&self, context: &GameContext, colliders: Vec<Rectangle>, zones: LevelZones) {
+        if let Some(level) = self.level_idx {
+            context
+                .flag_send
+                .send(Some(ControlFlag::ReplaceLevelData {
+                    level,
+                    colliders,
+                    zones,
+                }))
+                .unwrap();
+        }
+    }
+}
+
+/// Build a rectangle between two corners, in any order
+fn rect_from_points(a: Vector2, b: Vector2) -> Rectangle {
+    Rectangle::new(
+        a.x.min(b.x),
+        a.y.min(b.y),
+        (a.x - b.x).abs(),
+        (a.y - b.y).abs(),
+    )
+}
+
+impl Action<Scenes, ScreenError, GameContext> for LevelEditorScreen {
+    fn on_register(&mut self) -> Result<(), ScreenError> {
+        debug!("Registered");
+        Ok(())
+    }
+
+    fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
+        debug!("Running LevelEditorScreen for the first time");
+
+        // Coming back from a test-play
+        context
+            .flag_send
+            .send(Some(ControlFlag::SetPlaytestSpawn(None)))
+            .unwrap();
+
+        // Only take a fresh copy of the level when switching levels, so test-plays do not lose any work
+        if self.level_idx != Some(context.current_level) {
+            let level = context.levels.get(context.current_level).unwrap();
+            self.open_level(context.current_level, level);
+        }
+
+        if let Err(e) = context.discord_rpc_send.send(Some(
+            ActivityBuilder::default()
+                .details(context.locale.text("discord.editor"))
+                .assets(
+                    Assets::default().large("game-logo-small", Some(context.config.name.clone())),
+                ),
+        )) {
+            error!("Failed to update discord: {}", e);
+        }
+
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        delta: &chrono::Duration,
+        context: &GameContext,
+    ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
+        trace!("execute() called on LevelEditorScreen");
+
+        // Grab exclusive access to the renderer
+        let mut renderer = context.renderer.borrow_mut();
+
+        // Handle all inputs
+        self.update(&renderer, delta, context);
+
+        // Render the level
+        renderer.clear_background(context.config.colors.background);
+        {
+            let mut raylib_camera_space = renderer.begin_mode2D(self.camera);
+            self.render_world_space(&mut raylib_camera_space, context);
+        }
+
+        // Render the editor UI
+        self.locale = context.locale.clone();
+        self.render_screen_space(&mut renderer, &context.config);
+
+        if self.wants_save {
+            self.wants_save = false;
+            match save_level_data(&self.level_name, &self.colliders, &self.zones) {
+                Ok(_) => {
+                    self.saved_data = (self.colliders.clone(), self.zones.clone());
+                    self.has_unsaved_changes = false;
+                    self.push_level_data(context, self.colliders.clone(), self.zones.clone());
+                    self.show_status("editor.saved");
+                }
+                Err(e) => {
+                    error!("Failed to save level: {}", e);
+                    self.show_status("editor.save_failed");
+                }
+            }
+        }
+
+        if let Some(spawn) = self.playtest_spawn.take() {
+            // Run the level with the working copy, starting at the mouse
+            self.push_level_data(context, self.colliders.clone(), self.zones.clone());
+            context
+                .flag_send
+                .send(Some(ControlFlag::SetPlaytestSpawn(Some(spawn))))
+                .unwrap();
+            context
+                .flag_send
+                .send(Some(ControlFlag::BeginLevel(self.level_idx.unwrap())))
+                .unwrap();
+            Ok(ActionFlag::SwitchState(Scenes::InGameScene))
+        } else if self.wants_exit {
+            // Throw away anything that was not saved
+            self.push_level_data(
+                context,
+                self.saved_data.0.clone(),
+                self.saved_data.1.clone(),
+            );
+            self.level_idx = None;
+            context
+                .flag_send
+                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .unwrap();
+            Ok(ActionFlag::SwitchState(Scenes::LevelSelectScreen))
+        } else {
+            Ok(ActionFlag::Continue)
+        }
+    }
+
+    fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
+        debug!("Finished LevelEditorScreen");
+        self.drag = None;
+        self.wants_exit = false;
+        self.status_message = None;
+        Ok(())
+    }
+}
+
+impl FrameUpdate for LevelEditorScreen {
+    fn update(
+        &mut self,
+        raylib: &HackedRaylibHandle,
+        delta_seconds: &Duration,
+        _context: &GameContext,
+    ) {
+        let screen_size = raylib.get_screen_size();
+        let mouse_position = raylib.get_mouse_position();
+        self.camera.offset = screen_size.scale_by(0.5);
+
+        // Work out where the mouse is in the level
+        let mouse_world = raylib.get_screen_to_world2D(mouse_position, self.camera);
+        let mouse_level = Vector2::new(
+            mouse_world.x - WORLD_LEVEL_X_OFFSET,
+            mouse_world.y - self.height_offset,
+        );
+        self.mouse_level_position = mouse_level;
+
+        // Zoom around the mouse
+        let wheel = raylib.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.camera.zoom = (self.camera.zoom * (1.0 + wheel * 0.1)).max(0.1).min(4.0);
+            let new_mouse_world = raylib.get_screen_to_world2D(mouse_position, self.camera);
+            self.camera.target += mouse_world - new_mouse_world;
+        }
+
+        // Pan with the keyboard
+        let pan_distance =
+            PAN_SPEED * (delta_seconds.num_milliseconds() as f32 / 1000.0) / self.camera.zoom;
+        let ctrl_down = raylib.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || raylib.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if raylib.is_key_down(KeyboardKey::KEY_LEFT) || raylib.is_key_down(KeyboardKey::KEY_A) {
+            self.camera.target.x -= pan_distance;
+        }
+        if raylib.is_key_down(KeyboardKey::KEY_RIGHT) || raylib.is_key_down(KeyboardKey::KEY_D) {
+            self.camera.target.x += pan_distance;
+        }
+        if raylib.is_key_down(KeyboardKey::KEY_UP) || raylib.is_key_down(KeyboardKey::KEY_W) {
+            self.camera.target.y -= pan_distance;
+        }
+        if !ctrl_down
+            && (raylib.is_key_down(KeyboardKey::KEY_DOWN) || raylib.is_key_down(KeyboardKey::KEY_S))
+        {
+            self.camera.target.y += pan_distance;
+        }
+
+        // Pan with the right or middle mouse button
+        let pan_button_down = raylib.is_mouse_button_down(MouseButton::MOUSE_RIGHT_BUTTON)
+            || raylib.is_mouse_button_down(MouseButton::MOUSE_MIDDLE_BUTTON);
+        match self.drag {
+            Some(DragAction::Pan { last_mouse }) if pan_button_down => {
+                self.camera.target -=
+                    (mouse_position - last_mouse).scale_by(1.0 / self.camera.zoom);
+                self.drag = Some(DragAction::Pan {
+                    last_mouse: mouse_position,
+                });
+            }
+            Some(DragAction::Pan { .. }) => self.drag = None,
+            None if pan_button_down => {
+                self.drag = Some(DragAction::Pan {
+                    last_mouse: mouse_position,
+                })
+            }
+            _ => {}
+        }
+
+        // Tool shortcuts
+        let tool_keys = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+            KeyboardKey::KEY_FIVE,
+        ];
+        for (key, tool) in tool_keys.iter().zip(EditorTool::ALL.iter()) {
+            if raylib.is_key_pressed(*key) && self.tool != *tool {
+                self.tool = *tool;
+                self.selected = None;
+            }
+        }
+        if raylib.is_key_pressed(KeyboardKey::KEY_G) {
+            self.snap_to_grid = !self.snap_to_grid;
+        }
+        if raylib.is_key_pressed(KeyboardKey::KEY_DELETE)
+            || raylib.is_key_pressed(KeyboardKey::KEY_BACKSPACE)
+        {
+            if let Some(idx) = self.selected.take() {
+                if let Some(rects) = self.tool_rects() {
+                    rects.remove(idx);
+                }
+                self.has_unsaved_changes = true;
+            }
+        }
+        if raylib.is_key_pressed(KeyboardKey::KEY_P) {
+            self.playtest_spawn = Some(mouse_world);
+        }
+        if ctrl_down && raylib.is_key_pressed(KeyboardKey::KEY_S) {
+            self.wants_save = true;
+        }
+        if raylib.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            if self.has_unsaved_changes
+                && !matches!(self.status_message, Some(("editor.unsaved_changes", _)))
+            {
+                self.show_status("editor.unsaved_changes");
+            } else {
+                self.wants_exit = true;
+            }
+        }
+
+        // Editing with the left mouse button. Clicks on the toolbar are handled by the UI instead
+        if raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON)
+            && mouse_position.y > raylib.layout().scaled(TOOLBAR_HEIGHT)
+            && self.drag.is_none()
+        {
+            self.begin_drag(mouse_level);
+        } else if raylib.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            self.continue_drag(mouse_level);
+        } else if raylib.is_mouse_button_released(MouseButton::MOUSE_LEFT_BUTTON) {
+            self.end_drag(mouse_level);
+        }
+
+        // Count down the status message
+        if let Some((_, frames)) = &mut self.status_message {
+            *frames -= 1;
+            if *frames <= 0 {
+                self.status_message = None;
+            }
+        }
+    }
+}
+
+impl WorldSpaceRender for LevelEditorScreen {
+    fn render_world_space(
+        &mut self,
+        raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>,
+        context: &GameContext,
+    ) {
+        let level = context.levels.get(self.level_idx.unwrap()).unwrap();
+
+        // Render the level art. Appearing and disappearing platforms are shown half faded
+        if let Some(textures) = &level.textures {
+            for (layer, texture) in level
+                .background
+                .iter()
+                .zip(textures.background_layers.iter())
+            {
+                texture.render_parallax(
+                    raylib,
+                    Vector2::new(0.0, layer.y_offset - texture.height() as f32),
+                    &self.camera,
+                    &layer.parallax_settings(),
+                );
+            }
+            for (layer, tint) in [
+                (&textures.platform_tex, Color::WHITE),
+                (&textures.appearing_platform_tex, Color::WHITE.fade(0.5)),
+                (&textures.disappearing_platform_tex, Color::WHITE.fade(0.5)),
+            ] {
+                layer.render(
+                    raylib,
+                    Vector2::new(WORLD_LEVEL_X_OFFSET, -layer.height as f32),
+                    &self.camera,
+                    tint,
+                );
+            }
+        }
+
+        // Render the floor
+        let line_width = 2.0 / self.camera.zoom;
+        let screen_left = raylib.get_screen_to_world2D(Vector2::zero(), self.camera);
+        let screen_right = raylib.get_screen_to_world2D(raylib.get_screen_size(), self.camera);
+        raylib.draw_line_ex(
+            Vector2::new(screen_left.x, 0.0),
+            Vector2::new(screen_right.x, 0.0),
+            line_width,
+            context.config.colors.white,
+        );
+
+        // Render every rectangle, with the current tool's on top
+        let mut tools = EditorTool::ALL.to_vec();
+        tools.retain(|tool| *tool != self.tool && *tool != EditorTool::Win);
+        if self.tool != EditorTool::Win {
+            tools.push(self.tool);
+        }
+        for tool in tools {
+            let color = tool.color(&context.config);
+            let rects = match tool {
+                EditorTool::Collider => &self.colliders,
+                EditorTool::Kill => &self.zones.kill,
+                EditorTool::Appear => &self.zones.appear,
+                EditorTool::Disappear => &self.zones.disappear,
+                EditorTool::Win => unreachable!(),
+            };
+            let is_active = tool == self.tool;
+            for (idx, rect) in rects.iter().enumerate() {
+                let world_rect = self.level_to_world(rect);
+                raylib
+                    .draw_rectangle_rec(world_rect, color.fade(if is_active { 0.3 } else { 0.1 }));
+                let outline = if is_active && self.selected == Some(idx) {
+                    Color::WHITE
+                } else {
+                    color
+                };
+                raylib.draw_rectangle_lines_ex(world_rect, line_width.max(1.0) as i32, outline);
+            }
+        }
+
+        // Render the win line
+        let win_x = self.zones.win.x + WORLD_LEVEL_X_OFFSET;
+        raylib.draw_line_ex(
+            Vector2::new(win_x, self.height_offset - 1000.0),
+            Vector2::new(win_x, 0.0),
+            line_width * 2.0,
+            EditorTool::Win.color(&context.config),
+        );
+
+        // Split lines can only be changed in zones.json, so they are just shown for reference
+        for split_x in &self.zones.splits {
+            let split_x = split_x + WORLD_LEVEL_X_OFFSET;
+            raylib.draw_line_ex(
+                Vector2::new(split_x, self.height_offset - 1000.0),
+                Vector2::new(split_x, 0.0),
+                line_width,
+                EditorTool::Win.color(&context.config).fade(0.5),
+            );
+        }
+
+        // Render the rectangle being drawn
+        if let Some(DragAction::Create { start }) = self.drag {
+            let end = Vector2::new(
+                self.snap(self.mouse_level_position.x),
+                self.snap(self.mouse_level_position.y),
+            );
+            raylib.draw_rectangle_lines_ex(
+                self.level_to_world(&rect_from_points(start, end)),
+                line_width.max(1.0) as i32,
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+impl ScreenSpaceRender for LevelEditorScreen {
+    fn render_screen_space(&mut self, raylib: &mut HackedRaylibHandle, config: &GameConfig) {
+        let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
+        let mouse_position = raylib.get_mouse_position();
+        let mouse_pressed = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        let locale = self.locale.clone();
+
+        // Toolbar background
+        raylib.draw_rectangle(
+            0,
+            0,
+            screen_size.x as i32,
+            layout.scaled(TOOLBAR_HEIGHT) as i32,
+            Color::BLACK.fade(0.8),
+        );
+
+        // Tool buttons
+        for (i, tool) in EditorTool::ALL.iter().enumerate() {
+            let position = layout.at(
+                Anchor::TopLeft,
+                Vector2::new(20.0 + (170.0 * i as f32), 10.0),
+            );
+            let hovering = Rectangle::new(
+                position.x,
+                position.y,
+                layout.scaled(160.0),
+                layout.scaled(20.0),
+            )
+            .check_collision_point_rec(mouse_position);
+            raylib.draw_rgb_split_text(
+                position,
+                &format!("[{}] {}", i + 1, locale.text(tool.label_key())),
+                20,
+                hovering || self.tool == *tool,
+                if self.tool == *tool {
+                    tool.color(config)
+                } else {
+                    Color::WHITE
+                },
+            );
+            if hovering && mouse_pressed && self.tool != *tool {
+                self.tool = *tool;
+                self.selected = None;
+            }
+        }
+
+        // Save button
+        let save_position = layout.at(Anchor::TopRight, Vector2::new(-100.0, 10.0));
+        let save = locale.text("editor.save");
+        let save_size = raylib.measure_ui_text_ex(save, 20);
+        let hovering_save =
+            Rectangle::new(save_position.x, save_position.y, save_size.x, save_size.y)
+                .check_collision_point_rec(mouse_position);
+        raylib.draw_rgb_split_text(save_position, save, 20, hovering_save, Color::WHITE);
+        if hovering_save && mouse_pressed {
+            self.wants_save = true;
+        }
+
+        // Status line
+        let snap = locale.text(if self.snap_to_grid { "ui.on" } else { "ui.off" });
+        raylib.draw_ui_text(
+            &format!(
+                "{}{}   ({:.0}, {:.0})   {}",
+                self.level_name,
+                if self.has_unsaved_changes { " *" } else { "" },
+                self.mouse_level_position.x,
+                self.mouse_level_position.y,
+                locale.text_with("editor.snap", &[("state", &snap)])
+            ),
+            layout.at(Anchor::TopLeft, Vector2::new(20.0, 42.0)),
+            20,
+            Color::GRAY,
+        );
+        if let Some((message_key, _)) = self.status_message {
+            raylib.draw_rgb_split_text(
+                layout.at(Anchor::Top, Vector2::new(0.0, 42.0)),
+                locale.text(message_key),
+                20,
+                true,
+                Color::WHITE,
+            );
+        }
+
+        // Controls
+        raylib.draw_ui_text(
+            locale.text("editor.controls"),
+            layout.at(Anchor::BottomLeft, Vector2::new(20.0, -30.0)),
+            15,
+            Color::GRAY,
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
 use chrono::{DateTime, Utc};
 use dirty_fsm::{Action, ActionFlag};
@@ -8,12 +11,16 @@ use raylib::prelude::*;
 
 use crate::{
     context::{ControlFlag, GameContext},
+    locale::Locale,
+    scenes::ingame_scene::level::unlock::{level_availability, LevelAvailability, UnlockRule},
     utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
+        widgets::UiState,
     },
     GameConfig,
 };
@@ -21,12 +28,28 @@ use crate::{
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, info, trace};
 
+/// A campaign's heading and levels, as shown on the level select screen
+#[derive(Debug)]
+struct CampaignListing {
+    name: String,
+    beaten_levels: usize,
+    total_levels: usize,
+    /// Index, position in the campaign, and availability of every level that is not hidden
+    levels: Vec<(usize, usize, LevelAvailability)>,
+}
+
 #[derive(Debug)]
 pub struct LevelSelectScreen {
     is_btm_pressed: bool,
     selected_level: Option<usize>,
-    visible_levels: usize,
+    edit_level: Option<usize>,
+    /// Every campaign with at least one level to show
+    campaigns: Vec<CampaignListing>,
+    /// The last few letters typed, used to enter secret codes
+    typed_letters: String,
+    ui: UiState,
     counter: i32,
+    locale: Rc<Locale>,
 }
 
 impl LevelSelectScreen {
@@ -35,8 +58,12 @@ impl LevelSelectScreen {
         Self {
             is_btm_pressed: false,
             selected_level: None,
-            visible_levels: 0,
+            edit_level: None,
+            campaigns: Vec::new(),
+            typed_letters: String::new(),
+            ui: UiState::default(),
             counter: 0,
+            locale: Rc::default(),
         }
     }
 }
@@ -50,9 +77,15 @@ impl Action<Scenes, ScreenError, GameContext> for LevelSelectScreen {
     fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
         debug!("Running LevelSelectScreen for the first time");
 
+        // Menus never need level textures, so free up VRAM
+        context
+            .flag_send
+            .send(Some(ControlFlag::UnloadLevels))
+            .unwrap();
+
         if let Err(e) = context.discord_rpc_send.send(Some(
             ActivityBuilder::default()
-                .details("learning how to play")
+                .details(context.locale.text("discord.level_select"))
                 .assets(
                     Assets::default().large("game-logo-small", Some(context.config.name.clone())),
                 ),
@@ -60,9 +93,7 @@ impl Action<Scenes, ScreenError, GameContext> for LevelSelectScreen {
             error!("Failed to update discord: {}", e);
         }
 
-        // Calculate the number of levels to render
-        self.visible_levels =
-            (context.player_progress.level_best_times.len() + 1).min(context.total_levels);
+        self.typed_letters.clear();
 
         Ok(())
     }
@@ -73,17 +104,63 @@ impl Action<Scenes, ScreenError, GameContext> for LevelSelectScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on LevelSelectScreen");
+
+        // Typing a secret level's code unlocks it
+        while let Some(key) = context.renderer.borrow_mut().get_key_pressed_number() {
+            if let Some(letter) = std::char::from_u32(key).filter(char::is_ascii_alphabetic) {
+                self.typed_letters.push(letter.to_ascii_uppercase());
+            }
+        }
+        for level in &context.levels {
+            if let UnlockRule::Secret(code) = &level.unlock {
+                if !context.player_progress.unlocked_secrets.contains(code)
+                    && self.typed_letters.ends_with(code.as_str())
+                {
+                    context
+                        .flag_send
+                        .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                        .unwrap();
+                    context
+                        .flag_send
+                        .send(Some(ControlFlag::UnlockSecret(code.clone())))
+                        .unwrap();
+                    self.typed_letters.clear();
+                }
+            }
+        }
+
+        self.campaigns = context
+            .campaigns
+            .iter()
+            .map(|campaign| CampaignListing {
+                name: campaign.name.clone(),
+                beaten_levels: campaign.beaten_levels(&context.levels, &context.player_progress),
+                total_levels: campaign.levels.len(),
+                levels: campaign
+                    .levels
+                    .iter()
+                    .enumerate()
+                    .map(|(position, level)| {
+                        (
+                            *level,
+                            position,
+                            level_availability(&context.levels, *level, &context.player_progress),
+                        )
+                    })
+                    .filter(|(_, _, availability)| *availability != LevelAvailability::Hidden)
+                    .collect(),
+            })
+            .filter(|listing| !listing.levels.is_empty())
+            .collect();
+
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
 
         self.counter += 1;
 
         if let Some(level) = self.selected_level {
-            // Play the sound
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
-
             // Switch the level
             context
                 .flag_send
@@ -99,11 +176,14 @@ impl Action<Scenes, ScreenError, GameContext> for LevelSelectScreen {
 
             // Enter the game
             Ok(ActionFlag::SwitchState(Scenes::InGameScene))
-        } else if self.is_btm_pressed {
+        } else if let Some(level) = self.edit_level {
+            // The editor needs the level's textures too
             context
                 .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .send(Some(ControlFlag::BeginLevel(level)))
                 .unwrap();
+            Ok(ActionFlag::SwitchState(Scenes::LevelEditor))
+        } else if self.is_btm_pressed {
             Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
         } else {
             Ok(ActionFlag::Continue)
@@ -113,7 +193,9 @@ impl Action<Scenes, ScreenError, GameContext> for LevelSelectScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished LevelSelectScreen");
         self.selected_level = None;
+        self.edit_level = None;
         self.is_btm_pressed = false;
+        self.ui.reset();
         self.counter = 0;
         Ok(())
     }
@@ -126,6 +208,7 @@ impl ScreenSpaceRender for LevelSelectScreen {
         config: &GameConfig,
     ) {
         let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
 
         // Render the background
         raylib.clear_background(Color::BLACK);
@@ -137,73 +220,74 @@ impl ScreenSpaceRender for LevelSelectScreen {
             config.colors.white,
         );
 
-        let screen_size = raylib.get_screen_size();
-
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        let locale = self.locale.clone();
+        let mut ui = self.ui.begin(raylib);
 
         //Render the title
-        let timer: i32 = get_random_value(50, 400);
-            if self.counter > timer {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(40.0, 80.0),
-                    "[Level Select]",
-                    70,
-                    true,
-                    Color::WHITE,
-                );
-            }
-            if self.counter > timer + 20 {
-                self.counter = 0;
-            }
-            else {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(40.0, 80.0),
-                    "[Level Select]",
-                    70,
-                    false,
-                    Color::WHITE,
-                );
-            }
+        ui.title(
+            layout.at(Anchor::TopLeft, Vector2::new(40.0, 80.0)),
+            &format!("[{}]", locale.text("level_select.title")),
+            &mut self.counter,
+        );
 
-        // Render the levels
-        for level in 0..self.visible_levels {
-            let hovering_button =
-                Rectangle::new(100.0, 300.0 + (25.0 * level as f32), 180.0, 25.0 ).check_collision_point_rec(mouse_position);
-            raylib.draw_rgb_split_text(
-                Vector2::new(100.0, 300.0+ (25.0 * level as f32)),
-                &format!("LEVEL {}", level),
-                25,
-                hovering_button,
-                Color::WHITE,
-            );
-            if hovering_button {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(70.0, 300.0+ (25.0 * level as f32)),
-                    ">>",
-                    25,
-                    hovering_button,
-                    Color::WHITE,
-                );
+        // Render the levels, grouped by campaign. Hidden levels do not get a row. Rows are in design pixels
+        let mut y = 300.0;
+        'campaigns: for campaign in &self.campaigns {
+            let progress = if campaign.beaten_levels == campaign.total_levels {
+                locale.text("level_select.complete").to_string()
+            } else {
+                format!("{}/{}", campaign.beaten_levels, campaign.total_levels)
             };
-            if mouse_pressed && hovering_button {
-                self.selected_level = Some(level);
-                break;
+            ui.raylib.draw_ui_text(
+                &format!("{}  {}", campaign.name, progress),
+                layout.at(Anchor::TopLeft, Vector2::new(100.0, y)),
+                20,
+                Color::DARKGRAY,
+            );
+            y += 30.0;
+
+            for (level, position, availability) in &campaign.levels {
+                let row_position = layout.at(Anchor::TopLeft, Vector2::new(100.0, y));
+                #[cfg(debug_assertions)]
+                let edit_position = layout.at(Anchor::TopLeft, Vector2::new(400.0, y));
+                y += 25.0;
+                if *availability == LevelAvailability::Locked {
+                    ui.text_color = Color::DARKGRAY;
+                    ui.label(
+                        row_position,
+                        &locale.text_with("level_select.locked", &[("level", position)]),
+                        25,
+                    );
+                    ui.text_color = Color::WHITE;
+                } else {
+                    let label = locale.text_with("level_select.level", &[("level", position)]);
+                    if ui.button(row_position, &label, 25) {
+                        self.selected_level = Some(*level);
+                        break 'campaigns;
+                    }
+                }
+
+                // Only in debug mode, allow opening the level in the editor
+                #[cfg(debug_assertions)]
+                {
+                    ui.text_color = Color::GRAY;
+                    let is_edit_pressed =
+                        ui.button(edit_position, locale.text("level_select.edit"), 25);
+                    ui.text_color = Color::WHITE;
+                    if is_edit_pressed {
+                        self.edit_level = Some(*level);
+                        break 'campaigns;
+                    }
+                }
             }
+            y += 15.0;
         }
 
         //Back to Menu
-        let hovering_back_button = Rectangle::new(35.0, screen_size.y as f32 - 80.0, 200.0, 40.0)
-            .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(25.0, screen_size.y - 50.0),
-            "BACK TO MENU",
+        self.is_btm_pressed = ui.button(
+            layout.at(Anchor::BottomLeft, Vector2::new(50.0, -50.0)),
+            locale.text("ui.back_to_menu"),
             25,
-            hovering_back_button,
-            Color::WHITE,
-        );
-        self.is_btm_pressed = hovering_back_button && mouse_pressed;
+        ) || ui.back_pressed();
     }
 }
@@ -0,0 +1,149 @@
+use std::rc::Rc;
+
+use dirty_fsm::{Action, ActionFlag};
+use discord_sdk::activity::{ActivityBuilder, Assets};
+use raylib::prelude::*;
+
+use crate::{
+    context::GameContext,
+    locale::Locale,
+    utilities::{asset_loader::LoadingProgress, layout::Anchor, render_layer::ScreenSpaceRender},
+    GameConfig,
+};
+
+use super::{Scenes, ScreenError};
+use tracing::{debug, error, trace};
+
+#[derive(Debug)]
+pub struct LoadingScreen {
+    progress: LoadingProgress,
+    counter: i32,
+    locale: Rc<Locale>,
+}
+
+impl LoadingScreen {
+    /// Construct a new `LoadingScreen`
+    pub fn new() -> Self {
+        Self {
+            progress: LoadingProgress::default(),
+            counter: 0,
+            locale: Rc::default(),
+        }
+    }
+}
+
+impl Action<Scenes, ScreenError, GameContext> for LoadingScreen {
+    fn on_register(&mut self) -> Result<(), ScreenError> {
+        debug!("Registered");
+        Ok(())
+    }
+
+    fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
+        debug!("Running LoadingScreen for the first time");
+
+        if let Err(e) = context.discord_rpc_send.send(Some(
+            ActivityBuilder::default()
+                .details(context.locale.text("discord.loading"))
+                .assets(
+                    Assets::default().large("game-logo-small", Some(context.config.name.clone())),
+                ),
+        )) {
+            error!("Failed to update discord: {}", e);
+        }
+
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        _delta: &chrono::Duration,
+        context: &GameContext,
+    ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
+        trace!("execute() called on LoadingScreen");
+
+        // The main loop registers the rest of the scenes once everything is loaded, or gives up if the loader died
+        self.progress = context.loading_progress.clone();
+        self.locale = context.locale.clone();
+        self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+
+        self.counter += 1;
+
+        if self.progress.failed {
+            Ok(ActionFlag::SwitchState(Scenes::FsmErrorScreen))
+        } else if self.progress.is_done() {
+            Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
+        } else {
+            Ok(ActionFlag::Continue)
+        }
+    }
+
+    fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
+        debug!("Finished LoadingScreen");
+        self.counter = 0;
+        Ok(())
+    }
+}
+
+impl ScreenSpaceRender for LoadingScreen {
+    fn render_screen_space(
+        &mut self,
+        raylib: &mut crate::utilities::non_ref_raylib::HackedRaylibHandle,
+        config: &GameConfig,
+    ) {
+        let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
+
+        // Render the background
+        raylib.clear_background(Color::BLACK);
+        raylib.draw_rectangle_lines(
+            0,
+            0,
+            screen_size.x as i32,
+            screen_size.y as i32,
+            config.colors.white,
+        );
+
+        // Render the title
+        raylib.draw_rgb_split_text(
+            layout.at(Anchor::Left, Vector2::new(80.0, -100.0)),
+            self.locale.text("loading.title"),
+            50,
+            (self.counter / 20) % 2 == 0,
+            Color::WHITE,
+        );
+
+        // Render the progress bar
+        let bar = layout.rect(
+            Anchor::Left,
+            Vector2::new(80.0, -10.0),
+            Vector2::new(0.0, 20.0),
+        );
+        let bar = Rectangle {
+            width: screen_size.x - bar.x * 2.0,
+            ..bar
+        };
+        raylib.draw_rectangle_lines_ex(bar, 1, config.colors.white);
+        let inset = layout.scaled(4.0).round();
+        raylib.draw_rectangle_rec(
+            Rectangle::new(
+                bar.x + inset,
+                bar.y + inset,
+                ((bar.width - inset * 2.0) * self.progress.fraction()).round(),
+                bar.height - inset * 2.0,
+            ),
+            config.colors.red,
+        );
+
+        // Render what is currently loading
+        raylib.draw_rgb_split_text(
+            layout.at(Anchor::Left, Vector2::new(80.0, 20.0)),
+            &format!(
+                "[{}/{}] {}",
+                self.progress.completed, self.progress.total, self.progress.current_item
+            ),
+            20,
+            false,
+            Color::WHITE,
+        );
+    }
+}
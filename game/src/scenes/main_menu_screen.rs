@@ -1,6 +1,11 @@
-use std::{collections::hash_map::Iter, iter::Enumerate, ops::{Div, Sub}};
+use std::{
+    collections::hash_map::Iter,
+    iter::Enumerate,
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, Local, Utc};
 use dirty_fsm::{Action, ActionFlag};
 use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
@@ -8,12 +13,16 @@ use raylib::prelude::*;
 
 use crate::{
     context::{ControlFlag, GameContext},
+    locale::Locale,
+    scenes::{confirm_screen::Dialog, ingame_scene::timer::format_time},
     utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
+        widgets::UiState,
     },
     GameConfig,
 };
@@ -24,11 +33,18 @@ use tracing::{debug, error, info, trace};
 #[derive(Debug)]
 pub struct MainMenuScreen {
     is_start_pressed: bool,   //Is start button pressed
+    is_endless_pressed: bool, //Is endless button pressed
+    is_daily_pressed: bool,   //Is daily challenge button pressed
     is_htp_pressed: bool,     //Is how to play button pressed
     is_options_pressed: bool, //Is options button pressed
     is_quit_pressed: bool,    //Is quit button pressed
-    level_times: Option<Vec<(usize, (usize, i64))>>,
+    /// Each level's best time, and whether it was set on an older version of the level
+    level_times: Option<Vec<(usize, (usize, Duration, bool))>>,
+    endless_best_distance: u64,
+    daily_streak: u32,
+    ui: UiState,
     counter: i32,
+    locale: Rc<Locale>,
 }
 
 impl MainMenuScreen {
@@ -36,11 +52,17 @@ impl MainMenuScreen {
     pub fn new() -> Self {
         Self {
             is_start_pressed: false,
+            is_endless_pressed: false,
+            is_daily_pressed: false,
             is_htp_pressed: false,
             is_options_pressed: false,
             is_quit_pressed: false,
             level_times: None,
+            endless_best_distance: 0,
+            daily_streak: 0,
+            ui: UiState::default(),
             counter: 0,
+            locale: Rc::default(),
         }
     }
 }
@@ -54,11 +76,19 @@ impl Action<Scenes, ScreenError, GameContext> for MainMenuScreen {
     fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
         debug!("Running MainMenuScreen for the first time");
 
+        // Menus never need level textures, so free up VRAM
+        context
+            .flag_send
+            .send(Some(ControlFlag::UnloadLevels))
+            .unwrap();
+
         // Update discord
         if let Err(e) = context.discord_rpc_send.send(Some(
-            ActivityBuilder::default().details("main menu").assets(
-                Assets::default().large("game-logo-small", Some(context.config.name.clone())),
-            ),
+            ActivityBuilder::default()
+                .details(context.locale.text("discord.main_menu"))
+                .assets(
+                    Assets::default().large("game-logo-small", Some(context.config.name.clone())),
+                ),
         )) {
             error!("Failed to update discord: {}", e);
         }
@@ -72,37 +102,61 @@ impl Action<Scenes, ScreenError, GameContext> for MainMenuScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on MainMenuScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
 
         self.counter += 1;
 
-        self.level_times = Some(context.player_progress.level_best_times.iter().map(|x| (*x.0, *x.1)).collect::<Vec<(_,_)>>().iter().map(|x| *x).enumerate().collect());
+        self.level_times = Some(
+            context
+                .levels
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, level)| {
+                    context
+                        .player_progress
+                        .get_level_record(&level.name)
+                        .map(|record| (idx, record.time(), !record.is_current(level.content_hash)))
+                })
+                .enumerate()
+                .collect(),
+        );
+        self.endless_best_distance = context.player_progress.endless_best_distance;
+        self.daily_streak = context
+            .player_progress
+            .daily_streak(Local::today().naive_local());
 
         if self.is_start_pressed {
+            Ok(ActionFlag::SwitchState(Scenes::LevelSelectScreen))
+        } else if self.is_endless_pressed {
+            // Every endless run gets a new seed
             context
                 .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .send(Some(ControlFlag::BeginEndless(
+                    Utc::now().timestamp_millis() as u64,
+                )))
                 .unwrap();
-            Ok(ActionFlag::SwitchState(Scenes::LevelSelectScreen))
-        } else if self.is_htp_pressed {
+            Ok(ActionFlag::SwitchState(Scenes::InGameScene))
+        } else if self.is_daily_pressed {
             context
                 .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .send(Some(ControlFlag::BeginDailyChallenge(
+                    Local::today().naive_local(),
+                )))
                 .unwrap();
+            Ok(ActionFlag::SwitchState(Scenes::InGameScene))
+        } else if self.is_htp_pressed {
             Ok(ActionFlag::SwitchState(Scenes::HowToPlayScreen))
         } else if self.is_options_pressed {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
             Ok(ActionFlag::SwitchState(Scenes::OptionsScreen))
         } else if self.is_quit_pressed {
             context
                 .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .send(Some(ControlFlag::OpenDialog(Dialog::QuitGame)))
                 .unwrap();
-            context.flag_send.send(Some(ControlFlag::Quit)).unwrap();
-            Ok(ActionFlag::Continue)
+            Ok(ActionFlag::SwitchState(Scenes::ConfirmScreen))
         } else {
             Ok(ActionFlag::Continue)
         }
@@ -111,9 +165,12 @@ impl Action<Scenes, ScreenError, GameContext> for MainMenuScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished MainMenuScreen");
         self.is_start_pressed = false;
+        self.is_endless_pressed = false;
+        self.is_daily_pressed = false;
         self.is_htp_pressed = false;
         self.is_options_pressed = false;
         self.is_quit_pressed = false;
+        self.ui.reset();
         self.counter = 0;
         Ok(())
     }
@@ -137,201 +194,138 @@ impl ScreenSpaceRender for MainMenuScreen {
             config.colors.white,
         );
 
-
-
-        // Calculate the logo position
-        let screen_size = raylib.get_screen_size();
+        // Everything is placed against the edges of the screen, so it stays put at any window size
+        let layout = raylib.layout();
 
         //Mouse Position
         let mouse_position: Vector2 = raylib.get_mouse_position();
 
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
-
         // Only in debug mode, render a debug message
         #[cfg(debug_assertions)]
         {
-            raylib.draw_text(
+            raylib.draw_ui_text(
                 "Game in DEBUG MODE. Do not redistribute!",
-                10,
-                screen_size.y as i32 - 35,
+                layout.at(Anchor::BottomLeft, Vector2::new(10.0, -45.0)),
                 15,
                 Color::WHITE,
             );
         }
 
         // Displays mouse position
-        raylib.draw_text(
+        raylib.draw_ui_text(
             &format!("[{}, {}]", mouse_position.x, mouse_position.y),
-            screen_size.x as i32 - 130,
-            screen_size.y as i32 - 30,
+            layout.at(Anchor::BottomRight, Vector2::new(-130.0, -30.0)),
             25,
             Color::DARKGRAY,
         );
 
         // Render the game version info
-        raylib.draw_text(
+        raylib.draw_ui_text(
             &format!(
                 "Version: {} Commit: {}",
                 get_version_string(),
                 env!("VERGEN_GIT_SHA_SHORT")
             ),
-            10,
-            screen_size.y as i32 - 20,
+            layout.at(Anchor::BottomLeft, Vector2::new(10.0, -20.0)),
             15,
             Color::WHITE,
         );
 
-        // Render the title
-        let timer: i32 = get_random_value(50, 400);
-        if self.counter > timer {
-            raylib.draw_rgb_split_text(
-                Vector2::new(37.0, 80.0),
-                &format!("[{}]", config.name),
-                70,
-                true,
-                Color::WHITE,
-            );
-
-            if self.counter > timer + 20 {
-                self.counter = 0;
-            }
+        let locale = self.locale.clone();
+        let mut ui = self.ui.begin(raylib);
 
-        }else{
-            raylib.draw_rgb_split_text(
-                Vector2::new(37.0, 80.0),
-                &format!("[{}]", config.name),
-                70,
-                false,
-                Color::WHITE,
-            );
-        }
-
-        // Start Game
-        let hovering_start_game =
-            Rectangle::new(80.0, 300.0, 170.0, 20.0).check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, 300.0),
-            "START GAME",
-            25,
-            hovering_start_game,
-            Color::WHITE,
-        );
-
-        if hovering_start_game {
-            raylib.draw_rgb_split_text(
-                Vector2::new(50.0, 300.0),
-                ">>",
-                25,
-                hovering_start_game,
-                Color::WHITE,
-            );
-        };
-
-        self.is_start_pressed = mouse_pressed && hovering_start_game;
-
-        // How to Play
-        let hovering_htp =
-            Rectangle::new(80.0, 350.0, 170.0, 20.0).check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, 350.0),
-            "HOW TO PLAY",
-            25,
-            hovering_htp,
-            Color::WHITE,
-        );
-        if hovering_htp {
-            raylib.draw_rgb_split_text(
-                Vector2::new(50.0, 350.0),
-                ">>",
-                25,
-                hovering_htp,
-                Color::WHITE,
-            );
-        };
-        self.is_htp_pressed = mouse_pressed && hovering_htp;
-
-        // OPTIONS
-        let hovering_options =
-            Rectangle::new(80.0, 400.0, 135.0, 20.0).check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, 400.0),
-            "OPTIONS",
-            25,
-            hovering_options,
-            Color::WHITE,
+        // Render the title
+        ui.title(
+            layout.at(Anchor::TopLeft, Vector2::new(37.0, 80.0)),
+            &format!("[{}]", config.name),
+            &mut self.counter,
         );
-        if hovering_options {
-            raylib.draw_rgb_split_text(
-                Vector2::new(50.0, 400.0),
-                ">>",
-                25,
-                hovering_options,
-                Color::WHITE,
-            );
-        };
-        self.is_options_pressed = mouse_pressed && hovering_options;
-
 
-
-        // CREDITS
-        let hovering_credits =
-            Rectangle::new(80.0, 445.0, 135.0, 20.0).check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, 450.0),
-            "CREDITS",
+        // Render the menu buttons
+        let pressed = ui.list(
+            layout.at(Anchor::TopLeft, Vector2::new(80.0, 300.0)),
+            &[
+                locale.text("main_menu.start_game"),
+                locale.text("main_menu.endless"),
+                locale.text("main_menu.daily"),
+                locale.text("main_menu.how_to_play"),
+                locale.text("main_menu.options"),
+                locale.text("main_menu.credits"),
+                locale.text("main_menu.quit"),
+            ],
             25,
-            hovering_credits,
-            Color::WHITE,
+            50.0,
         );
-        if hovering_credits {
-            raylib.draw_rgb_split_text(Vector2::new(50.0, 450.0), ">>", 25, true, Color::WHITE);
-        };
-        if hovering_credits && mouse_pressed {
+        self.is_start_pressed = pressed == Some(0);
+        self.is_endless_pressed = pressed == Some(1);
+        self.is_daily_pressed = pressed == Some(2);
+        self.is_htp_pressed = pressed == Some(3);
+        self.is_options_pressed = pressed == Some(4);
+        if pressed == Some(5) {
             let _ = webbrowser::open("https://github.com/Ewpratten/ludum-dare-49#the-team");
         }
-
-        // QUIT
-        let hovering_quit =
-            Rectangle::new(80.0, 495.0, 65.0, 20.0).check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, 500.0),
-            "QUIT",
-            25,
-            hovering_quit,
-            Color::WHITE,
-        );
-        if hovering_quit {
-            raylib.draw_rgb_split_text(
-                Vector2::new(50.0, 500.0),
-                ">>",
-                25,
-                hovering_quit,
-                Color::WHITE,
+        self.is_quit_pressed = pressed == Some(6);
+
+        if self.daily_streak > 0 {
+            raylib.draw_ui_text(
+                &locale.text_with("main_menu.daily_streak", &[("days", &self.daily_streak)]),
+                layout.at(Anchor::TopLeft, Vector2::new(200.0, 403.0)),
+                20,
+                Color::DARKGRAY,
             );
-        };
+        }
 
         // Best Times
-        raylib.draw_text(
-            "BEST TIMES",
-            screen_size.x as i32 - 200,
-            40,
+        raylib.draw_ui_text(
+            locale.text("main_menu.best_times"),
+            layout.at(Anchor::TopRight, Vector2::new(-240.0, 40.0)),
             25,
             Color::DARKGRAY,
         );
 
-        if let Some(times) = &self.level_times{
-            for (i, (level, time)) in times.iter() {
-                let time = Duration::seconds(*time);
-                raylib.draw_text(
-                    &format!("Lvl {}         {}:{}", level, time.num_minutes(), time.num_seconds() % 60),
-                    screen_size.x as i32 - 200,
-                    100 + (25 * (*i as i32)),
+        if let Some(times) = &self.level_times {
+            for (i, (level, time, is_stale)) in times.iter() {
+                let text = locale.text_with(
+                    "main_menu.level_time",
+                    &[("level", level), ("time", &format_time(time))],
+                );
+                raylib.draw_ui_text(
+                    &format!("{}{}", text, if *is_stale { "*" } else { "" }),
+                    layout.at(
+                        Anchor::TopRight,
+                        Vector2::new(-240.0, 100.0 + 25.0 * *i as f32),
+                    ),
                     20,
                     Color::DARKGRAY,
                 );
             }
+
+            // Explain any times that no longer match their level
+            if times.iter().any(|(_, (_, _, is_stale))| *is_stale) {
+                raylib.draw_ui_text(
+                    locale.text("main_menu.stale_times"),
+                    layout.at(Anchor::TopRight, Vector2::new(-240.0, 70.0)),
+                    15,
+                    Color::DARKGRAY,
+                );
+            }
         }
-        self.is_quit_pressed = mouse_pressed && hovering_quit;
 
+        // Best endless distance, below the level times
+        if self.endless_best_distance > 0 {
+            let level_count = self.level_times.as_ref().map_or(0, Vec::len) as i32;
+            raylib.draw_ui_text(
+                &locale.text_with(
+                    "main_menu.endless_best",
+                    &[("distance", &self.endless_best_distance)],
+                ),
+                layout.at(
+                    Anchor::TopRight,
+                    Vector2::new(-240.0, 100.0 + 25.0 * level_count as f32),
+                ),
+                20,
+                Color::DARKGRAY,
+            );
+        }
     }
 }
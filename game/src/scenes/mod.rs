@@ -1,7 +1,8 @@
-use self::{death_screen::DeathScreen, fsm_error_screen::FsmErrorScreen, how_to_play_screen::HowToPlayScreen, ingame_scene::{InGameScreen, level::{Level, loader::load_all_levels}}, level_select_screen::LevelSelectScreen, main_menu_screen::MainMenuScreen, next_level_screen::NextLevelScreen, options_screen::OptionsScreen, pause_screen::PauseScreen, win_screen::WinScreen, cheater_screen::CheaterScreen};
-use crate::{context::GameContext, utilities::{datastore::{ResourceLoadError, load_music_from_internal_data, load_sound_from_internal_data, load_texture_from_internal_data}, non_ref_raylib::HackedRaylibHandle}};
-use dirty_fsm::StateMachine;
-use raylib::{texture::Texture2D, RaylibThread};
+use self::{confirm_screen::ConfirmScreen, death_screen::DeathScreen, fsm_error_screen::FsmErrorScreen, how_to_play_screen::HowToPlayScreen, ingame_scene::InGameScreen, level_select_screen::LevelSelectScreen, main_menu_screen::MainMenuScreen, next_level_screen::NextLevelScreen, options_screen::OptionsScreen, pause_screen::PauseScreen, win_screen::WinScreen, cheater_screen::CheaterScreen, loading_screen::LoadingScreen, level_editor_screen::LevelEditorScreen};
+use self::transition::TrackedScene;
+use crate::{context::GameContext, utilities::datastore::ResourceLoadError};
+use dirty_fsm::{Action, StateMachine};
+use raylib::texture::Texture2D;
 
 pub mod fsm_error_screen;
 pub mod ingame_scene;
@@ -14,9 +15,14 @@ pub mod win_screen;
 pub mod next_level_screen;
 pub mod level_select_screen;
 pub mod cheater_screen;
+pub mod loading_screen;
+pub mod level_editor_screen;
+pub mod transition;
+pub mod scene_stack;
+pub mod confirm_screen;
 
 /// Defines all scenes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash, Deserialize)]
 pub enum Scenes {
     #[default]
     FsmErrorScreen,
@@ -30,6 +36,9 @@ pub enum Scenes {
     NextLevelScreen,
     LevelSelectScreen,
     CheaterScreen,
+    LoadingScreen,
+    LevelEditor,
+    ConfirmScreen,
 }
 
 /// Contains any possible errors thrown while rendering
@@ -39,38 +48,52 @@ pub enum ScreenError {
     ResourceLoad(#[from] ResourceLoadError),
 }
 
-/// Build the state machine for all scenes
-pub fn build_screen_state_machine(
-    raylib_handle: &mut HackedRaylibHandle,
-    thread: &RaylibThread,
-    levels: Vec<Level>
-) -> Result<
+/// Register a scene, wrapped so that switching away from it plays a transition
+fn add_scene<A>(
+    machine: &mut StateMachine<Scenes, ScreenError, GameContext>,
+    id: Scenes,
+    scene: A,
+) -> Result<(), ScreenError>
+where
+    A: Action<Scenes, ScreenError, GameContext> + std::fmt::Debug + 'static,
+{
+    machine.add_action(id, TrackedScene::new(id, scene))?;
+    Ok(())
+}
+
+/// Build the state machine with only the scenes that can run before any assets are loaded
+pub fn build_screen_state_machine() -> Result<
     // StateMachine<Scenes, ScreenError, RefCell<(NonRefDrawHandle, Rc<RefCell<GameContext>>)>>,
     StateMachine<Scenes, ScreenError, GameContext>,
     ScreenError,
 > {
-    // Load the various textures needed by the states
-    let player_sprite_sheet =
-        load_texture_from_internal_data(raylib_handle, thread, "character/player_run.png").unwrap();
-    let world_background =
-        load_texture_from_internal_data(raylib_handle, thread, "default-texture.png").unwrap();
-
-    // Set up the state machine
     let mut machine = StateMachine::new();
-    machine.add_action(Scenes::FsmErrorScreen, FsmErrorScreen::new())?;
-    machine.add_action(Scenes::MainMenuScreen, MainMenuScreen::new())?;
-    machine.add_action(Scenes::HowToPlayScreen, HowToPlayScreen::new())?;
-    machine.add_action(Scenes::OptionsScreen, OptionsScreen::new())?;
-    machine.add_action(Scenes::PauseScreen, PauseScreen::new())?;
-    machine.add_action(
-        Scenes::InGameScene,
-        InGameScreen::new(player_sprite_sheet, world_background, levels),
-    )?;
-    machine.add_action(Scenes::DeathScreen, DeathScreen::new())?;
-    machine.add_action(Scenes::WinScreen, WinScreen::new())?;
-    machine.add_action(Scenes::NextLevelScreen, NextLevelScreen::new())?;
-    machine.add_action(Scenes::LevelSelectScreen, LevelSelectScreen::new())?;
-    machine.add_action(Scenes::CheaterScreen, CheaterScreen::new())?;
+    add_scene(&mut machine, Scenes::FsmErrorScreen, FsmErrorScreen::new())?;
+    add_scene(&mut machine, Scenes::LoadingScreen, LoadingScreen::new())?;
     Ok(machine)
+}
 
+/// Register all the scenes that depend on assets from the loader
+pub fn register_game_scenes(
+    machine: &mut StateMachine<Scenes, ScreenError, GameContext>,
+    player_sprite_sheet: Texture2D,
+    world_background: Texture2D,
+) -> Result<(), ScreenError> {
+    add_scene(machine, Scenes::MainMenuScreen, MainMenuScreen::new())?;
+    add_scene(machine, Scenes::HowToPlayScreen, HowToPlayScreen::new())?;
+    add_scene(machine, Scenes::OptionsScreen, OptionsScreen::new())?;
+    add_scene(machine, Scenes::PauseScreen, PauseScreen::new())?;
+    add_scene(
+        machine,
+        Scenes::InGameScene,
+        InGameScreen::new(player_sprite_sheet, world_background),
+    )?;
+    add_scene(machine, Scenes::DeathScreen, DeathScreen::new())?;
+    add_scene(machine, Scenes::WinScreen, WinScreen::new())?;
+    add_scene(machine, Scenes::NextLevelScreen, NextLevelScreen::new())?;
+    add_scene(machine, Scenes::LevelSelectScreen, LevelSelectScreen::new())?;
+    add_scene(machine, Scenes::CheaterScreen, CheaterScreen::new())?;
+    add_scene(machine, Scenes::LevelEditor, LevelEditorScreen::new())?;
+    add_scene(machine, Scenes::ConfirmScreen, ConfirmScreen::new())?;
+    Ok(())
 }
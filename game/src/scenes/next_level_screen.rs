@@ -1,6 +1,9 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::Duration;
 use dirty_fsm::{Action, ActionFlag};
 use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
@@ -8,12 +11,16 @@ use raylib::prelude::*;
 
 use crate::{
     context::{ControlFlag, GameContext},
+    locale::Locale,
+    scenes::ingame_scene::{level::unlock::next_level, timer::format_time},
     utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
+        widgets::UiState,
     },
     GameConfig,
 };
@@ -25,9 +32,10 @@ use tracing::{debug, error, info, trace};
 pub struct NextLevelScreen {
     is_next_pressed: bool,
     is_level_select_pressed: bool,
-    screen_load_time: DateTime<Utc>,
     attempt_time: String,
     best_time: String,
+    ui: UiState,
+    locale: Rc<Locale>,
 }
 
 impl NextLevelScreen {
@@ -36,9 +44,10 @@ impl NextLevelScreen {
         Self {
             is_next_pressed: false,
             is_level_select_pressed: false,
-            screen_load_time: Utc::now(),
             attempt_time: String::new(),
             best_time: String::new(),
+            ui: UiState::default(),
+            locale: Rc::default(),
         }
     }
 }
@@ -51,12 +60,13 @@ impl Action<Scenes, ScreenError, GameContext> for NextLevelScreen {
 
     fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
         debug!("Running NextLevelScreen for the first time");
-        self.screen_load_time = Utc::now();
 
         if let Err(e) = context.discord_rpc_send.send(Some(
-            ActivityBuilder::default().details("accepting fate").assets(
-                Assets::default().large("game-logo-small", Some(context.config.name.clone())),
-            ),
+            ActivityBuilder::default()
+                .details(context.locale.text("discord.next_level"))
+                .assets(
+                    Assets::default().large("game-logo-small", Some(context.config.name.clone())),
+                ),
         )) {
             error!("Failed to update discord: {}", e);
         }
@@ -70,46 +80,39 @@ impl Action<Scenes, ScreenError, GameContext> for NextLevelScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on NextLevelScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
 
-        let attempt_elapsed = self.screen_load_time - context.level_start_time;
-        self.attempt_time = format!(
-            "{:02}:{:02}",
-            attempt_elapsed.num_minutes(),
-            attempt_elapsed.num_seconds() % 60
-        );
+        let attempt_elapsed = context.level_timer.elapsed();
+        self.attempt_time = format_time(&attempt_elapsed);
         let best_time = context
-            .player_progress
-            .get_level_best_time(context.current_level)
+            .levels
+            .get(context.current_level)
+            .and_then(|level| context.player_progress.get_level_best_time(&level.name))
             .unwrap_or(attempt_elapsed);
-        self.best_time = format!(
-            "{:02}:{:02}",
-            best_time.num_minutes(),
-            best_time.num_seconds() % 60
-        );
+        self.best_time = format_time(&best_time);
 
         if self.is_next_pressed {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
-
-            // Start the next level
-            let current_level = context.current_level;
-            context
-                .flag_send
-                .send(Some(ControlFlag::BeginLevel(current_level + 1)))
-                .unwrap();
-
-            Ok(ActionFlag::SwitchState(Scenes::InGameScene))
-        }
-        else if self.is_level_select_pressed {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
+            // Start the next level. Locked and hidden levels are skipped over
+            match next_level(
+                &context.levels,
+                context.current_level,
+                &context.player_progress,
+            ) {
+                Some(level) => {
+                    context
+                        .flag_send
+                        .send(Some(ControlFlag::BeginLevel(level)))
+                        .unwrap();
+                    Ok(ActionFlag::SwitchState(Scenes::InGameScene))
+                }
+                None => Ok(ActionFlag::SwitchState(Scenes::LevelSelectScreen)),
+            }
+        } else if self.is_level_select_pressed {
             Ok(ActionFlag::SwitchState(Scenes::LevelSelectScreen))
-        }else {
+        } else {
             Ok(ActionFlag::Continue)
         }
     }
@@ -118,6 +121,7 @@ impl Action<Scenes, ScreenError, GameContext> for NextLevelScreen {
         debug!("Finished NextLevelScreen");
         self.is_next_pressed = false;
         self.is_level_select_pressed = false;
+        self.ui.reset();
         Ok(())
     }
 }
@@ -129,6 +133,7 @@ impl ScreenSpaceRender for NextLevelScreen {
         config: &GameConfig,
     ) {
         let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
 
         // Render the background
         raylib.clear_background(Color::BLACK);
@@ -142,15 +147,12 @@ impl ScreenSpaceRender for NextLevelScreen {
 
         let screen_size = raylib.get_screen_size();
 
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        let locale = self.locale.clone();
 
         //Render the title
         raylib.draw_rgb_split_text(
-            Vector2::new(80.0, screen_size.y / 2.0 - 100.0),
-            "LEVEL COMPLETE",
+            layout.at(Anchor::Left, Vector2::new(80.0, -100.0)),
+            locale.text("next_level.complete"),
             50,
             true,
             Color::WHITE,
@@ -158,62 +160,34 @@ impl ScreenSpaceRender for NextLevelScreen {
 
         //Time
         raylib.draw_rgb_split_text(
-            Vector2::new(80.0, screen_size.y / 2.0 - 40.0),
-            &format!("YOUR TIME: {}", self.attempt_time),
+            layout.at(Anchor::Left, Vector2::new(80.0, -40.0)),
+            &locale.text_with("next_level.your_time", &[("time", &self.attempt_time)]),
             20,
             false,
             Color::WHITE,
         );
         raylib.draw_rgb_split_text(
-            Vector2::new(80.0, screen_size.y / 2.0 - 20.0),
-            &format!("BEST TIME: {}", self.best_time),
+            layout.at(Anchor::Left, Vector2::new(80.0, -20.0)),
+            &locale.text_with("next_level.best_time", &[("time", &self.best_time)]),
             20,
             false,
             Color::WHITE,
         );
 
+        let mut ui = self.ui.begin(raylib);
+
         //Next Level
-        let hovering_next_button =
-            Rectangle::new(80.0, screen_size.y as f32 / 2.0 + 50.0, 200.0, 40.0)
-                .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, screen_size.y / 2.0 + 50.0),
-            "Next Level",
+        self.is_next_pressed = ui.button(
+            layout.at(Anchor::Left, Vector2::new(80.0, 50.0)),
+            locale.text("next_level.next"),
             25,
-            hovering_next_button,
-            Color::WHITE,
         );
-        if hovering_next_button {
-            raylib.draw_rgb_split_text(
-                Vector2::new(50.0, screen_size.y as f32 / 2.0 + 50.0),
-                ">>",
-                25,
-                hovering_next_button,
-                Color::WHITE,
-            );
-        };
-        self.is_next_pressed = hovering_next_button && mouse_pressed;
 
-        //Next Level
-        let hovering_level_select_button =
-            Rectangle::new(80.0, screen_size.y as f32 / 2.0 + 90.0, 300.0, 20.0)
-                .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(80.0, screen_size.y / 2.0 + 100.0),
-            "Back To Level Select",
+        //Back to Level Select
+        self.is_level_select_pressed = ui.button(
+            layout.at(Anchor::Left, Vector2::new(80.0, 100.0)),
+            locale.text("next_level.back"),
             25,
-            hovering_level_select_button,
-            Color::WHITE,
-        );
-        if hovering_level_select_button {
-            raylib.draw_rgb_split_text(
-                Vector2::new(50.0, screen_size.y as f32 / 2.0 + 100.0),
-                ">>",
-                25,
-                hovering_level_select_button,
-                Color::WHITE,
-            );
-        };
-        self.is_level_select_pressed = hovering_level_select_button && mouse_pressed;
+        ) || ui.back_pressed();
     }
 }
@@ -1,26 +1,79 @@
-use std::ops::{Div, Sub};
+use std::rc::Rc;
 
-use chrono::{DateTime, Utc};
 use dirty_fsm::{Action, ActionFlag};
 use discord_sdk::activity::{ActivityBuilder, Assets};
-use pkg_version::pkg_version_major;
 use raylib::prelude::*;
 
-use crate::{GameConfig, context::{ControlFlag, GameContext}, utilities::{
-        datastore::{load_texture_from_internal_data, ResourceLoadError},
-        game_version::get_version_string,
-        math::interpolate_exp,
-        non_ref_raylib::HackedRaylibHandle,
+use crate::{
+    context::{ControlFlag, GameContext},
+    input::{InputAction, InputBinding, REBINDABLE_ACTIONS},
+    locale::{Locale, LANGUAGES},
+    settings::Settings,
+    utilities::{
+        key_names::{key_name, BINDABLE_GAMEPAD_BUTTONS},
+        layout::Anchor,
         render_layer::ScreenSpaceRender,
-    }};
+        widgets::UiState,
+    },
+    GameConfig,
+};
 
 use super::{Scenes, ScreenError};
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, trace};
+
+/// Window sizes that can be picked from the options screen
+const RESOLUTIONS: &[(i32, i32)] = &[
+    (1080, 720),
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+/// UI scales that can be picked from the options screen
+const UI_SCALES: &[f32] = &[0.75, 1.0, 1.25, 1.5];
+
+/// Frame rates that can be picked from the options screen
+const TARGET_FPS_OPTIONS: &[u32] = &[30, 60, 120, 144, 240];
+
+/// The options are split into pages so they fit on the smallest window size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionsPage {
+    General,
+    Controls,
+    Hud,
+}
+
+const OPTIONS_PAGES: &[OptionsPage] = &[
+    OptionsPage::General,
+    OptionsPage::Controls,
+    OptionsPage::Hud,
+];
+
+impl OptionsPage {
+    fn locale_key(self) -> &'static str {
+        match self {
+            Self::General => "options.page_general",
+            Self::Controls => "options.page_controls",
+            Self::Hud => "options.page_hud",
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct OptionsScreen {
-    is_btm_pressed: bool, //Is back to menu button pressed
+    is_btm_pressed: bool, //Is back button pressed
+    /// A copy of the current settings to make changes to
+    settings: Settings,
+    settings_changed: bool,
+    /// Set while waiting for a key or gamepad button to be pressed for an action
+    rebinding: Option<InputAction>,
+    page: OptionsPage,
+    ui: UiState,
     counter: i32,
+    locale: Rc<Locale>,
 }
 
 impl OptionsScreen {
@@ -28,7 +81,21 @@ impl OptionsScreen {
     pub fn new() -> Self {
         Self {
             is_btm_pressed: false,
+            settings: Settings::default(),
+            settings_changed: false,
+            rebinding: None,
+            page: OptionsPage::General,
+            ui: UiState::default(),
             counter: 0,
+            locale: Rc::default(),
+        }
+    }
+
+    /// Bind an input to the action currently being rebound
+    fn bind(&mut self, binding: InputBinding) {
+        if let Some(action) = self.rebinding.take() {
+            self.settings.input_bindings.rebind(action, binding);
+            self.settings_changed = true;
         }
     }
 }
@@ -44,7 +111,7 @@ impl Action<Scenes, ScreenError, GameContext> for OptionsScreen {
 
         if let Err(e) = context.discord_rpc_send.send(Some(
             ActivityBuilder::default()
-                .details("we gott'em boys!")
+                .details(context.locale.text("discord.options"))
                 .assets(
                     Assets::default().large("game-logo-small", Some(context.config.name.clone())),
                 ),
@@ -52,9 +119,6 @@ impl Action<Scenes, ScreenError, GameContext> for OptionsScreen {
             error!("Failed to update discord: {}", e);
         }
 
-        // Rick-roll the user
-        let _ = webbrowser::open("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
-
         Ok(())
     }
 
@@ -64,16 +128,60 @@ impl Action<Scenes, ScreenError, GameContext> for OptionsScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on OptionsScreen");
+
+        // Start from the real settings every frame, since they can also be changed with shortcuts like F11
+        self.settings = context.settings.clone();
+
+        // Wait for a key or gamepad button to bind
+        if self.rebinding.is_some() {
+            // The input is not meant for the menu, even on the frame it is bound
+            self.ui.block_input_for_frame();
+
+            let mut renderer = context.renderer.borrow_mut();
+            let pressed_button = BINDABLE_GAMEPAD_BUTTONS
+                .iter()
+                .map(|(button, _)| *button)
+                .find(|button| {
+                    renderer.is_gamepad_available(0)
+                        && renderer.is_gamepad_button_pressed(0, *button)
+                });
+
+            // Escape always cancels, so there is a way out without binding anything
+            if renderer.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                self.rebinding = None;
+            } else if let Some(button) = pressed_button {
+                self.bind(InputBinding::GamepadButton(button));
+            } else if let Some(key) = renderer.get_key_pressed() {
+                // Keys without names can not be saved
+                if key_name(key).is_some() {
+                    self.bind(InputBinding::Key(key));
+                }
+            }
+        }
+
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
 
+        self.ui.send_click_sound(context);
+
         self.counter += 1;
 
-        if self.is_btm_pressed {
+        if self.settings_changed {
+            self.settings_changed = false;
             context
                 .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .send(Some(ControlFlag::ApplySettings(self.settings.clone())))
                 .unwrap();
-            Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
+        }
+
+        if self.is_btm_pressed {
+            Ok(ActionFlag::SwitchState(
+                context
+                    .scene_stack
+                    .opener()
+                    .unwrap_or(Scenes::MainMenuScreen),
+            ))
         } else {
             Ok(ActionFlag::Continue)
         }
@@ -82,6 +190,8 @@ impl Action<Scenes, ScreenError, GameContext> for OptionsScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished OptionsScreen");
         self.is_btm_pressed = false;
+        self.rebinding = None;
+        self.ui.reset();
         Ok(())
     }
 }
@@ -94,8 +204,8 @@ impl ScreenSpaceRender for OptionsScreen {
     ) {
         let screen_size = raylib.get_screen_size();
 
-        // Render the background
-        raylib.clear_background(Color::BLACK);
+        // Options are an overlay, so mostly hide whatever opened them
+        raylib.draw_rectangle_v(Vector2::zero(), screen_size, Color::BLACK.fade(0.85));
         raylib.draw_rectangle_lines(
             0,
             0,
@@ -104,45 +214,157 @@ impl ScreenSpaceRender for OptionsScreen {
             config.colors.white,
         );
 
-        let screen_size = raylib.get_screen_size();
+        let locale = self.locale.clone();
+        let layout = raylib.layout();
+        let row = |y: f32| layout.at(Anchor::TopLeft, Vector2::new(100.0, y));
+        let mut ui = self.ui.begin(raylib);
 
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
+        // Render the title
+        ui.title(
+            layout.at(Anchor::TopLeft, Vector2::new(40.0, 80.0)),
+            &format!("[{}]", locale.text("options.title")),
+            &mut self.counter,
+        );
 
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        // Render every setting on the current page. Changes are applied right away
+        ui.stepper(
+            row(160.0),
+            locale.text("options.page"),
+            OPTIONS_PAGES,
+            &mut self.page,
+            |page: OptionsPage| locale.text(page.locale_key()).to_string(),
+        );
 
-        // Render the title
-        let timer: i32 = get_random_value(50, 400);
-        if self.counter > timer {
-            raylib.draw_rgb_split_text(Vector2::new(40.0, 80.0), "[Options]", 70, true, Color::WHITE);
-            if self.counter > timer + 20 {
-                self.counter = 0;
-            }
-        }
-        else{
-            raylib.draw_rgb_split_text(Vector2::new(40.0, 80.0), "[Options]", 70, false, Color::WHITE);
+        let settings = &mut self.settings;
+        let mut changed = false;
+        match self.page {
+            OptionsPage::General => {
+                changed |= ui.slider(
+                    row(220.0),
+                    locale.text("options.master_volume"),
+                    &mut settings.master_volume,
+                );
+                changed |= ui.slider(
+                    row(260.0),
+                    locale.text("options.music_volume"),
+                    &mut settings.music_volume,
+                );
+                changed |= ui.slider(
+                    row(300.0),
+                    locale.text("options.sfx_volume"),
+                    &mut settings.sfx_volume,
+                );
+                changed |= ui.toggle(
+                    row(340.0),
+                    locale.text("options.fullscreen"),
+                    &mut settings.fullscreen,
+                );
 
-        }
+                let mut resolution = settings.resolution.unwrap_or(config.base_window_size);
+                if ui.stepper(
+                    row(380.0),
+                    locale.text("options.resolution"),
+                    RESOLUTIONS,
+                    &mut resolution,
+                    |(width, height): (i32, i32)| format!("{}x{}", width, height),
+                ) {
+                    settings.resolution = Some(resolution);
+                    changed = true;
+                }
 
-        // Render the text
-        raylib.draw_rgb_split_text(
-            Vector2::new(100.0, 300.0),
-            ">> The game controls YOU",
-            45,
-            true,
-            Color::WHITE,
-        );
+                changed |= ui.toggle(
+                    row(420.0),
+                    locale.text("options.vsync"),
+                    &mut settings.vsync,
+                );
+                changed |= ui.stepper(
+                    row(460.0),
+                    locale.text("options.target_fps"),
+                    TARGET_FPS_OPTIONS,
+                    &mut settings.target_fps,
+                    |fps: u32| fps.to_string(),
+                );
+                changed |= ui.slider(
+                    row(500.0),
+                    locale.text("options.shader_strength"),
+                    &mut settings.shader_strength,
+                );
+                changed |= ui.toggle(
+                    row(540.0),
+                    locale.text("options.auto_respawn"),
+                    &mut settings.auto_respawn,
+                );
+
+                // Languages without a string table can only come from a hand-edited settings file
+                let mut language = LANGUAGES
+                    .iter()
+                    .copied()
+                    .find(|(code, _)| *code == settings.language)
+                    .unwrap_or(LANGUAGES[0]);
+                if ui.stepper(
+                    row(580.0),
+                    locale.text("options.language"),
+                    LANGUAGES,
+                    &mut language,
+                    |(_, name): (&str, &str)| name.to_string(),
+                ) {
+                    settings.language = language.0.to_string();
+                    changed = true;
+                }
+                changed |= ui.stepper(
+                    row(620.0),
+                    locale.text("options.ui_scale"),
+                    UI_SCALES,
+                    &mut settings.ui_scale,
+                    |scale: f32| format!("{}%", (scale * 100.0).round()),
+                );
+            }
+            OptionsPage::Controls => {
+                // Clicking a binding waits for a new key or gamepad button
+                for (idx, action) in REBINDABLE_ACTIONS.iter().enumerate() {
+                    let label = locale.text(action.locale_key());
+                    let value = if self.rebinding == Some(*action) {
+                        locale.text("options.press_a_key").to_string()
+                    } else {
+                        format!("[{}]", settings.input_bindings.describe(*action))
+                    };
+                    if ui.setting_button(row(220.0 + idx as f32 * 40.0), label, &value) {
+                        self.rebinding = Some(*action);
+                    }
+                }
+            }
+            OptionsPage::Hud => {
+                let hud = &mut settings.hud;
+                changed |= ui.toggle(row(220.0), locale.text("options.hud_timer"), &mut hud.timer);
+                changed |= ui.toggle(
+                    row(260.0),
+                    locale.text("options.hud_level_name"),
+                    &mut hud.level_name,
+                );
+                changed |= ui.toggle(
+                    row(300.0),
+                    locale.text("options.hud_attempts"),
+                    &mut hud.attempts,
+                );
+                changed |= ui.toggle(
+                    row(340.0),
+                    locale.text("options.hud_best_time"),
+                    &mut hud.best_time,
+                );
+                changed |= ui.toggle(
+                    row(380.0),
+                    locale.text("options.hud_progress_bar"),
+                    &mut hud.progress_bar,
+                );
+            }
+        }
+        self.settings_changed |= changed;
 
         //Back to Menu
-        let hovering_back = Rectangle::new(35.0, screen_size.y as f32 - 80.0, 200.0, 40.0)
-            .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new(25.0, screen_size.y - 50.0),
-            "BACK TO MENU",
+        self.is_btm_pressed = ui.button(
+            layout.at(Anchor::BottomLeft, Vector2::new(50.0, -50.0)),
+            locale.text("ui.back"),
             25,
-            hovering_back,
-            Color::WHITE,
-        );
-        self.is_btm_pressed = mouse_pressed && hovering_back;
+        ) || ui.back_pressed();
     }
 }
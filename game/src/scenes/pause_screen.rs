@@ -1,4 +1,7 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
 use chrono::{DateTime, Utc};
 use dirty_fsm::{Action, ActionFlag};
@@ -6,24 +9,46 @@ use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
 use raylib::prelude::*;
 
-use crate::{GameConfig, context::{ControlFlag, GameContext}, utilities::{
+use crate::{
+    context::{ControlFlag, GameContext},
+    input::InputAction,
+    locale::Locale,
+    scenes::confirm_screen::Dialog,
+    utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
-    }};
+        text::TextAlign,
+        widgets::UiState,
+    },
+    GameConfig,
+};
 
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, info, trace};
 
 #[derive(Debug)]
-pub struct PauseScreen {}
+pub struct PauseScreen {
+    is_resume_pressed: bool,
+    is_options_pressed: bool,
+    is_menu_pressed: bool,
+    ui: UiState,
+    locale: Rc<Locale>,
+}
 
 impl PauseScreen {
     /// Construct a new `PauseScreen`
     pub fn new() -> Self {
-        Self {}
+        Self {
+            is_resume_pressed: false,
+            is_options_pressed: false,
+            is_menu_pressed: false,
+            ui: UiState::default(),
+            locale: Rc::default(),
+        }
     }
 }
 
@@ -38,9 +63,11 @@ impl Action<Scenes, ScreenError, GameContext> for PauseScreen {
 
         // Update discord
         if let Err(e) = context.discord_rpc_send.send(Some(
-            ActivityBuilder::default().details("paused").assets(
-                Assets::default().large("game-logo-small", Some(context.config.name.clone())),
-            ),
+            ActivityBuilder::default()
+                .details(context.locale.text("discord.paused"))
+                .assets(
+                    Assets::default().large("game-logo-small", Some(context.config.name.clone())),
+                ),
         )) {
             error!("Failed to update discord: {}", e);
         }
@@ -54,54 +81,23 @@ impl Action<Scenes, ScreenError, GameContext> for PauseScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on PauseScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
-        //Screen Size
-        let screen_size = context.renderer.borrow_mut().get_screen_size();
-
-        let centered_x_menu = (screen_size.x as f32 / 2.0) - 120.0;
-        let centered_y_menu = (screen_size.y as f32 / 2.0) + 100.0;
-        let centered_x_paused = (screen_size.x as f32 / 2.0) - 220.0;
-        let centered_y_paused = (screen_size.y as f32 / 2.0) - 40.0;
-
-        //Mouse Position
-        let mouse_position: Vector2 = context.renderer.borrow_mut().get_mouse_position();
-        //Mouse Input
-        let is_left_click = context
-            .renderer
-            .borrow_mut()
-            .is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON);
-
-        //"Hitboxes" for the resume and Main menu buttons
-
-        //For Paused
-        if is_left_click
-            && Rectangle::new(centered_x_paused, centered_y_paused, 435.0, 80.0)
-                .check_collision_point_rec(mouse_position)
-        {
+        self.ui.send_click_sound(context);
+
+        // Go back to whatever was paused
+        let opener = context.scene_stack.opener().unwrap_or(Scenes::InGameScene);
+        if self.is_resume_pressed || context.input.is_pressed(InputAction::Pause) {
+            Ok(ActionFlag::SwitchState(opener))
+        } else if self.is_options_pressed {
+            Ok(ActionFlag::SwitchState(Scenes::OptionsScreen))
+        } else if self.is_menu_pressed {
             context
                 .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .send(Some(ControlFlag::OpenDialog(Dialog::LeaveLevel)))
                 .unwrap();
-            return Ok(ActionFlag::SwitchState(Scenes::InGameScene));
-        }
-        //For Menu
-        if is_left_click
-            && Rectangle::new(centered_x_menu, centered_y_menu, 200.0, 50.0)
-                .check_collision_point_rec(mouse_position)
-        {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
-            return Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen));
-        }
-
-        if context
-            .renderer
-            .borrow_mut()
-            .is_key_pressed(KeyboardKey::KEY_ESCAPE)
-        {
-            Ok(ActionFlag::SwitchState(Scenes::InGameScene))
+            Ok(ActionFlag::SwitchState(Scenes::ConfirmScreen))
         } else {
             Ok(ActionFlag::Continue)
         }
@@ -109,6 +105,10 @@ impl Action<Scenes, ScreenError, GameContext> for PauseScreen {
 
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished PauseScreen");
+        self.is_resume_pressed = false;
+        self.is_options_pressed = false;
+        self.is_menu_pressed = false;
+        self.ui.reset();
         Ok(())
     }
 }
@@ -121,13 +121,8 @@ impl ScreenSpaceRender for PauseScreen {
     ) {
         let screen_size = raylib.get_screen_size();
 
-        // Render the background
-        raylib.clear_background(Color::BLACK.fade(50.0));
-
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-        //Mouse Input
-        let is_left_click = raylib.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON);
+        // Dim the frozen game behind the menu
+        raylib.draw_rectangle_v(Vector2::zero(), screen_size, Color::BLACK.fade(0.6));
 
         raylib.draw_rectangle_lines(
             0,
@@ -137,37 +132,23 @@ impl ScreenSpaceRender for PauseScreen {
             config.colors.white,
         );
 
-        //Variables for centering
-        let centered_x_menu = (screen_size.x as f32 / 2.0) - 120.0;
-        let centered_y_menu = (screen_size.y as f32 / 2.0) + 100.0;
-        let centered_x_paused = (screen_size.x as f32 / 2.0) - 220.0;
-        let centered_y_paused = (screen_size.y as f32 / 2.0) - 40.0;
-
         //Pause Menu Texts With Glitchy Effect
-        let hovering_pause = Rectangle::new(centered_x_paused, centered_y_paused, 435.0, 80.0)
-            .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new((screen_size.x / 2.0) - 220.0, (screen_size.y / 2.0) - 40.0),
-            "Paused",
-            120,
-            hovering_pause,
-            Color::WHITE,
-        );
-        raylib.draw_rgb_split_text(
-            Vector2::new((screen_size.x / 2.0) - 80.0, (screen_size.y / 2.0) + 60.0),
-            "Click To Resume",
-            20,
-            false,
-            Color::WHITE,
-        );
-        let hovering_main_menu = Rectangle::new(centered_x_menu, centered_y_menu, 200.0, 50.0)
-            .check_collision_point_rec(mouse_position);
-        raylib.draw_rgb_split_text(
-            Vector2::new((screen_size.x / 2.0) - 120.0, (screen_size.y / 2.0) + 100.0),
-            "Main Menu",
-            50,
-            hovering_main_menu,
-            Color::WHITE,
-        );
+        let locale = self.locale.clone();
+        let mut ui = self.ui.begin(raylib);
+        // Everything is centered, since translations are all different widths
+        let layout = ui.layout();
+        let center = |y: f32| layout.at(Anchor::Center, Vector2::new(0.0, y));
+        let paused = locale.text("pause.paused");
+        let position = ui.align(center(-40.0), paused, 120, TextAlign::Center);
+        self.is_resume_pressed = ui.button(position, paused, 120) || ui.back_pressed();
+        let resume = locale.text("pause.click_to_resume");
+        let position = ui.align(center(60.0), resume, 20, TextAlign::Center);
+        ui.label(position, resume, 20);
+        let options = locale.text("pause.options");
+        let position = ui.align(center(100.0), options, 50, TextAlign::Center);
+        self.is_options_pressed = ui.button(position, options, 50);
+        let main_menu = locale.text("pause.main_menu");
+        let position = ui.align(center(160.0), main_menu, 50, TextAlign::Center);
+        self.is_menu_pressed = ui.button(position, main_menu, 50);
     }
 }
@@ -0,0 +1,82 @@
+//! Keeps track of overlay scenes and the scenes that opened them.
+//!
+//! `dirty_fsm` only ever runs one scene, so overlays are faked. When a scene switches to an overlay,
+//! the main loop keeps its last frame and draws it under the overlay every frame. The overlay can
+//! then switch back to [`SceneStack::opener`] instead of a hard-coded scene.
+
+use super::Scenes;
+
+impl Scenes {
+    /// Overlays are drawn over a frozen frame of the scene that opened them
+    pub fn is_overlay(&self) -> bool {
+        matches!(
+            self,
+            Self::PauseScreen | Self::OptionsScreen | Self::ConfirmScreen
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SceneStack {
+    /// Scenes that opened each overlay on screen, from the bottom up
+    openers: Vec<Scenes>,
+}
+
+impl SceneStack {
+    /// Update the stack for a switch between scenes.
+    /// Returns if the outgoing scene's last frame should be frozen, which is only needed for the first overlay opened
+    pub fn on_switch(&mut self, from: Scenes, to: Scenes) -> bool {
+        if self.openers.last() == Some(&to) {
+            // Going back to whatever opened the overlay
+            self.openers.pop();
+            false
+        } else if to.is_overlay() {
+            let is_first_overlay = self.openers.is_empty();
+            self.openers.push(from);
+            is_first_overlay
+        } else {
+            // Any other switch leaves every overlay behind
+            self.openers.clear();
+            false
+        }
+    }
+
+    /// The scene that opened the overlay on top
+    pub fn opener(&self) -> Option<Scenes> {
+        self.openers.last().copied()
+    }
+
+    /// Is any overlay open. If so, the frozen frame should be drawn under it
+    pub fn has_overlay(&self) -> bool {
+        !self.openers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_overlays_return_to_their_opener() {
+        let mut stack = SceneStack::default();
+        assert!(stack.on_switch(Scenes::InGameScene, Scenes::PauseScreen));
+        assert!(!stack.on_switch(Scenes::PauseScreen, Scenes::OptionsScreen));
+        assert_eq!(stack.opener(), Some(Scenes::PauseScreen));
+
+        assert!(!stack.on_switch(Scenes::OptionsScreen, Scenes::PauseScreen));
+        assert_eq!(stack.opener(), Some(Scenes::InGameScene));
+
+        assert!(!stack.on_switch(Scenes::PauseScreen, Scenes::InGameScene));
+        assert!(!stack.has_overlay());
+    }
+
+    #[test]
+    fn test_leaving_an_overlay_elsewhere_clears_the_stack() {
+        let mut stack = SceneStack::default();
+        stack.on_switch(Scenes::InGameScene, Scenes::PauseScreen);
+        stack.on_switch(Scenes::PauseScreen, Scenes::ConfirmScreen);
+        stack.on_switch(Scenes::ConfirmScreen, Scenes::MainMenuScreen);
+        assert!(!stack.has_overlay());
+        assert_eq!(stack.opener(), None);
+    }
+}
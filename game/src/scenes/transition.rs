@@ -0,0 +1,223 @@
+//! Effects played when switching between scenes.
+//!
+//! Every scene is registered wrapped in a [`TrackedScene`], which reports each switch to the main loop.
+//! The main loop keeps the outgoing scene's last frame, and blends it into the incoming scene with the
+//! transition shader until the transition is over. Input is blocked while a transition plays.
+
+use dirty_fsm::{Action, ActionFlag};
+use rust_embed::EmbeddedFile;
+
+use crate::context::{ControlFlag, GameContext};
+
+use super::{Scenes, ScreenError};
+
+/// How one scene is swapped for another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionEffect {
+    /// Cut straight to the next scene
+    None,
+    /// Fade to black, then into the next scene
+    Fade,
+    /// Wipe across the screen one jittering row at a time
+    GlitchWipe,
+    /// Swap the screen over in random blocks
+    PixelDissolve,
+}
+
+impl TransitionEffect {
+    /// The value the transition shader uses to pick this effect
+    pub fn shader_id(&self) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::Fade => 1.0,
+            Self::GlitchWipe => 2.0,
+            Self::PixelDissolve => 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TransitionStyle {
+    pub effect: TransitionEffect,
+    pub duration_ms: u64,
+}
+
+/// A transition for switching between specific scenes. Leaving out `from` or `to` matches any scene
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransitionRule {
+    pub from: Option<Scenes>,
+    pub to: Option<Scenes>,
+    #[serde(flatten)]
+    pub style: TransitionStyle,
+}
+
+/// Which transition to play for each pair of scenes. This data is immutable, and should only be edited by hand
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransitionConfig {
+    /// Used when no rule matches
+    pub default: TransitionStyle,
+    /// Checked in order. The first match is used
+    pub rules: Vec<TransitionRule>,
+}
+
+impl TransitionConfig {
+    /// Load from a file
+    pub fn load(file: EmbeddedFile) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(&file.data)
+    }
+
+    /// Get the transition to play when switching from one scene to another
+    pub fn style_for(&self, from: Scenes, to: Scenes) -> TransitionStyle {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.from.map_or(true, |scene| scene == from)
+                    && rule.to.map_or(true, |scene| scene == to)
+            })
+            .map_or(self.default, |rule| rule.style)
+    }
+}
+
+/// A transition that is currently playing
+#[derive(Debug)]
+pub struct ActiveTransition {
+    style: TransitionStyle,
+    elapsed_seconds: f32,
+}
+
+impl ActiveTransition {
+    /// Start a transition. Returns `None` if there is nothing to play
+    pub fn start(style: TransitionStyle) -> Option<Self> {
+        if style.effect == TransitionEffect::None || style.duration_ms == 0 {
+            None
+        } else {
+            Some(Self {
+                style,
+                elapsed_seconds: 0.0,
+            })
+        }
+    }
+
+    pub fn advance(&mut self, delta_seconds: f32) {
+        self.elapsed_seconds += delta_seconds;
+    }
+
+    /// How far along the transition is, from `0.0` to `1.0`
+    pub fn progress(&self) -> f32 {
+        (self.elapsed_seconds * 1000.0 / self.style.duration_ms as f32).min(1.0)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    pub fn effect(&self) -> TransitionEffect {
+        self.style.effect
+    }
+}
+
+/// Wraps a scene to report whenever it switches to another one
+#[derive(Debug)]
+pub struct TrackedScene<A> {
+    id: Scenes,
+    scene: A,
+}
+
+impl<A> TrackedScene<A> {
+    pub fn new(id: Scenes, scene: A) -> Self {
+        Self { id, scene }
+    }
+}
+
+impl<A> Action<Scenes, ScreenError, GameContext> for TrackedScene<A>
+where
+    A: Action<Scenes, ScreenError, GameContext> + std::fmt::Debug,
+{
+    fn on_register(&mut self) -> Result<(), ScreenError> {
+        self.scene.on_register()
+    }
+
+    fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
+        self.scene.on_first_run(context)
+    }
+
+    fn execute(
+        &mut self,
+        delta: &chrono::Duration,
+        context: &GameContext,
+    ) -> Result<ActionFlag<Scenes>, ScreenError> {
+        let result = self.scene.execute(delta, context);
+        if let Ok(ActionFlag::SwitchState(to)) = &result {
+            context
+                .flag_send
+                .send(Some(ControlFlag::SceneSwitched {
+                    from: self.id,
+                    to: *to,
+                }))
+                .unwrap();
+        }
+        result
+    }
+
+    fn on_finish(&mut self, interrupted: bool) -> Result<(), ScreenError> {
+        self.scene.on_finish(interrupted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn style(effect: TransitionEffect, duration_ms: u64) -> TransitionStyle {
+        TransitionStyle {
+            effect,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_is_used() {
+        let config: TransitionConfig = serde_json::from_str(
+            r#"{
+                "default": { "effect": "fade", "duration_ms": 300 },
+                "rules": [
+                    { "from": "InGameScene", "to": "PauseScreen", "effect": "none", "duration_ms": 0 },
+                    { "to": "InGameScene", "effect": "glitch_wipe", "duration_ms": 500 },
+                    { "from": "PauseScreen", "effect": "pixel_dissolve", "duration_ms": 400 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.style_for(Scenes::InGameScene, Scenes::PauseScreen),
+            style(TransitionEffect::None, 0)
+        );
+        assert_eq!(
+            config.style_for(Scenes::PauseScreen, Scenes::InGameScene),
+            style(TransitionEffect::GlitchWipe, 500)
+        );
+        assert_eq!(
+            config.style_for(Scenes::PauseScreen, Scenes::MainMenuScreen),
+            style(TransitionEffect::PixelDissolve, 400)
+        );
+        assert_eq!(
+            config.style_for(Scenes::MainMenuScreen, Scenes::OptionsScreen),
+            style(TransitionEffect::Fade, 300)
+        );
+    }
+
+    #[test]
+    fn test_transition_progress() {
+        assert!(ActiveTransition::start(style(TransitionEffect::None, 300)).is_none());
+        assert!(ActiveTransition::start(style(TransitionEffect::Fade, 0)).is_none());
+
+        let mut transition = ActiveTransition::start(style(TransitionEffect::Fade, 500)).unwrap();
+        transition.advance(0.25);
+        assert_relative_eq!(transition.progress(), 0.5);
+        assert!(!transition.is_done());
+        transition.advance(0.5);
+        assert_relative_eq!(transition.progress(), 1.0);
+        assert!(transition.is_done());
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::{Div, Sub};
+use std::{
+    ops::{Div, Sub},
+    rc::Rc,
+};
 
 use chrono::{DateTime, Utc};
 use dirty_fsm::{Action, ActionFlag};
@@ -6,13 +9,21 @@ use discord_sdk::activity::{ActivityBuilder, Assets};
 use pkg_version::pkg_version_major;
 use raylib::prelude::*;
 
-use crate::{GameConfig, context::{ControlFlag, GameContext}, utilities::{
+use crate::{
+    context::{ControlFlag, GameContext},
+    locale::Locale,
+    scenes::ingame_scene::level::campaign::DEFAULT_WIN_MESSAGE,
+    utilities::{
         datastore::{load_texture_from_internal_data, ResourceLoadError},
         game_version::get_version_string,
+        layout::Anchor,
         math::interpolate_exp,
         non_ref_raylib::HackedRaylibHandle,
         render_layer::ScreenSpaceRender,
-    }};
+        widgets::UiState,
+    },
+    GameConfig,
+};
 
 use super::{Scenes, ScreenError};
 use tracing::{debug, error, info, trace};
@@ -20,7 +31,11 @@ use tracing::{debug, error, info, trace};
 #[derive(Debug)]
 pub struct WinScreen {
     is_menu_pressed: bool, //Is menu button pressed
+    /// Set by the campaign that was just beaten
+    win_message: String,
+    ui: UiState,
     counter: i32,
+    locale: Rc<Locale>,
 }
 
 impl WinScreen {
@@ -28,7 +43,10 @@ impl WinScreen {
     pub fn new() -> Self {
         Self {
             is_menu_pressed: false,
+            win_message: DEFAULT_WIN_MESSAGE.to_string(),
+            ui: UiState::default(),
             counter: 0,
+            locale: Rc::default(),
         }
     }
 }
@@ -42,9 +60,23 @@ impl Action<Scenes, ScreenError, GameContext> for WinScreen {
     fn on_first_run(&mut self, context: &GameContext) -> Result<(), ScreenError> {
         debug!("Running WinScreen for the first time");
 
+        // Every campaign can have its own message. Daily challenges are not part of one
+        self.win_message = if context.daily_challenge.is_some() {
+            context.locale.text("win.daily_done").to_string()
+        } else {
+            context
+                .levels
+                .get(context.current_level)
+                .and_then(|level| context.campaigns.get(level.campaign))
+                .map_or_else(
+                    || DEFAULT_WIN_MESSAGE.to_string(),
+                    |campaign| campaign.win_message.clone(),
+                )
+        };
+
         if let Err(e) = context.discord_rpc_send.send(Some(
             ActivityBuilder::default()
-                .details("somehow won the game")
+                .details(context.locale.text("discord.won"))
                 .assets(
                     Assets::default().large("game-logo-small", Some(context.config.name.clone())),
                 ),
@@ -61,14 +93,13 @@ impl Action<Scenes, ScreenError, GameContext> for WinScreen {
         context: &GameContext,
     ) -> Result<dirty_fsm::ActionFlag<Scenes>, ScreenError> {
         trace!("execute() called on WinScreen");
+        self.ui.read_input(context);
+        self.locale = context.locale.clone();
         self.render_screen_space(&mut context.renderer.borrow_mut(), &context.config);
+        self.ui.send_click_sound(context);
         self.counter += 1;
 
         if self.is_menu_pressed {
-            context
-                .flag_send
-                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
-                .unwrap();
             context.flag_send.send(Some(ControlFlag::EndLevel)).unwrap();
             Ok(ActionFlag::SwitchState(Scenes::MainMenuScreen))
         } else {
@@ -79,6 +110,7 @@ impl Action<Scenes, ScreenError, GameContext> for WinScreen {
     fn on_finish(&mut self, _interrupted: bool) -> Result<(), ScreenError> {
         debug!("Finished WinScreen");
         self.is_menu_pressed = false;
+        self.ui.reset();
         self.counter = 0;
 
         Ok(())
@@ -92,6 +124,7 @@ impl ScreenSpaceRender for WinScreen {
         config: &GameConfig,
     ) {
         let screen_size = raylib.get_screen_size();
+        let layout = raylib.layout();
 
         // Render the background
         raylib.clear_background(Color::BLACK);
@@ -103,14 +136,9 @@ impl ScreenSpaceRender for WinScreen {
             config.colors.white,
         );
 
-        //Mouse Position
-        let mouse_position: Vector2 = raylib.get_mouse_position();
-
-        let mouse_pressed: bool = raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
-
         raylib.draw_rgb_split_text(
-            Vector2::new(100.0, screen_size.y as f32 / 2.0 - 120.0),
-            "congrats.",
+            layout.at(Anchor::Left, Vector2::new(100.0, -120.0)),
+            self.locale.text("win.congrats"),
             40,
             false,
             Color::WHITE,
@@ -118,8 +146,8 @@ impl ScreenSpaceRender for WinScreen {
 
         if self.counter > 100 {
             raylib.draw_rgb_split_text(
-                Vector2::new(100.0, screen_size.y as f32 / 2.0 - 60.0),
-                "you win.",
+                layout.at(Anchor::Left, Vector2::new(100.0, -60.0)),
+                &self.win_message,
                 40,
                 false,
                 Color::WHITE,
@@ -127,8 +155,8 @@ impl ScreenSpaceRender for WinScreen {
         }
         if self.counter > 300 {
             raylib.draw_rgb_split_text(
-                Vector2::new(100.0, screen_size.y as f32 / 2.0),
-                "yay.",
+                layout.at(Anchor::Left, Vector2::new(100.0, 0.0)),
+                self.locale.text("win.yay"),
                 40,
                 false,
                 Color::WHITE,
@@ -137,27 +165,13 @@ impl ScreenSpaceRender for WinScreen {
 
         //Return to Main Menu
         if self.counter > 450 {
-            if Rectangle::new(100.0, screen_size.y as f32 / 2.0 + 90.0, 270.0, 20.0)
-                .check_collision_point_rec(mouse_position)
-            {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(100.0, screen_size.y as f32 / 2.0 + 100.0),
-                    ">> RETURN TO MAIN MENU",
-                    20,
-                    true,
-                    Color::WHITE,
-                );
-
-                self.is_menu_pressed = mouse_pressed
-            } else {
-                raylib.draw_rgb_split_text(
-                    Vector2::new(100.0, screen_size.y as f32 / 2.0 + 100.0),
-                    ">> RETURN TO MAIN MENU",
-                    20,
-                    false,
-                    Color::WHITE,
-                );
-            }
+            let locale = self.locale.clone();
+            let mut ui = self.ui.begin(raylib);
+            self.is_menu_pressed = ui.button(
+                layout.at(Anchor::Left, Vector2::new(100.0, 100.0)),
+                locale.text("ui.return_to_main_menu"),
+                20,
+            ) || ui.back_pressed();
         }
     }
 }
@@ -0,0 +1,129 @@
+use tracing::info;
+
+use crate::{input::InputBindings, locale::DEFAULT_LANGUAGE};
+
+/// Which parts of the in-game HUD are shown
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct HudSettings {
+    pub timer: bool,
+    pub level_name: bool,
+    pub attempts: bool,
+    /// The best time, and how far ahead or behind it the current run is
+    pub best_time: bool,
+    pub progress_bar: bool,
+}
+
+impl Default for HudSettings {
+    fn default() -> Self {
+        Self {
+            timer: true,
+            level_name: true,
+            attempts: true,
+            best_time: true,
+            progress_bar: true,
+        }
+    }
+}
+
+/// Player settings. Unlike progress, these are kept in a file meant to be edited by hand if needed
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Volumes are all `0.0` to `1.0`
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    /// Window size when not fullscreen. `None` uses the size from `application.json`
+    pub resolution: Option<(i32, i32)>,
+    /// Only applied on startup
+    pub vsync: bool,
+    pub target_fps: u32,
+    /// How much of the CRT warp and scanline effect to use. `0.0` to `1.0`
+    pub shader_strength: f32,
+    pub input_bindings: InputBindings,
+    pub hud: HudSettings,
+    /// Retry automatically a moment after dying, instead of waiting on the death screen
+    pub auto_respawn: bool,
+    /// How big menus and the HUD are, on top of scaling them to fit the window
+    pub ui_scale: f32,
+    /// Code of the language to show text in, like `"en"`
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 0.4,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            fullscreen: false,
+            resolution: None,
+            vsync: false,
+            target_fps: 60,
+            shader_strength: 1.0,
+            input_bindings: InputBindings::default(),
+            hud: HudSettings::default(),
+            auto_respawn: false,
+            ui_scale: 1.0,
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load_from_file() -> Self {
+        info!("Loading settings from file");
+        serde_json::from_str(
+            &std::fs::read_to_string("./settings.json").unwrap_or("{}".to_string()),
+        )
+        .unwrap_or(Self::default())
+    }
+
+    pub fn save(&self) {
+        info!("Saving settings to file");
+        std::fs::write(
+            "./settings.json",
+            serde_json::to_string_pretty(self).unwrap(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use raylib::consts::KeyboardKey;
+
+    use super::*;
+    use crate::input::{InputAction, InputBinding};
+
+    #[test]
+    fn test_missing_settings_use_defaults() {
+        let settings: Settings =
+            serde_json::from_str("{\"target_fps\":144,\"input_bindings\":{\"jump\":[\"W\"]}}")
+                .unwrap();
+        assert_eq!(settings.target_fps, 144);
+        assert_eq!(
+            settings.input_bindings.get(InputAction::Jump),
+            &[InputBinding::Key(KeyboardKey::KEY_W)]
+        );
+        assert_eq!(
+            settings.input_bindings.get(InputAction::Dash),
+            InputBindings::default().get(InputAction::Dash)
+        );
+        assert_eq!(settings.master_volume, Settings::default().master_volume);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut settings = Settings::default();
+        settings.resolution = Some((1920, 1080));
+        settings
+            .input_bindings
+            .rebind(InputAction::Dash, InputBinding::Key(KeyboardKey::KEY_ENTER));
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains("\"ENTER\""));
+        assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+    }
+}
@@ -0,0 +1,246 @@
+//! Prepares assets on a background thread so the game window can keep drawing while things load.
+//!
+//! Anything that needs the GPU or audio device must still happen on the main thread. The worker
+//! only does file I/O and decoding, then hands the results back over an MPSC channel.
+
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use tracing::{debug, error};
+
+use crate::scenes::ingame_scene::level::{
+    campaign::Campaign,
+    endless::{prepare_endless_catalogue, PreparedCatalogue},
+    loader::load_all_levels,
+    Level,
+};
+
+use super::datastore::{
+    decode_image_from_internal_data, stage_internal_file, DecodedImage, ResourceLoadError,
+    StagedFile,
+};
+
+/// A single piece of work for the loader thread
+#[derive(Debug, Clone)]
+pub enum AssetJob {
+    Texture(String),
+    Sound { name: String, path: String },
+    Music(String),
+    LevelMetadata,
+    /// Cut out every endless chunk. Sending them to the GPU waits until an endless run starts
+    EndlessChunks,
+}
+
+impl AssetJob {
+    /// Get a human-readable description of this job
+    pub fn describe(&self) -> String {
+        match self {
+            AssetJob::Texture(path) | AssetJob::Sound { path, .. } | AssetJob::Music(path) => {
+                path.clone()
+            }
+            AssetJob::LevelMetadata => "levels".to_string(),
+            AssetJob::EndlessChunks => "endless chunks".to_string(),
+        }
+    }
+}
+
+/// The result of a job, ready to be finished off on the main thread
+#[derive(Debug)]
+pub enum PreparedAsset {
+    Texture { path: String, image: DecodedImage },
+    Sound { name: String, file: StagedFile },
+    Music { path: String, file: StagedFile },
+    LevelMetadata {
+        levels: Vec<Level>,
+        campaigns: Vec<Campaign>,
+    },
+    EndlessChunks(PreparedCatalogue),
+}
+
+/// A snapshot of how far along the loader is
+#[derive(Debug, Clone, Default)]
+pub struct LoadingProgress {
+    pub completed: usize,
+    pub total: usize,
+    /// The asset that is loading right now
+    pub current_item: String,
+    /// An asset could not be loaded, or the worker stopped before finishing every job, so loading can never complete
+    pub failed: bool,
+}
+
+impl LoadingProgress {
+    /// Get the progress as a value from `0.0` to `1.0`
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.completed as f32 / self.total as f32
+        }
+    }
+
+    /// Check if every job has been finished on the main thread
+    pub fn is_done(&self) -> bool {
+        self.completed >= self.total
+    }
+}
+
+/// Handle to a background asset loading thread
+#[derive(Debug)]
+pub struct AssetLoader {
+    receiver: Receiver<(String, Result<PreparedAsset, ResourceLoadError>)>,
+    /// Every job's description, in the order they are finished
+    items: Vec<String>,
+    progress: LoadingProgress,
+}
+
+impl AssetLoader {
+    /// Start a worker thread that will prepare every job in order
+    pub fn spawn(jobs: Vec<AssetJob>) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let items: Vec<String> = jobs.iter().map(AssetJob::describe).collect();
+
+        std::thread::spawn(move || {
+            for job in jobs {
+                debug!("Preparing asset: {}", job.describe());
+                let result = prepare_asset(&job);
+
+                // If the receiver is gone, the game has shut down and there is no point continuing
+                if sender.send((job.describe(), result)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver,
+            progress: LoadingProgress {
+                completed: 0,
+                total: items.len(),
+                current_item: items.first().cloned().unwrap_or_default(),
+                failed: false,
+            },
+            items,
+        }
+    }
+
+    /// Take the next prepared asset and its description, if the worker has one ready. Assets the worker could not
+    /// prepare fail loading instead of being returned.
+    ///
+    /// The caller is expected to finish loading the asset before calling this again, as it counts towards the progress.
+    pub fn try_next(&mut self) -> Option<(String, PreparedAsset)> {
+        match self.receiver.try_recv() {
+            Ok((item, result)) => {
+                self.progress.completed += 1;
+                self.progress.current_item = self
+                    .items
+                    .get(self.progress.completed)
+                    .cloned()
+                    .unwrap_or_default();
+                match result {
+                    Ok(asset) => Some((item, asset)),
+                    Err(e) => {
+                        self.fail(&item, &e);
+                        None
+                    }
+                }
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                // The worker panicked, so nothing else is coming
+                if !self.progress.is_done() && !self.progress.failed {
+                    error!("Asset loader thread exited before finishing its work");
+                    self.progress.failed = true;
+                }
+                None
+            }
+        }
+    }
+
+    /// Give up on loading, because an asset could not be loaded
+    pub fn fail(&mut self, item: &str, error: &ResourceLoadError) {
+        error!("Could not load {}: {}", item, error);
+        self.progress.failed = true;
+    }
+
+    /// Get the current loading progress
+    pub fn progress(&self) -> LoadingProgress {
+        self.progress.clone()
+    }
+}
+
+/// Does all the main-thread-independent work for a job
+fn prepare_asset(job: &AssetJob) -> Result<PreparedAsset, ResourceLoadError> {
+    puffin::profile_function!();
+    Ok(match job {
+        AssetJob::Texture(path) => PreparedAsset::Texture {
+            path: path.clone(),
+            image: decode_image_from_internal_data(path)?,
+        },
+        AssetJob::Sound { name, path } => PreparedAsset::Sound {
+            name: name.clone(),
+            file: stage_internal_file(path)?,
+        },
+        AssetJob::Music(path) => PreparedAsset::Music {
+            path: path.clone(),
+            file: stage_internal_file(path)?,
+        },
+        AssetJob::LevelMetadata => {
+            let (levels, campaigns) = load_all_levels()?;
+            PreparedAsset::LevelMetadata { levels, campaigns }
+        }
+        AssetJob::EndlessChunks => PreparedAsset::EndlessChunks(prepare_endless_catalogue()?),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_loader(
+        receiver: Receiver<(String, Result<PreparedAsset, ResourceLoadError>)>,
+    ) -> AssetLoader {
+        let items = vec!["audio/soundtrack.mp3".to_string(), "levels".to_string()];
+        AssetLoader {
+            receiver,
+            progress: LoadingProgress {
+                completed: 0,
+                total: items.len(),
+                current_item: items[0].clone(),
+                failed: false,
+            },
+            items,
+        }
+    }
+
+    #[test]
+    fn test_disconnected_worker_fails_loading() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut loader = test_loader(receiver);
+        assert!(loader.try_next().is_none());
+        assert!(!loader.progress().failed);
+
+        // Dropping the sender is what a panicking worker looks like
+        drop(sender);
+        assert!(loader.try_next().is_none());
+        assert!(loader.progress().failed);
+        assert!(!loader.progress().is_done());
+    }
+
+    #[test]
+    fn test_failed_asset_fails_loading() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut loader = test_loader(receiver);
+        sender
+            .send((
+                "audio/soundtrack.mp3".to_string(),
+                Err(ResourceLoadError::AssetNotFound(
+                    "audio/soundtrack.mp3".to_string(),
+                )),
+            ))
+            .unwrap();
+
+        // The worker is still running, but loading can never finish
+        assert!(loader.try_next().is_none());
+        assert!(loader.progress().failed);
+        assert_eq!(loader.progress().current_item, "levels");
+    }
+}
@@ -1,4 +1,4 @@
-use raylib::audio::RaylibAudio;
+use raylib::audio::{Music, RaylibAudio, Sound};
 
 /// A thin wrapper around `raylib::core::audio::RaylibAudio` that keeps track of the volume of its audio channels.
 #[derive(Debug)]
@@ -7,6 +7,8 @@ pub struct AudioPlayer {
 
     // Volume
     pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
 }
 
 impl AudioPlayer {
@@ -15,6 +17,8 @@ impl AudioPlayer {
         Self {
             backend,
             master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
         }
     }
 
@@ -32,6 +36,30 @@ impl AudioPlayer {
     pub fn get_master_volume(&self) -> f32 {
         self.master_volume
     }
+
+    /// Set the volume of the music channel. `0.0` to `1.0`. This is applied to the music stream every frame
+    pub fn set_music_channel_volume(&mut self, volume: f32) {
+        self.music_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Set the volume of the sound effect channel. `0.0` to `1.0`
+    pub fn set_sfx_channel_volume(&mut self, volume: f32) {
+        self.sfx_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Update a music stream and set it to the music channel's volume
+    pub fn update_music_channel(&mut self, music: &mut Music) {
+        let volume = self.music_volume;
+        self.backend.set_music_volume(music, volume);
+        self.backend.update_music_stream(music);
+    }
+
+    /// Play a sound at the sound effect channel's volume
+    pub fn play_sfx(&mut self, sound: &mut Sound) {
+        let volume = self.sfx_volume;
+        self.backend.set_sound_volume(sound, volume);
+        self.backend.play_sound(sound);
+    }
 }
 
 impl std::ops::Deref for AudioPlayer {
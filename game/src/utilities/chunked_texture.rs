@@ -0,0 +1,103 @@
+//! Defines a texture that is split into fixed-width vertical strips.
+//!
+//! Level layers can be far wider than the GPU's maximum texture size, so they are uploaded in pieces.
+//! This also lets us skip drawing any piece that is not on screen.
+//! Pieces are reference counted, so they can be shared between several textures without using more VRAM.
+
+use std::rc::Rc;
+
+use raylib::{
+    camera::Camera2D,
+    color::Color,
+    math::Vector2,
+    prelude::{RaylibDraw, RaylibMode2D},
+    texture::Texture2D,
+    RaylibHandle, RaylibThread,
+};
+
+use super::{
+    datastore::{upload_decoded_image, DecodedImage, ResourceLoadError},
+    non_ref_raylib::HackedRaylibHandle,
+};
+
+#[derive(Debug)]
+pub struct ChunkedTexture {
+    /// Each chunk, along with its offset from the top left of the full image
+    chunks: Vec<(Vector2, Rc<Texture2D>)>,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl ChunkedTexture {
+    /// Split a decoded image into chunks of at most `chunk_width` pixels, and upload each one to VRAM
+    pub fn new(
+        raylib_handle: &mut RaylibHandle,
+        thread: &RaylibThread,
+        image: &DecodedImage,
+        chunk_width: u32,
+    ) -> Result<Self, ResourceLoadError> {
+        let mut chunks = Vec::new();
+        let mut x = 0;
+        while x < image.width {
+            let chunk = image.crop_columns(x, chunk_width);
+            chunks.push((
+                Vector2::new(x as f32, 0.0),
+                Rc::new(upload_decoded_image(raylib_handle, thread, &chunk)?),
+            ));
+            x += chunk_width;
+        }
+
+        Ok(Self {
+            chunks,
+            width: image.width as i32,
+            height: image.height as i32,
+        })
+    }
+
+    /// Construct a texture with no chunks yet
+    pub fn empty(height: i32) -> Self {
+        Self {
+            chunks: Vec::new(),
+            width: 0,
+            height,
+        }
+    }
+
+    /// Add all of another texture's chunks to this one, moved by `offset`. The chunks are shared, not copied
+    pub fn extend_from(&mut self, other: &ChunkedTexture, offset: Vector2) {
+        for (chunk_offset, texture) in &other.chunks {
+            self.chunks.push((*chunk_offset + offset, texture.clone()));
+        }
+        self.width = self.width.max((offset.x + other.width as f32) as i32);
+    }
+
+    /// Forget about any chunks that end before `x`
+    pub fn drop_chunks_before(&mut self, x: f32) {
+        self.chunks
+            .retain(|(offset, texture)| offset.x + texture.width as f32 >= x);
+    }
+
+    /// Render every chunk that overlaps the camera view, with the top left of the full image at `origin`
+    pub fn render(
+        &self,
+        raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>,
+        origin: Vector2,
+        camera: &Camera2D,
+        tint: Color,
+    ) {
+        // Convert the screen edges to world space
+        let top_left = raylib.get_screen_to_world2D(Vector2::new(0.0, 0.0), camera);
+        let bottom_right = raylib.get_screen_to_world2D(raylib.get_screen_size(), camera);
+
+        for (offset, texture) in &self.chunks {
+            let chunk_position = origin + *offset;
+            if chunk_position.x + (texture.width as f32) < top_left.x
+                || chunk_position.x > bottom_right.x
+            {
+                continue;
+            }
+
+            raylib.draw_texture_v(&**texture, chunk_position, tint);
+        }
+    }
+}
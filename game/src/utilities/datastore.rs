@@ -1,11 +1,12 @@
-use std::{io::Write, path::Path};
+use std::path::{Path, PathBuf};
 
 use raylib::{
     audio::{Music, Sound},
+    text::{Font, FontLoadEx},
     texture::Texture2D,
     RaylibHandle, RaylibThread,
 };
-use tempfile::{tempdir, NamedTempFile};
+use tempfile::{tempdir, TempDir};
 use tracing::debug;
 
 /// Contains all game assets.
@@ -26,6 +27,8 @@ pub enum ResourceLoadError {
     JsonDeser(#[from] serde_json::Error),
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
     #[error("Could not load embedded asset: {0}")]
     AssetNotFound(String),
     #[error("Generic error: {0}")]
@@ -76,11 +79,17 @@ pub fn load_texture_from_internal_data(
     Ok(texture)
 }
 
-pub fn load_music_from_internal_data(
-    raylib_handle: &mut RaylibHandle,
-    thread: &RaylibThread,
-    path: &str,
-) -> Result<Music, ResourceLoadError> {
+/// An embedded asset that has been unpacked to a real file on disk.
+///
+/// The file is deleted once this is dropped, so it must outlive anything Raylib streams from it.
+#[derive(Debug)]
+pub struct StagedFile {
+    _dir: TempDir,
+    pub path: PathBuf,
+}
+
+/// Unpacks an embedded asset to a temporary file so Raylib can read it. This does not touch the GPU or audio device.
+pub fn stage_internal_file(path: &str) -> Result<StagedFile, ResourceLoadError> {
     // Create a temp file path to work with
     let temp_dir = tempdir()?;
     debug!(
@@ -89,7 +98,7 @@ pub fn load_music_from_internal_data(
     );
     let tmp_path = temp_dir.path().join(Path::new(path).file_name().unwrap());
 
-    // Unpack the raw sound data to a real file on the local filesystem so raylib will read it correctly
+    // Unpack the raw data to a real file on the local filesystem so raylib will read it correctly
     std::fs::write(
         &tmp_path,
         &StaticGameData::get(path)
@@ -97,49 +106,117 @@ pub fn load_music_from_internal_data(
             .data,
     )?;
 
-    // Call through via FFI to re-load the file
-    let texture = Music::load_music_stream(thread, tmp_path.to_str().unwrap())
-        .map_err(ResourceLoadError::Generic)?;
+    Ok(StagedFile {
+        _dir: temp_dir,
+        path: tmp_path,
+    })
+}
 
-    // Close the file
-    debug!(
-        "Dropping temporary directory: {}",
-        temp_dir.path().display()
-    );
-    // temp_dir.close()?;
+pub fn load_music_from_staged_file(
+    thread: &RaylibThread,
+    file: &StagedFile,
+) -> Result<Music, ResourceLoadError> {
+    Music::load_music_stream(thread, file.path.to_str().unwrap())
+        .map_err(ResourceLoadError::Generic)
+}
 
-    Ok(texture)
+pub fn load_sound_from_staged_file(file: &StagedFile) -> Result<Sound, ResourceLoadError> {
+    Sound::load_sound(file.path.to_str().unwrap()).map_err(ResourceLoadError::Generic)
 }
 
-pub fn load_sound_from_internal_data(
+/// Loads an embedded font into VRAM. TTF and OTF fonts only get glyphs for the given codepoints.
+/// Image fonts (`.png`) are a row of glyphs from codepoint 32 up, separated by magenta, and ignore `font_size` and `codepoints`
+pub fn load_font_from_internal_data(
+    raylib_handle: &mut RaylibHandle,
+    thread: &RaylibThread,
     path: &str,
-) -> Result<Sound, ResourceLoadError> {
-    // Create a temp file path to work with
-    let temp_dir = tempdir()?;
-    debug!(
-        "Created temporary directory for passing embedded data to Raylib: {}",
-        temp_dir.path().display()
-    );
-    let tmp_path = temp_dir.path().join(Path::new(path).file_name().unwrap());
+    font_size: i32,
+    codepoints: &[i32],
+) -> Result<Font, ResourceLoadError> {
+    // The staged file only needs to last until raylib has read it
+    let file = stage_internal_file(path)?;
+    let file_path = file.path.to_str().unwrap();
+    if path.ends_with(".png") {
+        raylib_handle.load_font(thread, file_path)
+    } else {
+        raylib_handle.load_font_ex(thread, file_path, font_size, FontLoadEx::Chars(codepoints))
+    }
+    .map_err(ResourceLoadError::Generic)
+}
 
-    // Unpack the raw sound data to a real file on the local filesystem so raylib will read it correctly
-    std::fs::write(
-        &tmp_path,
+/// Raylib's ID for the 32-bit RGBA pixel format
+const PIXELFORMAT_UNCOMPRESSED_R8G8B8A8: i32 = 7;
+
+/// An image that has been decoded to raw RGBA pixels, and is ready to be sent to the GPU
+#[derive(Debug)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl DecodedImage {
+    /// Copy out a full-height vertical strip of this image. The strip is clamped to the image bounds
+    pub fn crop_columns(&self, x: u32, width: u32) -> DecodedImage {
+        let x = x.min(self.width);
+        let width = width.min(self.width - x);
+        let mut pixels = Vec::with_capacity((width * self.height * 4) as usize);
+        for row in 0..self.height {
+            let row_start = ((row * self.width + x) * 4) as usize;
+            pixels.extend_from_slice(&self.pixels[row_start..row_start + (width * 4) as usize]);
+        }
+        DecodedImage {
+            width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+/// Decodes an embedded image on the CPU. This is safe to call from any thread
+pub fn decode_image_from_internal_data(path: &str) -> Result<DecodedImage, ResourceLoadError> {
+    let image = image::load_from_memory(
         &StaticGameData::get(path)
             .ok_or(ResourceLoadError::AssetNotFound(path.to_string()))?
             .data,
-    )?;
-
-    // Call through via FFI to re-load the file
-    let texture =
-        Sound::load_sound(tmp_path.to_str().unwrap()).map_err(ResourceLoadError::Generic)?;
-
-    // Close the file
-    debug!(
-        "Dropping temporary directory: {}",
-        temp_dir.path().display()
-    );
-    temp_dir.close()?;
+    )?
+    .into_rgba8();
+
+    Ok(DecodedImage {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    })
+}
 
+/// Uploads a decoded image into VRAM. This must happen on the main thread
+pub fn upload_decoded_image(
+    _raylib_handle: &mut RaylibHandle,
+    _thread: &RaylibThread,
+    image: &DecodedImage,
+) -> Result<Texture2D, ResourceLoadError> {
+    puffin::profile_function!();
+
+    // Raylib only reads the pixel buffer during the upload, so it can borrow our memory instead of owning a copy.
+    // This skips a second decode on the main thread, which is the whole point of decoding ahead of time.
+    #[allow(unsafe_code)]
+    let raw_texture = unsafe {
+        raylib::ffi::LoadTextureFromImage(raylib::ffi::Image {
+            data: image.pixels.as_ptr() as *mut std::os::raw::c_void,
+            width: image.width as i32,
+            height: image.height as i32,
+            mipmaps: 1,
+            format: PIXELFORMAT_UNCOMPRESSED_R8G8B8A8,
+        })
+    };
+    if raw_texture.id == 0 {
+        return Err(ResourceLoadError::Generic(
+            "Failed to upload image to the GPU".to_string(),
+        ));
+    }
+
+    // Hand ownership of the GPU texture to a wrapper that will free it on drop
+    #[allow(unsafe_code)]
+    let texture = unsafe { Texture2D::from_raw(raw_texture) };
     Ok(texture)
 }
@@ -30,11 +30,39 @@ pub struct GameConfig {
     pub sentry_dsn: String,
     pub colors: ColorTheme,
     pub animation_fps: usize,
+    pub preload_next_level: bool,
+    #[serde(default)]
+    pub ui_font: FontConfig,
 
     #[serde(skip)]
     pub debug_view: bool
 }
 
+/// The font all UI text is drawn with
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FontConfig {
+    /// A `.ttf`, `.otf`, or image font `.png` in `assets/`. `None` uses raylib's built-in font
+    pub path: Option<String>,
+    /// The height of the font's glyphs, in font pixels. The built-in font is 10
+    pub base_size: i32,
+    /// Space between letters, in font pixels
+    pub spacing: f32,
+    /// Only draw text at whole multiples of `base_size`, so every font pixel is the same size on screen
+    pub pixel_perfect: bool,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            base_size: 10,
+            spacing: 1.0,
+            pixel_perfect: false,
+        }
+    }
+}
+
 impl GameConfig {
     /// Load from a file
     pub fn load(file: EmbeddedFile) -> Result<Self, serde_json::Error> {
@@ -57,3 +85,22 @@ impl FinalShaderConfig {
         serde_json::from_slice(&file.data)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utilities::datastore::StaticGameData;
+
+    #[test]
+    fn test_ui_font_is_shipped() {
+        let config =
+            GameConfig::load(StaticGameData::get("configs/application.json").unwrap()).unwrap();
+        if let Some(path) = config.ui_font.path {
+            assert!(
+                StaticGameData::get(&path).is_some(),
+                "missing font {}",
+                path
+            );
+        }
+    }
+}
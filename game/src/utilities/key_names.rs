@@ -0,0 +1,156 @@
+//! Human-readable names for the keys, mouse buttons, and gamepad inputs that can be bound to actions.
+//!
+//! Bindings are saved by name, so the settings file can be edited by hand.
+
+use raylib::consts::{GamepadAxis, GamepadButton, KeyboardKey, MouseButton};
+
+/// Every key that can be bound, along with its name
+pub const BINDABLE_KEYS: &[(KeyboardKey, &str)] = &[
+    (KeyboardKey::KEY_SPACE, "SPACE"),
+    (KeyboardKey::KEY_ESCAPE, "ESCAPE"),
+    (KeyboardKey::KEY_BACKSPACE, "BACKSPACE"),
+    (KeyboardKey::KEY_LEFT_SHIFT, "LEFT SHIFT"),
+    (KeyboardKey::KEY_RIGHT_SHIFT, "RIGHT SHIFT"),
+    (KeyboardKey::KEY_LEFT_CONTROL, "LEFT CTRL"),
+    (KeyboardKey::KEY_RIGHT_CONTROL, "RIGHT CTRL"),
+    (KeyboardKey::KEY_LEFT_ALT, "LEFT ALT"),
+    (KeyboardKey::KEY_RIGHT_ALT, "RIGHT ALT"),
+    (KeyboardKey::KEY_ENTER, "ENTER"),
+    (KeyboardKey::KEY_TAB, "TAB"),
+    (KeyboardKey::KEY_UP, "UP"),
+    (KeyboardKey::KEY_DOWN, "DOWN"),
+    (KeyboardKey::KEY_LEFT, "LEFT"),
+    (KeyboardKey::KEY_RIGHT, "RIGHT"),
+    (KeyboardKey::KEY_A, "A"),
+    (KeyboardKey::KEY_B, "B"),
+    (KeyboardKey::KEY_C, "C"),
+    (KeyboardKey::KEY_D, "D"),
+    (KeyboardKey::KEY_E, "E"),
+    (KeyboardKey::KEY_F, "F"),
+    (KeyboardKey::KEY_G, "G"),
+    (KeyboardKey::KEY_H, "H"),
+    (KeyboardKey::KEY_I, "I"),
+    (KeyboardKey::KEY_J, "J"),
+    (KeyboardKey::KEY_K, "K"),
+    (KeyboardKey::KEY_L, "L"),
+    (KeyboardKey::KEY_M, "M"),
+    (KeyboardKey::KEY_N, "N"),
+    (KeyboardKey::KEY_O, "O"),
+    (KeyboardKey::KEY_P, "P"),
+    (KeyboardKey::KEY_Q, "Q"),
+    (KeyboardKey::KEY_R, "R"),
+    (KeyboardKey::KEY_S, "S"),
+    (KeyboardKey::KEY_T, "T"),
+    (KeyboardKey::KEY_U, "U"),
+    (KeyboardKey::KEY_V, "V"),
+    (KeyboardKey::KEY_W, "W"),
+    (KeyboardKey::KEY_X, "X"),
+    (KeyboardKey::KEY_Y, "Y"),
+    (KeyboardKey::KEY_Z, "Z"),
+    (KeyboardKey::KEY_ZERO, "0"),
+    (KeyboardKey::KEY_ONE, "1"),
+    (KeyboardKey::KEY_TWO, "2"),
+    (KeyboardKey::KEY_THREE, "3"),
+    (KeyboardKey::KEY_FOUR, "4"),
+    (KeyboardKey::KEY_FIVE, "5"),
+    (KeyboardKey::KEY_SIX, "6"),
+    (KeyboardKey::KEY_SEVEN, "7"),
+    (KeyboardKey::KEY_EIGHT, "8"),
+    (KeyboardKey::KEY_NINE, "9"),
+    (KeyboardKey::KEY_F1, "F1"),
+    (KeyboardKey::KEY_F2, "F2"),
+    (KeyboardKey::KEY_F3, "F3"),
+    (KeyboardKey::KEY_F4, "F4"),
+    (KeyboardKey::KEY_F5, "F5"),
+    (KeyboardKey::KEY_F6, "F6"),
+    (KeyboardKey::KEY_F7, "F7"),
+    (KeyboardKey::KEY_F8, "F8"),
+    (KeyboardKey::KEY_F9, "F9"),
+    (KeyboardKey::KEY_F10, "F10"),
+    (KeyboardKey::KEY_F11, "F11"),
+    (KeyboardKey::KEY_F12, "F12"),
+];
+
+/// Every mouse button that can be bound, along with its name
+pub const BINDABLE_MOUSE_BUTTONS: &[(MouseButton, &str)] = &[
+    (MouseButton::MOUSE_LEFT_BUTTON, "LEFT CLICK"),
+    (MouseButton::MOUSE_RIGHT_BUTTON, "RIGHT CLICK"),
+    (MouseButton::MOUSE_MIDDLE_BUTTON, "MIDDLE CLICK"),
+];
+
+/// Every gamepad button that can be bound, along with its name. Face buttons are named like an Xbox controller
+pub const BINDABLE_GAMEPAD_BUTTONS: &[(GamepadButton, &str)] = &[
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP, "PAD UP"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN, "PAD DOWN"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT, "PAD LEFT"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT, "PAD RIGHT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN, "PAD A"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT, "PAD B"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT, "PAD X"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP, "PAD Y"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1, "PAD LB"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1, "PAD RB"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2, "PAD LT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2, "PAD RT"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT, "PAD SELECT"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT, "PAD START"),
+];
+
+/// Every gamepad axis that can be bound, along with its name
+pub const BINDABLE_GAMEPAD_AXES: &[(GamepadAxis, &str)] = &[
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_X, "LEFT STICK X"),
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_Y, "LEFT STICK Y"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_X, "RIGHT STICK X"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_Y, "RIGHT STICK Y"),
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER, "LEFT TRIGGER"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER, "RIGHT TRIGGER"),
+];
+
+/// Look up the name of a value in one of the tables above
+pub fn name_in<T: PartialEq + Copy>(table: &[(T, &'static str)], value: T) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(bindable, _)| *bindable == value)
+        .map(|(_, name)| *name)
+}
+
+/// Look up a value by its name in one of the tables above
+pub fn find_by_name<T: Copy>(table: &[(T, &'static str)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(_, bindable_name)| *bindable_name == name)
+        .map(|(value, _)| *value)
+}
+
+/// Get the name of a key. Returns `None` if the key can not be bound
+pub fn key_name(key: KeyboardKey) -> Option<&'static str> {
+    name_in(BINDABLE_KEYS, key)
+}
+
+/// Find a key by its name
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    find_by_name(BINDABLE_KEYS, name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_names_round_trip() {
+        for (key, name) in BINDABLE_KEYS {
+            assert_eq!(key_from_name(name), Some(*key));
+            assert_eq!(key_name(*key), Some(*name));
+        }
+    }
+
+    #[test]
+    fn test_gamepad_names_round_trip() {
+        for (button, name) in BINDABLE_GAMEPAD_BUTTONS {
+            assert_eq!(find_by_name(BINDABLE_GAMEPAD_BUTTONS, name), Some(*button));
+        }
+        for (axis, name) in BINDABLE_GAMEPAD_AXES {
+            assert_eq!(find_by_name(BINDABLE_GAMEPAD_AXES, name), Some(*axis));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Resolution independent placement for UI.
+//!
+//! Menus are laid out against the design resolution, which is `base_window_size` from `application.json`. Positions
+//! are an [`Anchor`] on the screen plus an offset in design pixels, and everything is scaled so the design resolution
+//! fits in the window. Font sizes given to the renderer's UI text functions are in design pixels too, so text scales
+//! along with everything else.
+
+use raylib::math::{Rectangle, Vector2};
+
+use super::text::TextAlign;
+
+/// A point on the screen, or on a rectangle, that things are placed relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Where the anchor is, from `(0.0, 0.0)` at the top left to `(1.0, 1.0)` at the bottom right
+    pub fn fraction(self) -> Vector2 {
+        match self {
+            Self::TopLeft => Vector2::new(0.0, 0.0),
+            Self::Top => Vector2::new(0.5, 0.0),
+            Self::TopRight => Vector2::new(1.0, 0.0),
+            Self::Left => Vector2::new(0.0, 0.5),
+            Self::Center => Vector2::new(0.5, 0.5),
+            Self::Right => Vector2::new(1.0, 0.5),
+            Self::BottomLeft => Vector2::new(0.0, 1.0),
+            Self::Bottom => Vector2::new(0.5, 1.0),
+            Self::BottomRight => Vector2::new(1.0, 1.0),
+        }
+    }
+
+    /// How text placed at this anchor lines up, so it grows away from the edge it is anchored to
+    pub fn text_align(self) -> TextAlign {
+        match self {
+            Self::TopLeft | Self::Left | Self::BottomLeft => TextAlign::Left,
+            Self::Top | Self::Center | Self::Bottom => TextAlign::Center,
+            Self::TopRight | Self::Right | Self::BottomRight => TextAlign::Right,
+        }
+    }
+}
+
+/// Converts design pixels to screen pixels for one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    screen_size: Vector2,
+    scale: f32,
+}
+
+impl Layout {
+    /// Fit the design resolution in the screen, then scale by the player's UI scale
+    pub fn new(screen_size: Vector2, design_size: Vector2, ui_scale: f32) -> Self {
+        let fit = (screen_size.x / design_size.x).min(screen_size.y / design_size.y);
+        Self {
+            screen_size,
+            // A minimized window has no size at all
+            scale: (fit * ui_scale).max(0.1),
+        }
+    }
+
+    /// How many screen pixels one design pixel covers
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn screen_size(&self) -> Vector2 {
+        self.screen_size
+    }
+
+    /// Scale a length from design pixels to screen pixels
+    pub fn scaled(&self, length: f32) -> f32 {
+        length * self.scale
+    }
+
+    /// A point `offset` design pixels away from an anchor on the screen. Positive offsets go right and down
+    pub fn at(&self, anchor: Anchor, offset: Vector2) -> Vector2 {
+        let fraction = anchor.fraction();
+        Vector2::new(
+            (self.screen_size.x * fraction.x + offset.x * self.scale).round(),
+            (self.screen_size.y * fraction.y + offset.y * self.scale).round(),
+        )
+    }
+
+    /// A size relative to the screen, like `(0.5, 1.0)` for half the width and all of the height
+    pub fn relative(&self, fraction: Vector2) -> Vector2 {
+        Vector2::new(
+            self.screen_size.x * fraction.x,
+            self.screen_size.y * fraction.y,
+        )
+    }
+
+    /// A rectangle of `size` design pixels, placed so the same anchor on the rectangle sits at [`Layout::at`].
+    /// A centered rectangle stays centered, and a bottom right one stays in the corner
+    pub fn rect(&self, anchor: Anchor, offset: Vector2, size: Vector2) -> Rectangle {
+        let position = self.at(anchor, offset);
+        let fraction = anchor.fraction();
+        let size = Vector2::new(size.x * self.scale, size.y * self.scale);
+        Rectangle::new(
+            (position.x - size.x * fraction.x).round(),
+            (position.y - size.y * fraction.y).round(),
+            size.x.round(),
+            size.y.round(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DESIGN_SIZE: Vector2 = Vector2 {
+        x: 1080.0,
+        y: 720.0,
+    };
+
+    #[test]
+    fn test_layout_fits_the_design_resolution() {
+        assert_relative_eq!(
+            Layout::new(Vector2::new(1080.0, 720.0), DESIGN_SIZE, 1.0).scale(),
+            1.0
+        );
+        assert_relative_eq!(
+            Layout::new(Vector2::new(1920.0, 1080.0), DESIGN_SIZE, 1.0).scale(),
+            1.5
+        );
+        assert_relative_eq!(
+            Layout::new(Vector2::new(3840.0, 2160.0), DESIGN_SIZE, 1.0).scale(),
+            3.0
+        );
+
+        // Tall windows are limited by their width instead
+        assert_relative_eq!(
+            Layout::new(Vector2::new(540.0, 1000.0), DESIGN_SIZE, 1.25).scale(),
+            0.625
+        );
+    }
+
+    #[test]
+    fn test_anchors() {
+        let layout = Layout::new(Vector2::new(1920.0, 1080.0), DESIGN_SIZE, 1.0);
+        assert_eq!(
+            layout.at(Anchor::TopLeft, Vector2::new(40.0, 80.0)),
+            Vector2::new(60.0, 120.0)
+        );
+        assert_eq!(
+            layout.at(Anchor::BottomRight, Vector2::new(-20.0, -10.0)),
+            Vector2::new(1890.0, 1065.0)
+        );
+        assert_eq!(
+            layout.at(Anchor::Center, Vector2::zero()),
+            Vector2::new(960.0, 540.0)
+        );
+    }
+
+    #[test]
+    fn test_rect_keeps_its_anchor() {
+        let layout = Layout::new(Vector2::new(1920.0, 1080.0), DESIGN_SIZE, 1.0);
+        let centered = layout.rect(Anchor::Center, Vector2::zero(), Vector2::new(500.0, 220.0));
+        assert_relative_eq!(centered.x, 585.0);
+        assert_relative_eq!(centered.y, 375.0);
+        assert_relative_eq!(centered.width, 750.0);
+        assert_relative_eq!(centered.height, 330.0);
+
+        let corner = layout.rect(
+            Anchor::BottomRight,
+            Vector2::new(-10.0, -10.0),
+            Vector2::new(100.0, 20.0),
+        );
+        assert_relative_eq!(corner.x + corner.width, 1905.0);
+        assert_relative_eq!(corner.y + corner.height, 1065.0);
+    }
+}
@@ -0,0 +1,97 @@
+//! Watches the level files on disk so they can be reloaded while the game is running.
+//!
+//! This only works in debug builds, where `StaticGameData` reads straight from the `assets` directory.
+
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+    sync::mpsc::{Receiver, TryRecvError},
+    time::Duration,
+};
+
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{debug, error};
+
+/// How long to wait for a burst of file changes to settle. Image editors tend to write files in several steps
+const DEBOUNCE_TIME: Duration = Duration::from_millis(500);
+
+pub struct LevelWatcher {
+    levels_dir: PathBuf,
+    receiver: Receiver<DebouncedEvent>,
+    // The watcher stops when dropped, so it must be kept around
+    _watcher: RecommendedWatcher,
+}
+
+impl std::fmt::Debug for LevelWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LevelWatcher")
+            .field("levels_dir", &self.levels_dir)
+            .finish()
+    }
+}
+
+impl LevelWatcher {
+    /// Start watching the `assets/levels` directory
+    pub fn new() -> Result<Self, notify::Error> {
+        let levels_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/levels");
+
+        // Event paths are absolute, so make sure ours is too
+        let levels_dir = levels_dir.canonicalize().unwrap_or(levels_dir);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut watcher = watcher(sender, DEBOUNCE_TIME)?;
+        watcher.watch(&levels_dir, RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            levels_dir,
+            receiver,
+            _watcher: watcher,
+        })
+    }
+
+    /// Get the names of every level that has changed since the last call
+    pub fn changed_levels(&self) -> HashSet<String> {
+        let mut changed = HashSet::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => {
+                    let paths = match event {
+                        DebouncedEvent::Create(path)
+                        | DebouncedEvent::Write(path)
+                        | DebouncedEvent::Remove(path) => vec![path],
+                        DebouncedEvent::Rename(from, to) => vec![from, to],
+                        DebouncedEvent::Error(e, path) => {
+                            error!("Level watcher error on {:?}: {}", path, e);
+                            continue;
+                        }
+                        _ => continue,
+                    };
+
+                    for path in paths {
+                        debug!("Level file changed: {}", path.display());
+                        if let Some(level_name) = self.level_name_for(&path) {
+                            changed.insert(level_name);
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    error!("Level watcher stopped unexpectedly");
+                    break;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Find which level a file belongs to. Files directly inside `levels`, like `levels.json`, do not belong to one
+    fn level_name_for(&self, path: &Path) -> Option<String> {
+        let mut components = path.strip_prefix(&self.levels_dir).ok()?.components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(level_name)), Some(_)) => {
+                Some(level_name.to_string_lossy().to_string())
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,12 +1,20 @@
 pub mod anim_render;
+pub mod asset_loader;
+pub mod chunked_texture;
 pub mod datastore;
 pub mod discord;
 pub mod game_config;
 pub mod game_version;
+pub mod key_names;
+pub mod layout;
+#[cfg(all(debug_assertions, feature = "hot-reload"))]
+pub mod level_watcher;
 pub mod math;
 pub mod non_ref_raylib;
 pub mod render_layer;
+pub mod seeded_rng;
 pub mod shaders;
+pub mod text;
 pub mod world_paint_texture;
 pub mod audio_player;
-pub mod reusable_button;
+pub mod widgets;
@@ -3,10 +3,157 @@ use std::ops::{Deref, DerefMut};
 use raylib::prelude::*;
 use raylib::{math::Vector2, prelude::RaylibDraw, RaylibHandle};
 
+use super::{
+    game_config::FontConfig,
+    layout::Layout,
+    text::{pixel_perfect_size, wrap_text, TextAlign},
+};
+
 #[derive(Debug)]
-pub struct HackedRaylibHandle(RaylibHandle);
+pub struct HackedRaylibHandle {
+    /// Used for all UI text when the current language needs more than the UI font can draw.
+    /// Fonts are declared first so they are unloaded before the window is closed
+    locale_font: Option<Font>,
+    /// The game's own font. `None` uses raylib's built-in font
+    ui_font: Option<Font>,
+    ui_font_config: FontConfig,
+    /// The resolution UI is laid out for, and the player's UI scale on top of fitting it to the window
+    ui_design_size: Vector2,
+    ui_scale: f32,
+    handle: RaylibHandle,
+}
 
 impl HackedRaylibHandle {
+    /// Switch the font used for UI text in languages that need their own. `None` goes back to the UI font
+    pub fn set_locale_font(&mut self, font: Option<Font>) {
+        self.locale_font = font;
+    }
+
+    /// Switch the game's UI font. `font` must be `None` if the config has no path, so the built-in font is used
+    pub fn set_ui_font(&mut self, font: Option<Font>, config: FontConfig) {
+        self.ui_font = font;
+        self.ui_font_config = config;
+    }
+
+    /// Set what UI is laid out against. See [`Layout`]
+    pub fn set_ui_layout(&mut self, design_size: Vector2, ui_scale: f32) {
+        self.ui_design_size = design_size;
+        self.ui_scale = ui_scale;
+    }
+
+    /// The UI layout for the current window size
+    pub fn layout(&self) -> Layout {
+        Layout::new(self.get_screen_size(), self.ui_design_size, self.ui_scale)
+    }
+
+    /// The size and letter spacing UI text is really drawn at, for the font in use.
+    /// `font_size` is in design pixels, and the result is in screen pixels
+    fn ui_text_metrics(&self, font_size: i32) -> (f32, f32) {
+        let font_size = self.layout().scaled(font_size as f32).round() as i32;
+        if self.locale_font.is_some() {
+            // Language fonts are smooth, so they can be drawn at any size
+            return (font_size as f32, font_size as f32 / 10.0);
+        }
+        let config = &self.ui_font_config;
+        let size = if config.pixel_perfect {
+            pixel_perfect_size(font_size, config.base_size)
+        } else {
+            font_size
+        };
+        (
+            size as f32,
+            config.spacing * size as f32 / config.base_size as f32,
+        )
+    }
+
+    /// How many screen pixels one of the font's pixels covers at a size. Smooth fonts count as one
+    pub fn ui_font_pixel(&self, font_size: i32) -> f32 {
+        if self.locale_font.is_some() {
+            return 1.0;
+        }
+        (self.ui_text_metrics(font_size).0 / self.ui_font_config.base_size as f32)
+            .round()
+            .max(1.0)
+    }
+
+    /// Draw UI text with the current font. Lines are separated by `\n`.
+    /// The position is in screen pixels, and the font size is in design pixels so text scales with the window
+    pub fn draw_ui_text(&mut self, text: &str, position: Vector2, font_size: i32, color: Color) {
+        let (size, spacing) = self.ui_text_metrics(font_size);
+
+        // Anything between whole pixels gets blurred
+        let position = Vector2::new(position.x.round(), position.y.round());
+        if let Some(font) = self.locale_font.take() {
+            self.draw_text_ex(&font, text, position, size, spacing, color);
+            self.locale_font = Some(font);
+        } else if let Some(font) = self.ui_font.take() {
+            self.draw_text_ex(&font, text, position, size, spacing, color);
+            self.ui_font = Some(font);
+        } else {
+            let font = self.get_font_default();
+            self.draw_text_ex(font, text, position, size, spacing, color);
+        }
+    }
+
+    /// Draw UI text lined up against `position`. Each line is aligned on its own
+    pub fn draw_ui_text_aligned(
+        &mut self,
+        text: &str,
+        position: Vector2,
+        font_size: i32,
+        align: TextAlign,
+        color: Color,
+    ) {
+        let line_height = self.ui_line_height(font_size);
+        for (idx, line) in text.split('\n').enumerate() {
+            let line_position = self.aligned_ui_text_position(
+                line,
+                Vector2::new(position.x, position.y + line_height * idx as f32),
+                font_size,
+                align,
+            );
+            self.draw_ui_text(line, line_position, font_size, color);
+        }
+    }
+
+    /// Measure the width and height of UI text drawn with [`HackedRaylibHandle::draw_ui_text`]
+    pub fn measure_ui_text_ex(&self, text: &str, font_size: i32) -> Vector2 {
+        let (size, spacing) = self.ui_text_metrics(font_size);
+        match (&self.locale_font, &self.ui_font) {
+            (Some(font), _) | (None, Some(font)) => measure_text_ex(font, text, size, spacing),
+            (None, None) => measure_text_ex(self.get_font_default(), text, size, spacing),
+        }
+    }
+
+    /// Measure the width of UI text drawn with [`HackedRaylibHandle::draw_ui_text`]
+    pub fn measure_ui_text(&self, text: &str, font_size: i32) -> i32 {
+        self.measure_ui_text_ex(text, font_size).x as i32
+    }
+
+    /// The distance between lines of UI text. Raylib puts half a line of space between them
+    pub fn ui_line_height(&self, font_size: i32) -> f32 {
+        (self.ui_text_metrics(font_size).0 * 1.5).round()
+    }
+
+    /// Where to draw a single line of UI text so it lines up against `position`
+    pub fn aligned_ui_text_position(
+        &self,
+        text: &str,
+        position: Vector2,
+        font_size: i32,
+        align: TextAlign,
+    ) -> Vector2 {
+        let width = self.measure_ui_text_ex(text, font_size).x;
+        Vector2::new(position.x - align.offset(width), position.y)
+    }
+
+    /// Break UI text into lines that fit in `max_width` screen pixels
+    pub fn wrap_ui_text(&self, text: &str, font_size: i32, max_width: f32) -> String {
+        wrap_text(text, max_width, |line| {
+            self.measure_ui_text_ex(line, font_size).x
+        })
+    }
+
     /// Get the screen size as a vector
     #[inline]
     pub fn get_screen_size(&self) -> Vector2 {
@@ -25,22 +172,26 @@ impl HackedRaylibHandle {
         hovering: bool,
         color: Color,
     ) {
-        let extra_smudge = if hovering { 2 } else { 0 };
-        self.draw_text(
+        // The colors are split by one of the font's pixels, so the effect looks the same with any font
+        let smudge = if hovering {
+            self.layout().scaled(2.0)
+        } else {
+            0.0
+        };
+        let split = self.ui_font_pixel(font_size) + smudge;
+        self.draw_ui_text(
             text,
-            position.x as i32 - 1 - extra_smudge,
-            position.y as i32,
+            Vector2::new(position.x - split, position.y),
             font_size,
             Color::BLUE,
         );
-        self.draw_text(
+        self.draw_ui_text(
             text,
-            position.x as i32 + 1 + extra_smudge,
-            position.y as i32,
+            Vector2::new(position.x + split, position.y),
             font_size,
             Color::RED,
         );
-        self.draw_text(text, position.x as i32, position.y as i32, font_size, color);
+        self.draw_ui_text(text, position, font_size, color);
     }
 }
 
@@ -48,7 +199,17 @@ impl RaylibDraw for HackedRaylibHandle {}
 
 impl From<RaylibHandle> for HackedRaylibHandle {
     fn from(handle: RaylibHandle) -> Self {
-        Self(handle)
+        Self {
+            locale_font: None,
+            ui_font: None,
+            ui_font_config: FontConfig::default(),
+            ui_design_size: Vector2::new(
+                handle.get_screen_width() as f32,
+                handle.get_screen_height() as f32,
+            ),
+            ui_scale: 1.0,
+            handle,
+        }
     }
 }
 
@@ -56,12 +217,12 @@ impl Deref for HackedRaylibHandle {
     type Target = RaylibHandle;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.handle
     }
 }
 
 impl DerefMut for HackedRaylibHandle {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.handle
     }
 }
@@ -3,7 +3,7 @@ use raylib::{prelude::RaylibMode2D, RaylibHandle};
 use crate::{GameConfig, context::GameContext, utilities::non_ref_raylib::HackedRaylibHandle};
 
 pub trait FrameUpdate {
-    fn update(&mut self, raylib: &HackedRaylibHandle, delta_seconds: &chrono::Duration, config: &GameConfig);
+    fn update(&mut self, raylib: &HackedRaylibHandle, delta_seconds: &chrono::Duration, context: &GameContext);
 }
 
 pub trait ScreenSpaceRender {
@@ -11,5 +11,5 @@ pub trait ScreenSpaceRender {
 }
 
 pub trait WorldSpaceRender {
-    fn render_world_space(&mut self, raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>, config: &GameConfig);
+    fn render_world_space(&mut self, raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>, context: &GameContext);
 }
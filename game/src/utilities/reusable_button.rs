@@ -1,20 +0,0 @@
-// use raylib::prelude::*;
-
-
-// #[derive(Debug)]
-// pub struct ReusableButton {
-//     text: String,
-//     position: Vector2,
-//     font_size: f32,
-//     font_color: Color,
-//     arrow_on_hover: bool
-// }
-
-// impl ReusableButton {
-//     /// Construct a new reusable button.
-//     pub fn new() -> Self {
-//         Self {
-
-//         }
-//     }
-// }
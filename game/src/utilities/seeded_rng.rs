@@ -0,0 +1,61 @@
+//! A small deterministic random number generator.
+//!
+//! Anything generated from a seed (like endless runs) must come out the same on every machine and every build,
+//! so this is implemented here instead of relying on an external crate's algorithm staying the same.
+
+/// A SplitMix64 generator
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Construct a new generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Get the next random 64-bit value
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Get a random index in `0..len`. `len` must not be zero
+    pub fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let mut a = SeededRng::new(49);
+        let mut b = SeededRng::new(49);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        // Reference values for SplitMix64 with a seed of 0. If these change, every seeded run changes too
+        let mut rng = SeededRng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn test_next_index_in_range() {
+        let mut rng = SeededRng::new(1234);
+        for len in 1..20 {
+            assert!(rng.next_index(len) < len);
+        }
+    }
+}
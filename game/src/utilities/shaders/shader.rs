@@ -30,6 +30,8 @@ pub enum ShaderError {
 pub struct ShaderWrapper {
     shader: Shader,
     variables: HashMap<String, i32>,
+    /// Extra textures to bind whenever the shader is used, by variable location
+    textures: HashMap<i32, raylib::ffi::Texture>,
 }
 
 impl ShaderWrapper {
@@ -77,7 +79,11 @@ impl ShaderWrapper {
             });
         }
 
-        Ok(Self { shader, variables })
+        Ok(Self {
+            shader,
+            variables,
+            textures: HashMap::new(),
+        })
     }
 
     /// Handles rendering a texture to the screen via the shader. If run inside another shader context, this *should* chain with it.
@@ -92,6 +98,14 @@ impl ShaderWrapper {
         // Create a shader context to work under
         let mut shader_context = raylib.begin_shader_mode(&self.shader);
 
+        // Textures only stay bound for a single draw, so they are bound again every time
+        for (location, texture) in &self.textures {
+            #[allow(unsafe_code)]
+            unsafe {
+                raylib::ffi::SetShaderValueTexture(*self.shader, *location, *texture);
+            }
+        }
+
         // Blit the texture
         shader_context.draw_texture_pro(
             &texture,
@@ -126,6 +140,20 @@ impl ShaderWrapper {
             Err(ShaderError::ShaderVarName(name.to_string()))
         }
     }
+
+    /// Set a texture variable in the shader. The texture must be set again if it is ever reloaded
+    pub fn set_texture(
+        &mut self,
+        name: &str,
+        texture: &RenderTexture2D,
+    ) -> Result<(), ShaderError> {
+        if let Some(ptr) = self.variables.get(name) {
+            self.textures.insert(*ptr, texture.texture);
+            Ok(())
+        } else {
+            Err(ShaderError::ShaderVarName(name.to_string()))
+        }
+    }
 }
 
 /// Too lazy to write this upstream
@@ -1,4 +1,8 @@
-use raylib::ffi::RenderTexture;
+use raylib::{
+    ffi::RenderTexture,
+    prelude::{Color, RaylibDraw, RaylibTexture2D, Rectangle, Vector2},
+    texture::RenderTexture2D,
+};
 
 /// Renders everything in the draw function to a texture
 #[allow(unsafe_code)]
@@ -12,3 +16,23 @@ pub fn render_to_texture<Func>(texture: &mut RenderTexture, draw_fn: Func) where
         raylib::ffi::EndTextureMode();
     }
 }
+
+/// Draws a render texture over the whole screen. Render textures are stored upside down, so this flips it back
+pub fn draw_render_texture<D>(raylib: &mut D, texture: &RenderTexture2D)
+where
+    D: RaylibDraw,
+{
+    raylib.draw_texture_pro(
+        texture,
+        Rectangle::new(
+            0.0,
+            0.0,
+            texture.width() as f32,
+            -(texture.height() as f32),
+        ),
+        Rectangle::new(0.0, 0.0, texture.width() as f32, texture.height() as f32),
+        Vector2::zero(),
+        0.0,
+        Color::WHITE,
+    );
+}
@@ -0,0 +1,92 @@
+//! Layout helpers for UI text. Drawing and measuring happen on [`HackedRaylibHandle`](super::non_ref_raylib::HackedRaylibHandle),
+//! which knows what font is in use.
+
+/// Which side of the drawn position text lines up against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    /// How far left of the drawn position a line of some width starts
+    pub fn offset(self, width: f32) -> f32 {
+        match self {
+            Self::Left => 0.0,
+            Self::Center => (width / 2.0).round(),
+            Self::Right => width,
+        }
+    }
+}
+
+/// The size pixel-perfect text is really drawn at. This is the nearest whole multiple of the font's base size,
+/// and never smaller than the base size, so every font pixel covers the same number of screen pixels
+pub fn pixel_perfect_size(font_size: i32, base_size: i32) -> i32 {
+    let scale = (font_size as f32 / base_size as f32).round() as i32;
+    scale.max(1) * base_size
+}
+
+/// Break text into lines no wider than `max_width`, as measured by `measure`.
+/// Lines only break between words, so a word wider than `max_width` gets a line to itself. Existing line breaks are kept
+pub fn wrap_text(text: &str, max_width: f32, measure: impl Fn(&str) -> f32) -> String {
+    text.split('\n')
+        .map(|paragraph| {
+            let mut lines: Vec<String> = Vec::new();
+            let mut line = String::new();
+            for word in paragraph.split(' ') {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if !line.is_empty() && measure(&candidate) > max_width {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+            lines.push(line);
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pixel_perfect_size() {
+        assert_eq!(pixel_perfect_size(20, 10), 20);
+        assert_eq!(pixel_perfect_size(25, 10), 30);
+        assert_eq!(pixel_perfect_size(18, 10), 20);
+        assert_eq!(pixel_perfect_size(4, 10), 10);
+        assert_eq!(pixel_perfect_size(70, 16), 64);
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        // Every letter is 10 wide, spaces included
+        let measure = |line: &str| line.chars().count() as f32 * 10.0;
+        assert_eq!(
+            wrap_text("the quick brown fox", 100.0, measure),
+            "the quick\nbrown fox"
+        );
+
+        // Long words are not split, and existing breaks stay
+        assert_eq!(
+            wrap_text("a supercalifragilistic b\nc d", 100.0, measure),
+            "a\nsupercalifragilistic\nb\nc d"
+        );
+        assert_eq!(wrap_text("", 100.0, measure), "");
+    }
+
+    #[test]
+    fn test_align_offset() {
+        assert_relative_eq!(TextAlign::Left.offset(101.0), 0.0);
+        assert_relative_eq!(TextAlign::Center.offset(101.0), 51.0);
+        assert_relative_eq!(TextAlign::Right.offset(101.0), 101.0);
+    }
+}
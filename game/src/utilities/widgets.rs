@@ -0,0 +1,506 @@
+//! A small immediate-mode widget layer for menus, drawn in the game's rgb-split style.
+//!
+//! Widgets are drawn and checked for input in the same call, every frame. Screens keep a [`UiState`]
+//! around between frames, call [`UiState::read_input`] from `execute`, draw with the [`Ui`] returned by
+//! [`UiState::begin`], and call [`UiState::send_click_sound`] so every widget sounds the same when used.
+//!
+//! Every interactive widget can also be focused and used from the keyboard or a gamepad, so menus never need the mouse.
+//!
+//! Widgets are placed at screen positions, usually from [`Ui::at`], and everything else about them is in design pixels
+//! and scaled by the [`Layout`], so hit boxes always cover what is drawn.
+
+use raylib::prelude::*;
+
+use crate::{
+    context::{ControlFlag, GameContext},
+    input::InputAction,
+};
+
+use super::{
+    layout::{Anchor, Layout},
+    non_ref_raylib::HackedRaylibHandle,
+    text::TextAlign,
+};
+
+/// How far right of a setting's label its value is drawn, in design pixels
+pub const SETTING_VALUE_OFFSET: f32 = 345.0;
+
+/// Width of the area a setting's value is drawn in
+const SETTING_VALUE_WIDTH: f32 = 290.0;
+
+const SETTING_FONT_SIZE: i32 = 25;
+
+/// Width of a slider's bar
+const SLIDER_WIDTH: f32 = 150.0;
+
+/// Menu actions pressed this frame, read from [`Input`] before drawing
+#[derive(Debug, Default, Clone, Copy)]
+struct NavInput {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    confirm: bool,
+    back: bool,
+}
+
+/// Move focus up or down through `count` widgets, wrapping around at the ends.
+/// With nothing focused, moving down starts at the top and moving up starts at the bottom
+fn move_focus(focused: Option<usize>, count: usize, step: i32) -> Option<usize> {
+    if count == 0 || step == 0 {
+        return focused;
+    }
+    Some(match focused {
+        Some(idx) => (idx as i32 + step).rem_euclid(count as i32) as usize,
+        None if step > 0 => 0,
+        None => count - 1,
+    })
+}
+
+/// Widget state that needs to be kept between frames
+#[derive(Debug, Default)]
+pub struct UiState {
+    /// The focused widget, by the order widgets are drawn in
+    focused: Option<usize>,
+    /// Where the mouse was last frame, to tell if it has moved. `None` counts as not moving
+    last_mouse_position: Option<Vector2>,
+    /// How many interactive widgets were drawn last frame
+    widget_count: usize,
+    /// Set when a widget is used, until the click sound is played
+    clicked: bool,
+    /// Set to ignore all input for one frame
+    input_blocked: bool,
+    /// Menu actions for the next frame's widgets
+    nav: NavInput,
+    /// What toggles show for on and off, in the current language
+    toggle_text: (String, String),
+}
+
+impl UiState {
+    /// Check the menu actions for this frame. Must be called from `execute` before drawing
+    pub fn read_input(&mut self, context: &GameContext) {
+        let input = &context.input;
+        // The mouse is not an action, so it has to be blocked separately
+        if input.is_blocked() {
+            self.block_input_for_frame();
+        }
+
+        self.nav = NavInput {
+            up: input.is_pressed(InputAction::MenuUp),
+            down: input.is_pressed(InputAction::MenuDown),
+            left: input.is_pressed(InputAction::MenuLeft),
+            right: input.is_pressed(InputAction::MenuRight),
+            confirm: input.is_pressed(InputAction::Confirm),
+            back: input.is_pressed(InputAction::Back),
+        };
+        self.toggle_text = (
+            context.locale.text("ui.on").to_string(),
+            context.locale.text("ui.off").to_string(),
+        );
+    }
+
+    /// Start drawing widgets for this frame
+    pub fn begin<'a>(&'a mut self, raylib: &'a mut HackedRaylibHandle) -> Ui<'a> {
+        let layout = raylib.layout();
+        let mouse_position = raylib.get_mouse_position();
+        let mouse_moved = self
+            .last_mouse_position
+            .map_or(false, |last| last != mouse_position);
+        self.last_mouse_position = Some(mouse_position);
+
+        // Moving the mouse off of a widget takes focus away from it
+        if mouse_moved {
+            self.focused = None;
+        }
+
+        let input_enabled = !std::mem::take(&mut self.input_blocked);
+        let nav = std::mem::take(&mut self.nav);
+        let pressed = |action_pressed: bool| input_enabled && action_pressed;
+
+        // Move focus with the keyboard or gamepad, using the widgets from last frame
+        let vertical_step = pressed(nav.down) as i32 - pressed(nav.up) as i32;
+        self.focused = move_focus(self.focused, self.widget_count, vertical_step);
+        self.widget_count = 0;
+
+        Ui {
+            mouse_pressed: input_enabled
+                && raylib.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON),
+            horizontal_step: pressed(nav.right) as i32 - pressed(nav.left) as i32,
+            confirm_pressed: pressed(nav.confirm),
+            back_pressed: pressed(nav.back),
+            raylib,
+            state: self,
+            layout,
+            mouse_position,
+            mouse_moved,
+            next_id: 0,
+            text_color: Color::WHITE,
+        }
+    }
+
+    /// Play the button sound if any widget was used since the last call. Should be called every frame
+    pub fn send_click_sound(&mut self, context: &GameContext) {
+        if std::mem::take(&mut self.clicked) {
+            context
+                .flag_send
+                .send(Some(ControlFlag::SoundTrigger("button-press".to_string())))
+                .unwrap();
+        }
+    }
+
+    /// Focus the first widget, for screens with one obvious choice. It keeps focus until the mouse moves
+    pub fn focus_first(&mut self) {
+        self.focused = Some(0);
+        self.last_mouse_position = None;
+    }
+
+    /// Forget which widget has focus, for when a screen is left
+    pub fn reset(&mut self) {
+        self.focused = None;
+        self.clicked = false;
+    }
+
+    /// Ignore all input next frame, for when the keys pressed are meant for something else
+    pub fn block_input_for_frame(&mut self) {
+        self.input_blocked = true;
+    }
+}
+
+/// Draws widgets for a single frame
+pub struct Ui<'a> {
+    /// For anything the widgets can not draw
+    pub raylib: &'a mut HackedRaylibHandle,
+    state: &'a mut UiState,
+    layout: Layout,
+    mouse_position: Vector2,
+    mouse_moved: bool,
+    mouse_pressed: bool,
+    /// `-1` or `1` if left or right was pressed, for changing the focused setting
+    horizontal_step: i32,
+    confirm_pressed: bool,
+    back_pressed: bool,
+    /// Id of the next interactive widget to be drawn
+    next_id: usize,
+    /// Color of any text drawn by widgets
+    pub text_color: Color,
+}
+
+impl Ui<'_> {
+    /// Give the next interactive widget an id and check it for input. Returns if it has focus, and if it was clicked
+    fn interact(&mut self, hitbox: Rectangle) -> (bool, bool) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.state.widget_count = self.next_id;
+
+        let hovering = hitbox.check_collision_point_rec(self.mouse_position);
+        let mouse_clicked = hovering && self.mouse_pressed;
+        if (hovering && self.mouse_moved) || mouse_clicked {
+            self.state.focused = Some(id);
+        }
+        let focused = self.state.focused == Some(id);
+        let clicked = mouse_clicked || (focused && self.confirm_pressed);
+        if clicked {
+            self.state.clicked = true;
+        }
+        (focused, clicked)
+    }
+
+    /// Check if the back key or gamepad button was pressed. Screens should treat this like their back button
+    pub fn back_pressed(&mut self) -> bool {
+        if self.back_pressed {
+            self.state.clicked = true;
+        }
+        self.back_pressed
+    }
+
+    /// The layout widgets are scaled by this frame
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// A screen position `offset` design pixels from an anchor. See [`Layout::at`]
+    pub fn at(&self, anchor: Anchor, offset: Vector2) -> Vector2 {
+        self.layout.at(anchor, offset)
+    }
+
+    /// Scale a length from design pixels to screen pixels
+    fn scaled(&self, length: f32) -> f32 {
+        self.layout.scaled(length)
+    }
+
+    /// Find where to draw a line of text so it lines up against `position`. Widgets are drawn from their top left corner
+    pub fn align(
+        &self,
+        position: Vector2,
+        text: &str,
+        font_size: i32,
+        align: TextAlign,
+    ) -> Vector2 {
+        self.raylib
+            .aligned_ui_text_position(text, position, font_size, align)
+    }
+
+    fn text(&mut self, position: Vector2, text: &str, font_size: i32, glitch: bool) {
+        self.raylib
+            .draw_rgb_split_text(position, text, font_size, glitch, self.text_color);
+    }
+
+    /// Draw some text that can not be interacted with
+    pub fn label(&mut self, position: Vector2, text: &str, font_size: i32) {
+        self.text(position, text, font_size, false);
+    }
+
+    /// Draw a screen title that glitches out every so often. `counter` should go up by one every frame
+    pub fn title(&mut self, position: Vector2, text: &str, counter: &mut i32) {
+        let timer: i32 = get_random_value(50, 400);
+        let glitching = *counter > timer;
+        if *counter > timer + 20 {
+            *counter = 0;
+        }
+        self.text(position, text, 70, glitching);
+    }
+
+    /// Draw a button, with a `>>` arrow beside it while focused. Returns `true` when clicked
+    pub fn button(&mut self, position: Vector2, text: &str, font_size: i32) -> bool {
+        // The hitbox covers exactly what is drawn, whatever size the font really draws at
+        let size = self.raylib.measure_ui_text_ex(text, font_size);
+        let (focused, clicked) =
+            self.interact(Rectangle::new(position.x, position.y, size.x, size.y));
+        self.text(position, text, font_size, focused);
+        if focused {
+            let arrow_offset = self.raylib.measure_ui_text(">> ", font_size) as f32;
+            self.text(
+                Vector2::new(position.x - arrow_offset, position.y),
+                ">>",
+                font_size,
+                true,
+            );
+        }
+        clicked
+    }
+
+    /// Draw a column of buttons. Returns the index of the one clicked, if any
+    pub fn list(
+        &mut self,
+        position: Vector2,
+        items: &[&str],
+        font_size: i32,
+        spacing: f32,
+    ) -> Option<usize> {
+        let mut clicked = None;
+        for (idx, item) in items.iter().enumerate() {
+            let item_position =
+                Vector2::new(position.x, position.y + self.scaled(spacing * idx as f32));
+            if self.button(item_position, item, font_size) {
+                clicked = Some(idx);
+            }
+        }
+        clicked
+    }
+
+    /// Draw a setting's label, and register its whole row as one widget. Returns if it has focus, and if it was clicked
+    fn setting_row(&mut self, position: Vector2, label: &str) -> (bool, bool) {
+        let height = self.raylib.measure_ui_text_ex(label, SETTING_FONT_SIZE).y;
+        let (focused, clicked) = self.interact(Rectangle::new(
+            position.x,
+            position.y - self.scaled(5.0),
+            self.scaled(SETTING_VALUE_OFFSET + SETTING_VALUE_WIDTH),
+            height + self.scaled(10.0),
+        ));
+        self.text(position, label, SETTING_FONT_SIZE, focused);
+        if focused {
+            let arrow_offset = self.raylib.measure_ui_text(">> ", SETTING_FONT_SIZE) as f32;
+            self.text(
+                Vector2::new(position.x - arrow_offset, position.y),
+                ">>",
+                SETTING_FONT_SIZE,
+                true,
+            );
+        }
+        (focused, clicked)
+    }
+
+    /// Draw `<` and `>` at either end of a setting's value.
+    /// Returns `-1` or `1` if one was clicked, or if left or right was pressed while the setting is focused
+    fn setting_arrows(&mut self, position: Vector2, focused: bool) -> i32 {
+        let left = Vector2::new(position.x + self.scaled(SETTING_VALUE_OFFSET), position.y);
+        let right = Vector2::new(left.x + self.scaled(SETTING_VALUE_WIDTH - 30.0), position.y);
+        self.text(left, "<", SETTING_FONT_SIZE, focused);
+        self.text(right, ">", SETTING_FONT_SIZE, focused);
+
+        // The arrows are small, so they can be clicked a little way outside of what is drawn
+        let arrow_size = self.raylib.measure_ui_text_ex("<", SETTING_FONT_SIZE);
+        let padding = self.scaled(10.0);
+        let clicked = |x: f32| {
+            self.mouse_pressed
+                && Rectangle::new(
+                    x - padding,
+                    position.y - padding,
+                    arrow_size.x + padding * 2.0,
+                    arrow_size.y + padding * 2.0,
+                )
+                .check_collision_point_rec(self.mouse_position)
+        };
+        let step = if clicked(left.x) {
+            -1
+        } else if clicked(right.x) {
+            1
+        } else if focused {
+            self.horizontal_step
+        } else {
+            0
+        };
+
+        // Clicks are already counted when the row is clicked
+        if focused && self.horizontal_step != 0 {
+            self.state.clicked = true;
+        }
+        step
+    }
+
+    /// Draw a setting that is picked from a list of options with arrows. Returns `true` if it changed
+    pub fn stepper<T: PartialEq + Copy>(
+        &mut self,
+        position: Vector2,
+        label: &str,
+        options: &[T],
+        value: &mut T,
+        format: impl Fn(T) -> String,
+    ) -> bool {
+        let (focused, _) = self.setting_row(position, label);
+        let step = self.setting_arrows(position, focused);
+        self.text(
+            Vector2::new(
+                position.x + self.scaled(SETTING_VALUE_OFFSET + 40.0),
+                position.y,
+            ),
+            &format(*value),
+            SETTING_FONT_SIZE,
+            false,
+        );
+
+        let old_value = *value;
+        *value = step_option(options, *value, step);
+        *value != old_value
+    }
+
+    /// Draw an on / off setting. Clicking anywhere on the row, or pressing left or right while it is focused, flips it.
+    /// Returns `true` if it changed
+    pub fn toggle(&mut self, position: Vector2, label: &str, value: &mut bool) -> bool {
+        let (focused, clicked) = self.setting_row(position, label);
+        let (on_text, off_text) = &self.state.toggle_text;
+        let value_text = format!("[{}]", if *value { on_text } else { off_text });
+        self.text(
+            Vector2::new(position.x + self.scaled(SETTING_VALUE_OFFSET), position.y),
+            &value_text,
+            SETTING_FONT_SIZE,
+            focused,
+        );
+        let changed = clicked || (focused && self.horizontal_step != 0);
+        if changed {
+            self.state.clicked = true;
+            *value = !*value;
+        }
+        changed
+    }
+
+    /// Draw a `0.0` to `1.0` setting as a bar. The arrows move it in steps of 10%, and clicking the bar sets it directly.
+    /// Returns `true` if it changed
+    pub fn slider(&mut self, position: Vector2, label: &str, value: &mut f32) -> bool {
+        let (focused, _) = self.setting_row(position, label);
+        let step = self.setting_arrows(position, focused);
+
+        // Draw the bar, filled up to the value
+        let bar = Rectangle::new(
+            position.x + self.scaled(SETTING_VALUE_OFFSET + 35.0),
+            position.y + self.scaled(3.0),
+            self.scaled(SLIDER_WIDTH),
+            self.scaled(SETTING_FONT_SIZE as f32 - 6.0),
+        );
+        let border = self.scaled(2.0).round() as i32;
+        self.raylib
+            .draw_rectangle_lines_ex(bar, border, self.text_color);
+        self.raylib.draw_rectangle_rec(
+            Rectangle::new(bar.x, bar.y, bar.width * *value, bar.height),
+            self.text_color,
+        );
+        self.text(
+            Vector2::new(bar.x + bar.width + self.scaled(10.0), position.y),
+            &format!("{}%", (*value * 100.0).round()),
+            SETTING_FONT_SIZE,
+            false,
+        );
+
+        let old_value = *value;
+        if self.mouse_pressed && bar.check_collision_point_rec(self.mouse_position) {
+            *value = step_fraction((self.mouse_position.x - bar.x) / bar.width, 0);
+        } else {
+            *value = step_fraction(*value, step);
+        }
+        (*value - old_value).abs() > f32::EPSILON
+    }
+
+    /// Draw a setting whose value is changed somewhere else, like a key binding. Returns `true` when clicked
+    pub fn setting_button(&mut self, position: Vector2, label: &str, value: &str) -> bool {
+        let (focused, clicked) = self.setting_row(position, label);
+        self.text(
+            Vector2::new(position.x + self.scaled(SETTING_VALUE_OFFSET), position.y),
+            value,
+            SETTING_FONT_SIZE,
+            focused,
+        );
+        clicked
+    }
+}
+
+/// Move through a list of options, stopping at either end
+fn step_option<T: PartialEq + Copy>(options: &[T], current: T, step: i32) -> T {
+    if step == 0 {
+        return current;
+    }
+    let idx = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0) as i32;
+    options[(idx + step).clamp(0, options.len() as i32 - 1) as usize]
+}
+
+/// Round a `0.0` to `1.0` value to the nearest 10%, then move it by `step` tenths
+fn step_fraction(value: f32, step: i32) -> f32 {
+    ((value * 10.0).round() + step as f32).clamp(0.0, 10.0) / 10.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_move_focus_wraps() {
+        assert_eq!(move_focus(None, 3, 1), Some(0));
+        assert_eq!(move_focus(None, 3, -1), Some(2));
+        assert_eq!(move_focus(Some(2), 3, 1), Some(0));
+        assert_eq!(move_focus(Some(0), 3, -1), Some(2));
+        assert_eq!(move_focus(Some(1), 3, 0), Some(1));
+        assert_eq!(move_focus(None, 0, 1), None);
+    }
+
+    #[test]
+    fn test_step_option_stops_at_ends() {
+        let options = [30, 60, 120];
+        assert_eq!(step_option(&options, 60, 1), 120);
+        assert_eq!(step_option(&options, 120, 1), 120);
+        assert_eq!(step_option(&options, 30, -1), 30);
+
+        // Values that are not an option are left alone until changed
+        assert_eq!(step_option(&options, 75, 0), 75);
+        assert_eq!(step_option(&options, 75, 1), 60);
+    }
+
+    #[test]
+    fn test_step_fraction() {
+        assert_relative_eq!(step_fraction(0.4, 1), 0.5);
+        assert_relative_eq!(step_fraction(0.96, 1), 1.0);
+        assert_relative_eq!(step_fraction(0.04, -1), 0.0);
+        assert_relative_eq!(step_fraction(0.33, 0), 0.3);
+    }
+}
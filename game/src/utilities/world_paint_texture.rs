@@ -11,6 +11,26 @@ use raylib::{
 
 use super::non_ref_raylib::HackedRaylibHandle;
 
+/// Settings for drawing a `WorldPaintTexture` as one layer of a parallax background
+#[derive(Debug, Clone, Copy)]
+pub struct ParallaxSettings {
+    /// How fast this layer moves relative to the world. `1.0` is locked to the world, and `0.0` is locked to the screen
+    pub scroll_factor: f32,
+    /// Should the texture also be repeated up and down?
+    pub tile_vertically: bool,
+    pub tint: Color,
+}
+
+impl Default for ParallaxSettings {
+    fn default() -> Self {
+        Self {
+            scroll_factor: 1.0,
+            tile_vertically: false,
+            tint: Color::WHITE,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct WorldPaintTexture {
     texture: Texture2D,
@@ -22,36 +42,68 @@ impl WorldPaintTexture {
         Self { texture }
     }
 
+    /// Get the height of the underlying texture
+    pub fn height(&self) -> i32 {
+        self.texture.height
+    }
+
     pub fn render(
         &self,
         raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>,
         origin: Vector2,
         camera: &Camera2D,
     ) {
+        self.render_parallax(raylib, origin, camera, &ParallaxSettings::default());
+    }
+
+    /// Render the texture as a parallax layer. `origin` is where one tile sits when the camera is looking at the world origin
+    pub fn render_parallax(
+        &self,
+        raylib: &mut RaylibMode2D<'_, HackedRaylibHandle>,
+        origin: Vector2,
+        camera: &Camera2D,
+        settings: &ParallaxSettings,
+    ) {
+        // An empty texture would never step past the screen edge
+        if self.texture.width <= 0 || self.texture.height <= 0 {
+            return;
+        }
+
+        // Layers further away than the world get dragged along with the camera
+        let origin = origin + camera.target.scale_by(1.0 - settings.scroll_factor);
+
         // Convert the screen edges to world space
         let top_left = raylib.get_screen_to_world2D(Vector2::new(0.0, 0.0), camera);
         let bottom_right = raylib.get_screen_to_world2D(raylib.get_screen_size(), camera);
 
-        // Calculate the distance between the edges and the origin
-        let left_edge_distance = top_left.x - origin.x;
-        let right_edge_distance = bottom_right.x - origin.x;
+        // Calculate the first tile position needed to cover the left edge, then keep adding tiles until we pass the right edge
+        let tile_width = self.texture.width as f32;
+        let tile_height = self.texture.height as f32;
+        let left_tile_x = origin.x + ((top_left.x - origin.x) / tile_width).floor() * tile_width;
 
-        // Calculate the x position to draw the tile in order for there always to be a tile covering the edges
-        let left_tile_x =
-            (left_edge_distance / self.texture.width as f32).floor() * self.texture.width as f32;
-        let right_tile_x =
-        left_tile_x + self.texture.width as f32;
+        // Vertical tiling works the same way, otherwise there is only one row
+        let (top_tile_y, bottom_edge) = if settings.tile_vertically {
+            (
+                origin.y + ((top_left.y - origin.y) / tile_height).floor() * tile_height,
+                bottom_right.y,
+            )
+        } else {
+            (origin.y, origin.y)
+        };
 
         // Render the tiles
-        raylib.draw_texture_v(
-            &self.texture,
-            Vector2::new(left_tile_x, origin.y),
-            Color::WHITE,
-        );
-        raylib.draw_texture_v(
-            &self.texture,
-            Vector2::new(right_tile_x, origin.y),
-            Color::WHITE,
-        );
+        let mut tile_y = top_tile_y;
+        loop {
+            let mut tile_x = left_tile_x;
+            while tile_x < bottom_right.x {
+                raylib.draw_texture_v(&self.texture, Vector2::new(tile_x, tile_y), settings.tint);
+                tile_x += tile_width;
+            }
+
+            tile_y += tile_height;
+            if tile_y >= bottom_edge {
+                break;
+            }
+        }
     }
 }
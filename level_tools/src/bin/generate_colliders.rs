@@ -0,0 +1,118 @@
+//! Regenerates a level's `colliders.json` and appear/disappear zones from its layer images.
+//!
+//! Usage: `generate_colliders <level directory> [--cell-size N] [--alpha-threshold N] [--coverage F] [--dry-run]`
+//!
+//! Colliders that reach outside of `platforms.png` (like the floor under every level) cannot come from the art,
+//! so they are carried over from the existing `colliders.json`. Kill zones and the win zone are never touched.
+
+use std::{
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use level_tools::{
+    format::{read_json, write_json, LevelZones, Rect},
+    mask::{MaskSettings, SolidMask},
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut level_dir = None;
+    let mut settings = MaskSettings::default();
+    let mut dry_run = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cell-size" => settings.cell_size = parse_next(&mut args, &arg),
+            "--alpha-threshold" => settings.alpha_threshold = parse_next(&mut args, &arg),
+            "--coverage" => settings.coverage = parse_next(&mut args, &arg),
+            "--dry-run" => dry_run = true,
+            _ if level_dir.is_none() => level_dir = Some(PathBuf::from(arg)),
+            _ => usage(),
+        }
+    }
+    let level_dir = level_dir.unwrap_or_else(|| usage());
+
+    if let Err(e) = generate(&level_dir, settings, dry_run) {
+        eprintln!(
+            "Failed to generate colliders for {}: {}",
+            level_dir.display(),
+            e
+        );
+        exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: generate_colliders <level directory> [--cell-size N] [--alpha-threshold N] [--coverage F] [--dry-run]");
+    exit(2);
+}
+
+fn parse_next<T>(args: &mut impl Iterator<Item = String>, flag: &str) -> T
+where
+    T: std::str::FromStr,
+{
+    args.next()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| {
+            eprintln!("{} needs a valid value", flag);
+            usage()
+        })
+}
+
+/// Build the rectangles for one layer image
+fn rects_from_layer(
+    path: &Path,
+    settings: MaskSettings,
+) -> image::ImageResult<(Vec<Rect>, u32, u32)> {
+    let image = image::open(path)?.into_rgba8();
+    let rects = SolidMask::from_image(&image, settings).to_rects();
+    Ok((rects, image.width(), image.height()))
+}
+
+fn generate(
+    level_dir: &Path,
+    settings: MaskSettings,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Build the solid platforms, keeping any hand-placed colliders that go past the edges of the art
+    let (platform_rects, width, height) =
+        rects_from_layer(&level_dir.join("platforms.png"), settings)?;
+    let colliders_path = level_dir.join("colliders.json");
+    let old_colliders: Vec<Rect> = if colliders_path.exists() {
+        read_json(&colliders_path)?
+    } else {
+        Vec::new()
+    };
+    let mut colliders: Vec<Rect> = old_colliders
+        .iter()
+        .filter(|rect| !rect.is_inside(width as i32, height as i32))
+        .copied()
+        .collect();
+    let kept_colliders = colliders.len();
+    colliders.extend(platform_rects);
+
+    // Rebuild the appearing and disappearing zones
+    let zones_path = level_dir.join("zones.json");
+    let mut zones: LevelZones = read_json(&zones_path)?;
+    zones.appear = rects_from_layer(&level_dir.join("appearing_platforms.png"), settings)?.0;
+    zones.disappear = rects_from_layer(&level_dir.join("disappearing_platforms.png"), settings)?.0;
+
+    println!(
+        "{}: {} colliders ({} kept from the old file, was {}), {} appear zones, {} disappear zones",
+        level_dir.display(),
+        colliders.len(),
+        kept_colliders,
+        old_colliders.len(),
+        zones.appear.len(),
+        zones.disappear.len()
+    );
+
+    if dry_run {
+        println!("Dry run, not writing any files");
+    } else {
+        write_json(&colliders_path, &colliders)?;
+        write_json(&zones_path, &zones)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Exports a level as a single colour-coded image for design review.
+//!
+//! Usage: `level_overview <level directory> <output.png|output.svg> [--scale F] [--deaths FILE]`
+//!
+//! The background and platform layers are drawn the same way the game lines them up, with colliders (green),
+//! appear zones (blue), disappear zones (yellow), kill zones (red), and the win zone (white) on top.
+//! SVG output keeps every zone as a separate labelled shape, with its coordinates shown on hover.
+//!
+//! If `--deaths` is given, it should point to a JSON list of `{"x": .., "y": ..}` points in level space,
+//! which will be drawn as a heatmap over the art.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use level_tools::{
+    format::read_json,
+    overview::{DeathPoint, Overview},
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut positional = Vec::new();
+    let mut scale = 0.5;
+    let mut deaths_path = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--scale" => {
+                scale = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or_else(|| usage())
+            }
+            "--deaths" => deaths_path = Some(PathBuf::from(args.next().unwrap_or_else(|| usage()))),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+    if positional.len() != 2 {
+        usage();
+    }
+
+    if let Err(e) = export(
+        &positional[0],
+        &positional[1],
+        scale,
+        deaths_path.as_deref(),
+    ) {
+        eprintln!("Failed to export {}: {}", positional[0].display(), e);
+        exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: level_overview <level directory> <output.png|output.svg> [--scale F] [--deaths FILE]");
+    exit(2);
+}
+
+fn export(
+    level_dir: &Path,
+    output: &Path,
+    scale: f32,
+    deaths_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let deaths: Vec<DeathPoint> = match deaths_path {
+        Some(path) => read_json(path)?,
+        None => Vec::new(),
+    };
+    let overview = Overview::build(level_dir, &deaths, scale)?;
+
+    match output.extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => fs::write(output, overview.to_svg()?)?,
+        Some("png") => overview.to_png().save(output)?,
+        _ => return Err("Output must end in .png or .svg".into()),
+    }
+    println!("Wrote {}", output.display());
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Mirrors of the JSON files that make up a level. The game's level editor writes its files through these too
+
+use std::{fs, io, path::Path};
+
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Map, Value};
+
+/// A rectangle in level space. This matches the JSON layout of raylib's `Rectangle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    /// Check if this rectangle sits fully inside a `width` by `height` area starting at the origin
+    pub fn is_inside(&self, width: i32, height: i32) -> bool {
+        self.x >= 0 && self.y >= 0 && self.x + self.width <= width && self.y + self.height <= height
+    }
+}
+
+/// The contents of a level's `zones.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelZones {
+    pub appear: Vec<Rect>,
+    pub disappear: Vec<Rect>,
+    pub kill: Vec<Rect>,
+    pub win: Rect,
+    /// X positions of the lines that split the level into timed sections, from left to right
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub splits: Vec<i32>,
+
+    /// Any fields this tool does not know about. These are written back untouched
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Read and parse a JSON file
+pub fn read_json<T>(path: &Path) -> io::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Write a JSON file using the same 4-space indentation as the hand-written level files
+pub fn write_json<T>(path: &Path, value: &T) -> io::Result<()>
+where
+    T: Serialize,
+{
+    let mut output = Vec::new();
+    let mut serializer =
+        serde_json::Serializer::with_formatter(&mut output, PrettyFormatter::with_indent(b"    "));
+    value.serialize(&mut serializer)?;
+    output.push(b'\n');
+    fs::write(path, output)
+}
@@ -0,0 +1,10 @@
+//! Offline tools for working with the game's level files.
+//!
+//! These are kept out of the `game` crate so they can run without a window, raylib, or any embedded assets.
+
+#[macro_use]
+extern crate serde;
+
+pub mod format;
+pub mod mask;
+pub mod overview;
@@ -0,0 +1,183 @@
+//! Converts the alpha channel of a layer image into a small set of rectangles
+
+use image::RgbaImage;
+
+use crate::format::Rect;
+
+/// Settings for deciding which parts of an image are solid
+#[derive(Debug, Clone, Copy)]
+pub struct MaskSettings {
+    /// Size of one grid cell in pixels. Level art is drawn on a 32px tile grid
+    pub cell_size: u32,
+    /// Pixels with an alpha at or above this are counted as solid
+    pub alpha_threshold: u8,
+    /// How much of a cell (`0.0` to `1.0`) must be solid for the whole cell to be solid
+    pub coverage: f32,
+}
+
+impl Default for MaskSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 32,
+            alpha_threshold: 128,
+            coverage: 0.5,
+        }
+    }
+}
+
+/// A grid of solid and empty cells covering an image
+#[derive(Debug)]
+pub struct SolidMask {
+    pub columns: u32,
+    pub rows: u32,
+    cell_size: u32,
+    image_width: u32,
+    image_height: u32,
+    cells: Vec<bool>,
+}
+
+impl SolidMask {
+    /// Build a mask from an image's alpha channel
+    pub fn from_image(image: &RgbaImage, settings: MaskSettings) -> Self {
+        let cell_size = settings.cell_size.max(1);
+        let columns = (image.width() + cell_size - 1) / cell_size;
+        let rows = (image.height() + cell_size - 1) / cell_size;
+
+        // Count the solid pixels in every cell
+        let mut solid_counts = vec![0_u32; (columns * rows) as usize];
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if pixel[3] >= settings.alpha_threshold {
+                solid_counts[((y / cell_size) * columns + (x / cell_size)) as usize] += 1;
+            }
+        }
+
+        // Cells on the right and bottom edges may be cut off by the image bounds
+        let mut cells = Vec::with_capacity(solid_counts.len());
+        for row in 0..rows {
+            for column in 0..columns {
+                let cell_width = cell_size.min(image.width() - column * cell_size);
+                let cell_height = cell_size.min(image.height() - row * cell_size);
+                let count = solid_counts[(row * columns + column) as usize];
+                cells.push(
+                    count > 0
+                        && count as f32 >= (cell_width * cell_height) as f32 * settings.coverage,
+                );
+            }
+        }
+
+        Self {
+            columns,
+            rows,
+            cell_size,
+            image_width: image.width(),
+            image_height: image.height(),
+            cells,
+        }
+    }
+
+    /// Check if a cell is solid
+    pub fn is_solid(&self, column: u32, row: u32) -> bool {
+        self.cells[(row * self.columns + column) as usize]
+    }
+
+    /// Greedily merge the solid cells into as few rectangles as possible.
+    ///
+    /// Runs are grown horizontally first, since level geometry is mostly wide platforms.
+    pub fn to_rects(&self) -> Vec<Rect> {
+        let mut used = vec![false; self.cells.len()];
+        let is_free = |used: &Vec<bool>, column: u32, row: u32| {
+            self.is_solid(column, row) && !used[(row * self.columns + column) as usize]
+        };
+
+        let mut rects = Vec::new();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                if !is_free(&used, column, row) {
+                    continue;
+                }
+
+                // Grow to the right
+                let mut width = 1;
+                while column + width < self.columns && is_free(&used, column + width, row) {
+                    width += 1;
+                }
+
+                // Grow downwards for as long as the whole run is free
+                let mut height = 1;
+                while row + height < self.rows
+                    && (column..column + width).all(|c| is_free(&used, c, row + height))
+                {
+                    height += 1;
+                }
+
+                for r in row..row + height {
+                    for c in column..column + width {
+                        used[(r * self.columns + c) as usize] = true;
+                    }
+                }
+
+                // Convert back to pixels, keeping the rect inside the image
+                let x = column * self.cell_size;
+                let y = row * self.cell_size;
+                rects.push(Rect {
+                    x: x as i32,
+                    y: y as i32,
+                    width: ((column + width) * self.cell_size).min(self.image_width) as i32
+                        - x as i32,
+                    height: ((row + height) * self.cell_size).min(self.image_height) as i32
+                        - y as i32,
+                });
+            }
+        }
+        rects
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::Rgba;
+
+    fn paint(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32) {
+        for px in x..x + width {
+            for py in y..y + height {
+                image.put_pixel(px, py, Rgba([255, 255, 255, 255]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merges_platform_into_one_rect() {
+        let mut image = RgbaImage::new(256, 128);
+        paint(&mut image, 32, 64, 160, 32);
+        let mask = SolidMask::from_image(&image, MaskSettings::default());
+        assert_eq!(
+            mask.to_rects(),
+            vec![Rect {
+                x: 32,
+                y: 64,
+                width: 160,
+                height: 32
+            }]
+        );
+    }
+
+    #[test]
+    fn test_ignores_thin_antialiasing() {
+        let mut image = RgbaImage::new(128, 128);
+        paint(&mut image, 0, 0, 64, 32);
+        paint(&mut image, 64, 0, 2, 32);
+        let mask = SolidMask::from_image(&image, MaskSettings::default());
+        assert_eq!(mask.to_rects().len(), 1);
+        assert_eq!(mask.to_rects()[0].width, 64);
+    }
+
+    #[test]
+    fn test_l_shape_uses_two_rects() {
+        let mut image = RgbaImage::new(128, 128);
+        paint(&mut image, 0, 0, 96, 32);
+        paint(&mut image, 0, 32, 32, 64);
+        let mask = SolidMask::from_image(&image, MaskSettings::default());
+        assert_eq!(mask.to_rects().len(), 2);
+    }
+}
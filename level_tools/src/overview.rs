@@ -0,0 +1,390 @@
+//! Composes a level's art and gameplay data into a single annotated image for design review
+
+use std::{error::Error, path::Path};
+
+use image::{imageops, DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+
+use crate::format::{read_json, LevelZones, Rect};
+
+/// Horizontal distance between the world origin and the start of a level.
+/// This mirrors `WORLD_LEVEL_X_OFFSET` in the game, and is needed to line up the background
+pub const WORLD_LEVEL_X_OFFSET: i32 = 200;
+
+/// Radius (in level pixels) of the blob drawn for every death
+const DEATH_RADIUS: f32 = 48.0;
+
+/// Every kind of gameplay data that gets drawn on top of the art
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    Collider,
+    Kill,
+    Appear,
+    Disappear,
+    Win,
+}
+
+impl AnnotationKind {
+    /// All kinds, in the order they should be drawn
+    pub const ALL: [AnnotationKind; 5] = [
+        AnnotationKind::Collider,
+        AnnotationKind::Appear,
+        AnnotationKind::Disappear,
+        AnnotationKind::Kill,
+        AnnotationKind::Win,
+    ];
+
+    /// The colour used for this kind. These come from the game's colour theme
+    pub fn color(&self) -> Rgba<u8> {
+        match self {
+            AnnotationKind::Collider => Rgba([61, 227, 161, 255]),
+            AnnotationKind::Kill => Rgba([240, 70, 53, 255]),
+            AnnotationKind::Appear => Rgba([101, 75, 250, 255]),
+            AnnotationKind::Disappear => Rgba([250, 235, 55, 255]),
+            AnnotationKind::Win => Rgba([240, 246, 227, 255]),
+        }
+    }
+
+    /// A short name for this kind
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnnotationKind::Collider => "collider",
+            AnnotationKind::Kill => "kill",
+            AnnotationKind::Appear => "appear",
+            AnnotationKind::Disappear => "disappear",
+            AnnotationKind::Win => "win",
+        }
+    }
+}
+
+/// A single rectangle of gameplay data in level space
+#[derive(Debug, Clone, Copy)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    pub rect: Rect,
+}
+
+/// A point where the player died, in level space
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DeathPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A level overview, ready to be exported
+#[derive(Debug)]
+pub struct Overview {
+    /// The background, platform layers, and death heatmap, already scaled
+    base: RgbaImage,
+    annotations: Vec<Annotation>,
+    /// Where level space `y = 0` sits in the unscaled canvas
+    origin_y: i32,
+    scale: f32,
+}
+
+impl Overview {
+    /// Build an overview of the level in `level_dir`
+    pub fn build(
+        level_dir: &Path,
+        deaths: &[DeathPoint],
+        scale: f32,
+    ) -> Result<Self, Box<dyn Error>> {
+        let colliders: Vec<Rect> = read_json(&level_dir.join("colliders.json"))?;
+        let zones: LevelZones = read_json(&level_dir.join("zones.json"))?;
+        let platforms = image::open(level_dir.join("platforms.png"))?.into_rgba8();
+        let appearing = image::open(level_dir.join("appearing_platforms.png"))?.into_rgba8();
+        let disappearing = image::open(level_dir.join("disappearing_platforms.png"))?.into_rgba8();
+        let background = image::open(level_dir.join("background.png"))?.into_rgba8();
+
+        // In game, every layer is drawn with its bottom edge on the floor.
+        // Level space starts at the top of the platform layer, so taller layers poke out above it
+        let layers = [&platforms, &appearing, &disappearing];
+        let canvas_height = layers.iter().map(|layer| layer.height()).max().unwrap_or(0);
+        let origin_y = canvas_height as i32 - platforms.height() as i32;
+        let canvas_width = layers
+            .iter()
+            .map(|layer| layer.width() as i32)
+            .chain(std::iter::once(zones.win.x + zones.win.width))
+            .max()
+            .unwrap_or(0) as u32;
+        let mut canvas = RgbaImage::new(canvas_width, canvas_height);
+
+        // Tile the background along the floor, lined up the same way it is in game
+        let mut tile_x = -WORLD_LEVEL_X_OFFSET;
+        while tile_x < canvas_width as i32 {
+            overlay_at(
+                &mut canvas,
+                &background,
+                tile_x,
+                canvas_height as i32 - background.height() as i32,
+                1.0,
+            );
+            tile_x += background.width() as i32;
+        }
+
+        // The platform layers. Appearing and disappearing platforms are only partially visible in game, so show them faded
+        overlay_at(
+            &mut canvas,
+            &platforms,
+            0,
+            canvas_height as i32 - platforms.height() as i32,
+            1.0,
+        );
+        overlay_at(
+            &mut canvas,
+            &appearing,
+            0,
+            canvas_height as i32 - appearing.height() as i32,
+            0.6,
+        );
+        overlay_at(
+            &mut canvas,
+            &disappearing,
+            0,
+            canvas_height as i32 - disappearing.height() as i32,
+            0.6,
+        );
+
+        // Shrink the art before drawing anything else so lines stay crisp
+        let scale = scale.max(0.01);
+        let mut base = imageops::resize(
+            &canvas,
+            ((canvas_width as f32 * scale).round() as u32).max(1),
+            ((canvas_height as f32 * scale).round() as u32).max(1),
+            imageops::FilterType::Triangle,
+        );
+        draw_heatmap(&mut base, deaths, origin_y, scale);
+
+        // Collect all the gameplay data
+        let mut annotations = Vec::new();
+        for (kind, rects) in [
+            (AnnotationKind::Collider, &colliders),
+            (AnnotationKind::Appear, &zones.appear),
+            (AnnotationKind::Disappear, &zones.disappear),
+            (AnnotationKind::Kill, &zones.kill),
+        ] {
+            annotations.extend(rects.iter().map(|rect| Annotation { kind, rect: *rect }));
+        }
+        annotations.push(Annotation {
+            kind: AnnotationKind::Win,
+            rect: zones.win,
+        });
+
+        Ok(Self {
+            base,
+            annotations,
+            origin_y,
+            scale,
+        })
+    }
+
+    /// Convert a level space rectangle to pixel bounds on the scaled output
+    fn to_output_space(&self, rect: &Rect) -> (f32, f32, f32, f32) {
+        (
+            rect.x as f32 * self.scale,
+            (rect.y + self.origin_y) as f32 * self.scale,
+            rect.width as f32 * self.scale,
+            rect.height as f32 * self.scale,
+        )
+    }
+
+    /// Render the overview as a raster image
+    pub fn to_png(&self) -> RgbaImage {
+        let mut image = self.base.clone();
+        for kind in AnnotationKind::ALL.iter() {
+            for annotation in self.annotations.iter().filter(|a| a.kind == *kind) {
+                let (x, y, width, height) = self.to_output_space(&annotation.rect);
+                let color = kind.color();
+                fill_rect(&mut image, x, y, width, height, color, 0.25);
+                outline_rect(&mut image, x, y, width, height, color);
+            }
+        }
+        image
+    }
+
+    /// Render the overview as an SVG. The art is embedded as a PNG, and all gameplay data is drawn as labelled vectors
+    pub fn to_svg(&self) -> Result<String, Box<dyn Error>> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(self.base.clone()).write_to(&mut png, ImageOutputFormat::Png)?;
+
+        let (width, height) = self.base.dimensions();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = width,
+            h = height
+        );
+        svg.push_str(&format!(
+            "  <image width=\"{}\" height=\"{}\" href=\"data:image/png;base64,{}\"/>\n",
+            width,
+            height,
+            base64_encode(&png)
+        ));
+
+        for kind in AnnotationKind::ALL.iter() {
+            let color = kind.color();
+            svg.push_str(&format!(
+                "  <g id=\"{}\" fill=\"rgb({r},{g},{b})\" fill-opacity=\"0.25\" stroke=\"rgb({r},{g},{b})\" stroke-width=\"2\">\n",
+                kind.label(),
+                r = color[0],
+                g = color[1],
+                b = color[2]
+            ));
+            for annotation in self.annotations.iter().filter(|a| a.kind == *kind) {
+                let (x, y, w, h) = self.to_output_space(&annotation.rect);
+                let rect = annotation.rect;
+                svg.push_str(&format!(
+                    "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\"><title>{} ({}, {}) {}x{}</title></rect>\n",
+                    x, y, w, h, kind.label(), rect.x, rect.y, rect.width, rect.height
+                ));
+            }
+            svg.push_str("  </g>\n");
+        }
+
+        // Legend
+        svg.push_str("  <g font-family=\"monospace\" font-size=\"14\">\n");
+        for (i, kind) in AnnotationKind::ALL.iter().enumerate() {
+            let color = kind.color();
+            svg.push_str(&format!(
+                "    <text x=\"10\" y=\"{}\" fill=\"rgb({},{},{})\">{}</text>\n",
+                20 + i * 18,
+                color[0],
+                color[1],
+                color[2],
+                kind.label()
+            ));
+        }
+        svg.push_str("  </g>\n</svg>\n");
+        Ok(svg)
+    }
+}
+
+/// Alpha-blend a single pixel onto an image. Out of bounds pixels are ignored
+fn blend_pixel(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, opacity: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return;
+    }
+    let alpha = (color[3] as f32 / 255.0) * opacity;
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        pixel[channel] =
+            (pixel[channel] as f32 * (1.0 - alpha) + color[channel] as f32 * alpha).round() as u8;
+    }
+    pixel[3] = (pixel[3] as f32 + (255.0 - pixel[3] as f32) * alpha).round() as u8;
+}
+
+/// Draw one image on top of another
+fn overlay_at(canvas: &mut RgbaImage, image: &RgbaImage, x: i32, y: i32, opacity: f32) {
+    for (px, py, pixel) in image.enumerate_pixels() {
+        if pixel[3] > 0 {
+            blend_pixel(canvas, x + px as i32, y + py as i32, *pixel, opacity);
+        }
+    }
+}
+
+fn fill_rect(
+    image: &mut RgbaImage,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    color: Rgba<u8>,
+    opacity: f32,
+) {
+    // Clip to the image first, as some colliders (like the floor) are far wider than the level
+    let left = x.max(0.0).round() as i32;
+    let top = y.max(0.0).round() as i32;
+    let right = (x + width).min(image.width() as f32).round() as i32;
+    let bottom = (y + height).min(image.height() as f32).round() as i32;
+    for py in top..bottom {
+        for px in left..right {
+            blend_pixel(image, px, py, color, opacity);
+        }
+    }
+}
+
+fn outline_rect(image: &mut RgbaImage, x: f32, y: f32, width: f32, height: f32, color: Rgba<u8>) {
+    fill_rect(image, x, y, width, 2.0, color, 1.0);
+    fill_rect(image, x, y + height - 2.0, width, 2.0, color, 1.0);
+    fill_rect(image, x, y, 2.0, height, color, 1.0);
+    fill_rect(image, x + width - 2.0, y, 2.0, height, color, 1.0);
+}
+
+/// Draw a red heatmap of every death on top of an already scaled image
+fn draw_heatmap(image: &mut RgbaImage, deaths: &[DeathPoint], origin_y: i32, scale: f32) {
+    if deaths.is_empty() {
+        return;
+    }
+
+    // Accumulate a density value for every pixel
+    let (width, height) = image.dimensions();
+    let mut density = vec![0.0_f32; (width * height) as usize];
+    let radius = DEATH_RADIUS * scale;
+    for death in deaths {
+        let cx = death.x * scale;
+        let cy = (death.y + origin_y as f32) * scale;
+        let min_x = (cx - radius).floor().max(0.0) as u32;
+        let max_x = (cx + radius).ceil().min(width as f32) as u32;
+        let min_y = (cy - radius).floor().max(0.0) as u32;
+        let max_y = (cy + radius).ceil().min(height as f32) as u32;
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let distance = ((px as f32 - cx).powi(2) + (py as f32 - cy).powi(2)).sqrt();
+                if distance < radius {
+                    density[(py * width + px) as usize] += 1.0 - (distance / radius);
+                }
+            }
+        }
+    }
+
+    // Normalize against the hottest spot
+    let max_density = density.iter().cloned().fold(0.0_f32, f32::max);
+    if max_density <= 0.0 {
+        return;
+    }
+    for (i, value) in density.iter().enumerate() {
+        if *value > 0.0 {
+            blend_pixel(
+                image,
+                (i as u32 % width) as i32,
+                (i as u32 / width) as i32,
+                Rgba([255, 0, 0, 255]),
+                (value / max_density) * 0.75,
+            );
+        }
+    }
+}
+
+/// Standard base64 with padding. This is only used to embed the art into SVG files
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let combined = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(combined >> (18 - i * 6) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+}